@@ -0,0 +1,350 @@
+#[cfg(not(feature = "library"))]
+use cosmwasm_std::entry_point;
+
+use crate::state::{
+    read_claimed, read_config, read_latest_stage, read_stage, store_claimed, store_config,
+    store_latest_stage, store_stage, Config, Stage, StageAsset,
+};
+
+use beth::airdrop::{
+    decode_hash, verify_proof, AirdropAsset, ConfigResponse, Cw20HookMsg, ExecuteMsg,
+    InstantiateMsg, IsClaimedResponse, LatestStageResponse, MigrateMsg, QueryMsg, StageResponse,
+};
+use beth::deduct_tax;
+use beth::migration::migrate_contract_version;
+use cosmwasm_std::{
+    attr, from_binary, to_binary, Addr, BankMsg, Binary, Coin, CosmosMsg, Deps, DepsMut, Env,
+    MessageInfo, Response, StdError, StdResult, Uint128, WasmMsg,
+};
+use cw2::set_contract_version;
+use cw20::{Cw20ExecuteMsg, Cw20ReceiveMsg, Expiration};
+
+// version info for migration info
+const CONTRACT_NAME: &str = "crates.io:anchor_beth_airdrop";
+const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn instantiate(
+    deps: DepsMut,
+    _env: Env,
+    _info: MessageInfo,
+    msg: InstantiateMsg,
+) -> StdResult<Response> {
+    let conf = Config {
+        owner: deps.api.addr_canonicalize(&msg.owner)?,
+    };
+
+    store_config(deps.storage, &conf)?;
+    store_latest_stage(deps.storage, 0)?;
+    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+
+    Ok(Response::default())
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn execute(deps: DepsMut, env: Env, info: MessageInfo, msg: ExecuteMsg) -> StdResult<Response> {
+    match msg {
+        ExecuteMsg::Receive(msg) => receive_cw20(deps, env, info, msg),
+        ExecuteMsg::UpdateConfig { owner } => execute_update_config(deps, info, owner),
+        ExecuteMsg::RegisterMerkleRoot {
+            merkle_root,
+            expiration,
+        } => {
+            assert_owner(deps.as_ref(), info.sender.as_str())?;
+            let fund = match info.funds.as_slice() {
+                [fund] if !fund.amount.is_zero() => fund.clone(),
+                _ => {
+                    return Err(StdError::generic_err(
+                        "Fund the stage with a single native coin",
+                    ))
+                }
+            };
+            register_merkle_root(
+                deps,
+                env,
+                merkle_root,
+                expiration,
+                StageAsset::Native { denom: fund.denom },
+                fund.amount,
+            )
+        }
+        ExecuteMsg::WithdrawUnclaimed { stage, recipient } => {
+            execute_withdraw_unclaimed(deps, env, info, stage, recipient)
+        }
+        ExecuteMsg::Claim {
+            stage,
+            amount,
+            proof,
+        } => execute_claim(deps, env, info, stage, amount, proof),
+    }
+}
+
+pub fn receive_cw20(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    cw20_msg: Cw20ReceiveMsg,
+) -> StdResult<Response> {
+    match from_binary(&cw20_msg.msg) {
+        Ok(Cw20HookMsg::RegisterMerkleRoot {
+            merkle_root,
+            expiration,
+        }) => {
+            // only the owner can fund a stage
+            assert_owner(deps.as_ref(), &cw20_msg.sender)?;
+            if cw20_msg.amount.is_zero() {
+                return Err(StdError::generic_err(
+                    "Fund the stage with a non zero amount",
+                ));
+            }
+
+            let contract_addr = deps.api.addr_canonicalize(info.sender.as_str())?;
+            register_merkle_root(
+                deps,
+                env,
+                merkle_root,
+                expiration,
+                StageAsset::Token { contract_addr },
+                cw20_msg.amount,
+            )
+        }
+        Err(err) => Err(err),
+    }
+}
+
+fn register_merkle_root(
+    deps: DepsMut,
+    env: Env,
+    merkle_root: String,
+    expiration: Option<Expiration>,
+    asset: StageAsset,
+    total_amount: Uint128,
+) -> StdResult<Response> {
+    decode_hash(&merkle_root)?;
+    let expiration = expiration.unwrap_or(Expiration::Never {});
+    if expiration.is_expired(&env.block) {
+        return Err(StdError::generic_err("Expiration is in the past"));
+    }
+
+    let stage_id = read_latest_stage(deps.storage)?
+        .checked_add(1)
+        .ok_or_else(|| StdError::generic_err("No stage left to register"))?;
+    store_stage(
+        deps.storage,
+        stage_id,
+        &Stage {
+            merkle_root: merkle_root.clone(),
+            asset,
+            total_amount,
+            claimed_amount: Uint128::zero(),
+            expiration,
+        },
+    )?;
+    store_latest_stage(deps.storage, stage_id)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "register_merkle_root"),
+        attr("stage", stage_id.to_string()),
+        attr("merkle_root", merkle_root),
+        attr("total_amount", total_amount),
+    ]))
+}
+
+pub fn execute_claim(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    stage_id: u8,
+    amount: Uint128,
+    proof: Vec<String>,
+) -> StdResult<Response> {
+    let mut stage = load_stage(deps.as_ref(), stage_id)?;
+    if stage.expiration.is_expired(&env.block) {
+        return Err(StdError::generic_err(format!(
+            "Airdrop stage {} expired",
+            stage_id
+        )));
+    }
+
+    let sender_raw = deps.api.addr_canonicalize(info.sender.as_str())?;
+    if read_claimed(deps.storage, stage_id, &sender_raw)? {
+        return Err(StdError::generic_err("Already claimed"));
+    }
+    if !verify_proof(&stage.merkle_root, info.sender.as_str(), amount, &proof)? {
+        return Err(StdError::generic_err("Verification failed"));
+    }
+
+    stage.claimed_amount = stage.claimed_amount.checked_add(amount)?;
+    if stage.claimed_amount > stage.total_amount {
+        return Err(StdError::generic_err("Not enough funds left in the stage"));
+    }
+    store_stage(deps.storage, stage_id, &stage)?;
+    store_claimed(deps.storage, stage_id, &sender_raw)?;
+
+    Ok(Response::new()
+        .add_message(payout_msg(
+            deps.as_ref(),
+            &stage.asset,
+            &info.sender,
+            amount,
+        )?)
+        .add_attributes(vec![
+            attr("action", "claim"),
+            attr("stage", stage_id.to_string()),
+            attr("address", info.sender),
+            attr("amount", amount),
+        ]))
+}
+
+pub fn execute_withdraw_unclaimed(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    stage_id: u8,
+    recipient: Option<String>,
+) -> StdResult<Response> {
+    assert_owner(deps.as_ref(), info.sender.as_str())?;
+    let recipient = match recipient {
+        Some(recipient) => deps.api.addr_validate(&recipient)?,
+        None => info.sender,
+    };
+
+    let mut stage = load_stage(deps.as_ref(), stage_id)?;
+    if !stage.expiration.is_expired(&env.block) {
+        return Err(StdError::generic_err(format!(
+            "Airdrop stage {} has not expired",
+            stage_id
+        )));
+    }
+
+    let unclaimed = stage.total_amount.checked_sub(stage.claimed_amount)?;
+    if unclaimed.is_zero() {
+        return Err(StdError::generic_err("No unclaimed funds to withdraw"));
+    }
+    stage.claimed_amount = stage.total_amount;
+    store_stage(deps.storage, stage_id, &stage)?;
+
+    Ok(Response::new()
+        .add_message(payout_msg(
+            deps.as_ref(),
+            &stage.asset,
+            &recipient,
+            unclaimed,
+        )?)
+        .add_attributes(vec![
+            attr("action", "withdraw_unclaimed"),
+            attr("stage", stage_id.to_string()),
+            attr("recipient", recipient),
+            attr("amount", unclaimed),
+        ]))
+}
+
+pub fn execute_update_config(
+    deps: DepsMut,
+    info: MessageInfo,
+    owner: Option<String>,
+) -> StdResult<Response> {
+    assert_owner(deps.as_ref(), info.sender.as_str())?;
+    let mut config = read_config(deps.storage)?;
+
+    if let Some(owner) = owner {
+        config.owner = deps.api.addr_canonicalize(&owner)?;
+    }
+
+    store_config(deps.storage, &config)?;
+
+    Ok(Response::new().add_attributes(vec![attr("action", "update_config")]))
+}
+
+fn assert_owner(deps: Deps, sender: &str) -> StdResult<()> {
+    let config = read_config(deps.storage)?;
+    if config.owner != deps.api.addr_canonicalize(sender)? {
+        return Err(StdError::generic_err("unauthorized"));
+    }
+    Ok(())
+}
+
+fn load_stage(deps: Deps, stage_id: u8) -> StdResult<Stage> {
+    read_stage(deps.storage, stage_id)?
+        .ok_or_else(|| StdError::generic_err(format!("Airdrop stage {} does not exist", stage_id)))
+}
+
+/// Native payouts are sent net of tax
+fn payout_msg(
+    deps: Deps,
+    asset: &StageAsset,
+    recipient: &Addr,
+    amount: Uint128,
+) -> StdResult<CosmosMsg> {
+    Ok(match asset {
+        StageAsset::Native { denom } => BankMsg::Send {
+            to_address: recipient.to_string(),
+            amount: vec![deduct_tax(
+                deps,
+                Coin {
+                    denom: denom.clone(),
+                    amount,
+                },
+            )?],
+        }
+        .into(),
+        StageAsset::Token { contract_addr } => WasmMsg::Execute {
+            contract_addr: deps.api.addr_humanize(contract_addr)?.to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: recipient.to_string(),
+                amount,
+            })?,
+            funds: vec![],
+        }
+        .into(),
+    })
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
+    match msg {
+        QueryMsg::Config {} => to_binary(&query_config(deps)?),
+        QueryMsg::LatestStage {} => to_binary(&LatestStageResponse {
+            latest_stage: read_latest_stage(deps.storage)?,
+        }),
+        QueryMsg::Stage { stage } => to_binary(&query_stage(deps, stage)?),
+        QueryMsg::IsClaimed { stage, address } => {
+            let address_raw = deps.api.addr_canonicalize(&address)?;
+            to_binary(&IsClaimedResponse {
+                is_claimed: read_claimed(deps.storage, stage, &address_raw)?,
+            })
+        }
+    }
+}
+
+fn query_config(deps: Deps) -> StdResult<ConfigResponse> {
+    let config = read_config(deps.storage)?;
+    Ok(ConfigResponse {
+        owner: deps.api.addr_humanize(&config.owner)?.to_string(),
+    })
+}
+
+fn query_stage(deps: Deps, stage_id: u8) -> StdResult<StageResponse> {
+    let stage = load_stage(deps, stage_id)?;
+    let asset = match stage.asset {
+        StageAsset::Native { denom } => AirdropAsset::Native { denom },
+        StageAsset::Token { contract_addr } => AirdropAsset::Token {
+            contract_addr: deps.api.addr_humanize(&contract_addr)?.to_string(),
+        },
+    };
+    Ok(StageResponse {
+        stage: stage_id,
+        merkle_root: stage.merkle_root,
+        asset,
+        total_amount: stage.total_amount,
+        claimed_amount: stage.claimed_amount,
+        expiration: stage.expiration,
+    })
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn migrate(deps: DepsMut, _env: Env, _msg: MigrateMsg) -> StdResult<Response> {
+    migrate_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION, &[])?;
+
+    Ok(Response::default())
+}
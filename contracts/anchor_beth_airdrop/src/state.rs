@@ -0,0 +1,76 @@
+use cosmwasm_std::{CanonicalAddr, StdResult, Storage, Uint128};
+use cosmwasm_storage::{bucket, bucket_read, singleton, singleton_read, Bucket, ReadonlyBucket};
+use cw20::Expiration;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+pub static KEY_CONFIG: &[u8] = b"config";
+pub static KEY_LATEST_STAGE: &[u8] = b"latest_stage";
+
+pub static PREFIX_STAGES: &[u8] = b"stages";
+pub static PREFIX_CLAIMS: &[u8] = b"claims";
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Config {
+    pub owner: CanonicalAddr,
+}
+
+pub fn store_config(storage: &mut dyn Storage, config: &Config) -> StdResult<()> {
+    singleton(storage, KEY_CONFIG).save(config)
+}
+
+pub fn read_config(storage: &dyn Storage) -> StdResult<Config> {
+    singleton_read(storage, KEY_CONFIG).load()
+}
+
+pub fn store_latest_stage(storage: &mut dyn Storage, stage: u8) -> StdResult<()> {
+    singleton(storage, KEY_LATEST_STAGE).save(&stage)
+}
+
+pub fn read_latest_stage(storage: &dyn Storage) -> StdResult<u8> {
+    singleton_read(storage, KEY_LATEST_STAGE).load()
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum StageAsset {
+    Native { denom: String },
+    Token { contract_addr: CanonicalAddr },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Stage {
+    pub merkle_root: String,
+    pub asset: StageAsset,
+    /// funds deposited with the merkle root
+    pub total_amount: Uint128,
+    /// claimed or withdrawn after expiration
+    pub claimed_amount: Uint128,
+    pub expiration: Expiration,
+}
+
+pub fn store_stage(storage: &mut dyn Storage, stage_id: u8, stage: &Stage) -> StdResult<()> {
+    bucket(storage, PREFIX_STAGES).save(&[stage_id], stage)
+}
+
+pub fn read_stage(storage: &dyn Storage, stage_id: u8) -> StdResult<Option<Stage>> {
+    bucket_read(storage, PREFIX_STAGES).may_load(&[stage_id])
+}
+
+pub fn store_claimed(
+    storage: &mut dyn Storage,
+    stage_id: u8,
+    address: &CanonicalAddr,
+) -> StdResult<()> {
+    Bucket::multilevel(storage, &[PREFIX_CLAIMS, &[stage_id]]).save(address.as_slice(), &true)
+}
+
+pub fn read_claimed(
+    storage: &dyn Storage,
+    stage_id: u8,
+    address: &CanonicalAddr,
+) -> StdResult<bool> {
+    let claimed: Option<bool> = ReadonlyBucket::multilevel(storage, &[PREFIX_CLAIMS, &[stage_id]])
+        .may_load(address.as_slice())?;
+    Ok(claimed.unwrap_or(false))
+}
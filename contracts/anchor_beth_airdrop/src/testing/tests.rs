@@ -0,0 +1,347 @@
+use cosmwasm_std::testing::{mock_env, mock_info, MockApi, MockStorage};
+use cosmwasm_std::{
+    coins, from_binary, to_binary, BankMsg, Coin, CosmosMsg, Decimal, Env, OwnedDeps, StdError,
+    SubMsg, Uint128, WasmMsg,
+};
+
+use crate::contract::{execute, instantiate, query};
+use beth::airdrop::{
+    AirdropAsset, ConfigResponse, Cw20HookMsg, ExecuteMsg, InstantiateMsg, IsClaimedResponse,
+    LatestStageResponse, QueryMsg, StageResponse,
+};
+use beth::mock_querier::{mock_dependencies_with_querier, WasmMockQuerier};
+use beth_airdrop_tree::Airdrop;
+use cw20::{Cw20ExecuteMsg, Cw20ReceiveMsg, Expiration};
+
+const MOCK_OWNER_ADDR: &str = "owner0000";
+const MOCK_TOKEN_CONTRACT_ADDR: &str = "token0000";
+
+fn mock_dependencies() -> OwnedDeps<MockStorage, MockApi, WasmMockQuerier> {
+    mock_dependencies_with_querier(
+        WasmMockQuerier::builder()
+            .with_tax(Decimal::percent(1), &[("uusd", 1000000u128)])
+            .build(),
+    )
+}
+
+fn default_init() -> InstantiateMsg {
+    InstantiateMsg {
+        owner: MOCK_OWNER_ADDR.to_string(),
+    }
+}
+
+fn default_airdrop() -> Airdrop {
+    Airdrop::new(vec![
+        ("addr0000".to_string(), Uint128::new(1000)),
+        ("addr0001".to_string(), Uint128::new(2000)),
+        ("addr0002".to_string(), Uint128::new(3000)),
+    ])
+    .unwrap()
+}
+
+fn claim_msg(airdrop: &Airdrop, stage: u8, index: usize) -> ExecuteMsg {
+    ExecuteMsg::Claim {
+        stage,
+        amount: airdrop.claims[index].amount,
+        proof: airdrop.claims[index].proof.clone(),
+    }
+}
+
+fn query_stage(
+    deps: &OwnedDeps<MockStorage, MockApi, WasmMockQuerier>,
+    stage: u8,
+) -> StageResponse {
+    from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::Stage { stage }).unwrap()).unwrap()
+}
+
+fn env_at_height(height: u64) -> Env {
+    let mut env = mock_env();
+    env.block.height = height;
+    env
+}
+
+#[test]
+fn proper_init() {
+    let mut deps = mock_dependencies();
+    let info = mock_info("addr0000", &[]);
+    let res = instantiate(deps.as_mut(), mock_env(), info, default_init()).unwrap();
+    assert_eq!(0, res.messages.len());
+
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap();
+    let config: ConfigResponse = from_binary(&res).unwrap();
+    assert_eq!(config.owner, MOCK_OWNER_ADDR);
+
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::LatestStage {}).unwrap();
+    let latest: LatestStageResponse = from_binary(&res).unwrap();
+    assert_eq!(latest.latest_stage, 0);
+}
+
+#[test]
+fn register_and_claim_native_stage() {
+    let mut deps = mock_dependencies();
+    let info = mock_info("addr0000", &[]);
+    instantiate(deps.as_mut(), mock_env(), info, default_init()).unwrap();
+
+    let airdrop = default_airdrop();
+    let msg = ExecuteMsg::RegisterMerkleRoot {
+        merkle_root: airdrop.merkle_root.clone(),
+        expiration: None,
+    };
+
+    let info = mock_info("addr0000", &coins(6000, "uusd"));
+    let res = execute(deps.as_mut(), mock_env(), info, msg.clone());
+    assert_eq!(res.unwrap_err(), StdError::generic_err("unauthorized"));
+
+    let info = mock_info(MOCK_OWNER_ADDR, &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg.clone());
+    assert_eq!(
+        res.unwrap_err(),
+        StdError::generic_err("Fund the stage with a single native coin")
+    );
+
+    let info = mock_info(MOCK_OWNER_ADDR, &coins(6000, "uusd"));
+    let invalid_root = ExecuteMsg::RegisterMerkleRoot {
+        merkle_root: "zz".to_string(),
+        expiration: None,
+    };
+    let res = execute(deps.as_mut(), mock_env(), info, invalid_root);
+    assert_eq!(res.unwrap_err(), StdError::generic_err("Invalid hash: zz"));
+
+    let info = mock_info(MOCK_OWNER_ADDR, &coins(6000, "uusd"));
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(res.attributes[1].value, "1");
+
+    assert_eq!(
+        query_stage(&deps, 1),
+        StageResponse {
+            stage: 1,
+            merkle_root: airdrop.merkle_root.clone(),
+            asset: AirdropAsset::Native {
+                denom: "uusd".to_string()
+            },
+            total_amount: Uint128::new(6000),
+            claimed_amount: Uint128::zero(),
+            expiration: Expiration::Never {},
+        }
+    );
+
+    let info = mock_info("addr0001", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, claim_msg(&airdrop, 1, 1)).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+            to_address: "addr0001".to_string(),
+            amount: vec![Coin {
+                denom: "uusd".to_string(),
+                // 2000 with 1% tax on Terra
+                amount: Uint128::new(if cfg!(feature = "terra") { 1980 } else { 2000 }),
+            }],
+        }))]
+    );
+
+    let info = mock_info("addr0001", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, claim_msg(&airdrop, 1, 1));
+    assert_eq!(res.unwrap_err(), StdError::generic_err("Already claimed"));
+
+    // the proof of another address does not verify
+    let info = mock_info("addr0000", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, claim_msg(&airdrop, 1, 2));
+    assert_eq!(
+        res.unwrap_err(),
+        StdError::generic_err("Verification failed")
+    );
+
+    let info = mock_info("addr0000", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, claim_msg(&airdrop, 2, 0));
+    assert_eq!(
+        res.unwrap_err(),
+        StdError::generic_err("Airdrop stage 2 does not exist")
+    );
+
+    let msg = QueryMsg::IsClaimed {
+        stage: 1,
+        address: "addr0001".to_string(),
+    };
+    let res: IsClaimedResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+    assert!(res.is_claimed);
+    let msg = QueryMsg::IsClaimed {
+        stage: 1,
+        address: "addr0000".to_string(),
+    };
+    let res: IsClaimedResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+    assert!(!res.is_claimed);
+
+    assert_eq!(query_stage(&deps, 1).claimed_amount, Uint128::new(2000));
+}
+
+#[test]
+fn register_and_claim_token_stage() {
+    let mut deps = mock_dependencies();
+    let info = mock_info("addr0000", &[]);
+    instantiate(deps.as_mut(), mock_env(), info, default_init()).unwrap();
+
+    let airdrop = default_airdrop();
+    let receive_msg = |sender: &str| {
+        ExecuteMsg::Receive(Cw20ReceiveMsg {
+            sender: sender.to_string(),
+            amount: Uint128::new(6000),
+            msg: to_binary(&Cw20HookMsg::RegisterMerkleRoot {
+                merkle_root: airdrop.merkle_root.clone(),
+                expiration: None,
+            })
+            .unwrap(),
+        })
+    };
+
+    let info = mock_info(MOCK_TOKEN_CONTRACT_ADDR, &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, receive_msg("addr0000"));
+    assert_eq!(res.unwrap_err(), StdError::generic_err("unauthorized"));
+
+    let info = mock_info(MOCK_TOKEN_CONTRACT_ADDR, &[]);
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        receive_msg(MOCK_OWNER_ADDR),
+    )
+    .unwrap();
+    assert_eq!(
+        query_stage(&deps, 1).asset,
+        AirdropAsset::Token {
+            contract_addr: MOCK_TOKEN_CONTRACT_ADDR.to_string()
+        }
+    );
+
+    let info = mock_info("addr0002", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, claim_msg(&airdrop, 1, 2)).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(WasmMsg::Execute {
+            contract_addr: MOCK_TOKEN_CONTRACT_ADDR.to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: "addr0002".to_string(),
+                amount: Uint128::new(3000),
+            })
+            .unwrap(),
+            funds: vec![],
+        })]
+    );
+}
+
+#[test]
+fn withdraw_unclaimed_after_expiration() {
+    let mut deps = mock_dependencies();
+    let info = mock_info("addr0000", &[]);
+    instantiate(deps.as_mut(), mock_env(), info, default_init()).unwrap();
+
+    let height = mock_env().block.height;
+    let airdrop = default_airdrop();
+
+    let info = mock_info(MOCK_OWNER_ADDR, &coins(6000, "uusd"));
+    let msg = ExecuteMsg::RegisterMerkleRoot {
+        merkle_root: airdrop.merkle_root.clone(),
+        expiration: Some(Expiration::AtHeight(height)),
+    };
+    let res = execute(deps.as_mut(), mock_env(), info, msg);
+    assert_eq!(
+        res.unwrap_err(),
+        StdError::generic_err("Expiration is in the past")
+    );
+
+    let info = mock_info(MOCK_OWNER_ADDR, &coins(6000, "uusd"));
+    let msg = ExecuteMsg::RegisterMerkleRoot {
+        merkle_root: airdrop.merkle_root.clone(),
+        expiration: Some(Expiration::AtHeight(height + 100)),
+    };
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let info = mock_info("addr0000", &[]);
+    execute(deps.as_mut(), mock_env(), info, claim_msg(&airdrop, 1, 0)).unwrap();
+
+    let withdraw_msg = ExecuteMsg::WithdrawUnclaimed {
+        stage: 1,
+        recipient: Some("treasury0000".to_string()),
+    };
+    let info = mock_info(MOCK_OWNER_ADDR, &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, withdraw_msg.clone());
+    assert_eq!(
+        res.unwrap_err(),
+        StdError::generic_err("Airdrop stage 1 has not expired")
+    );
+
+    let info = mock_info("addr0001", &[]);
+    let res = execute(
+        deps.as_mut(),
+        env_at_height(height + 100),
+        info,
+        claim_msg(&airdrop, 1, 1),
+    );
+    assert_eq!(
+        res.unwrap_err(),
+        StdError::generic_err("Airdrop stage 1 expired")
+    );
+
+    let info = mock_info("addr0001", &[]);
+    let res = execute(
+        deps.as_mut(),
+        env_at_height(height + 100),
+        info,
+        withdraw_msg.clone(),
+    );
+    assert_eq!(res.unwrap_err(), StdError::generic_err("unauthorized"));
+
+    let info = mock_info(MOCK_OWNER_ADDR, &[]);
+    let res = execute(
+        deps.as_mut(),
+        env_at_height(height + 100),
+        info,
+        withdraw_msg.clone(),
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+            to_address: "treasury0000".to_string(),
+            amount: vec![Coin {
+                denom: "uusd".to_string(),
+                // 5000 with 1% tax on Terra
+                amount: Uint128::new(if cfg!(feature = "terra") { 4950 } else { 5000 }),
+            }],
+        }))]
+    );
+
+    let info = mock_info(MOCK_OWNER_ADDR, &[]);
+    let res = execute(
+        deps.as_mut(),
+        env_at_height(height + 100),
+        info,
+        withdraw_msg,
+    );
+    assert_eq!(
+        res.unwrap_err(),
+        StdError::generic_err("No unclaimed funds to withdraw")
+    );
+}
+
+#[test]
+fn update_config() {
+    let mut deps = mock_dependencies();
+    let info = mock_info("addr0000", &[]);
+    instantiate(deps.as_mut(), mock_env(), info, default_init()).unwrap();
+
+    let msg = ExecuteMsg::UpdateConfig {
+        owner: Some("owner0001".to_string()),
+    };
+    let info = mock_info("addr0000", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg.clone());
+    assert_eq!(res.unwrap_err(), StdError::generic_err("unauthorized"));
+
+    let info = mock_info(MOCK_OWNER_ADDR, &[]);
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap();
+    let config: ConfigResponse = from_binary(&res).unwrap();
+    assert_eq!(config.owner, "owner0001");
+}
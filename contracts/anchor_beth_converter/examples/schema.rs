@@ -1,7 +1,7 @@
 use std::env::current_dir;
 use std::fs::create_dir_all;
 
-use beth::converter::{ConfigResponse, ExecuteMsg, InstantiateMsg, QueryMsg};
+use beth::converter::{ConfigResponse, ExecuteMsg, ExportStateResponse, InstantiateMsg, QueryMsg};
 use cosmwasm_schema::{export_schema, remove_schemas, schema_for};
 
 fn main() {
@@ -14,4 +14,5 @@ fn main() {
     export_schema(&schema_for!(ExecuteMsg), &out_dir);
     export_schema(&schema_for!(QueryMsg), &out_dir);
     export_schema(&schema_for!(ConfigResponse), &out_dir);
+    export_schema(&schema_for!(ExportStateResponse), &out_dir);
 }
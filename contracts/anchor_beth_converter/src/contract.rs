@@ -4,7 +4,8 @@ use cosmwasm_std::entry_point;
 use crate::state::{read_config, store_config, Config};
 
 use beth::converter::{
-    ConfigResponse, Cw20HookMsg, ExecuteMsg, InstantiateMsg, MigrateMsg, QueryMsg,
+    Asset, AssetReserveResponse, ConfigResponse, Cw20HookMsg, ExecuteMsg, ExportStateResponse,
+    InstantiateMsg, MigrateMsg, QueryMsg, EXPORT_VERSION,
 };
 use cosmwasm_std::{
     from_binary, to_binary, Binary, CosmosMsg, Deps, DepsMut, Env, MessageInfo, Response, StdError,
@@ -12,9 +13,15 @@ use cosmwasm_std::{
 };
 
 use crate::math::{convert_to_anchor_decimals, convert_to_wormhole_decimals};
-use crate::querier::query_decimals;
+use crate::querier::{query_decimals, query_token_balance, query_token_info};
+use beth::migration::migrate_contract_version;
+use cw2::{set_contract_version, CONTRACT};
 use cw20::{Cw20ExecuteMsg, Cw20ReceiveMsg};
 
+// version info for migration info
+const CONTRACT_NAME: &str = "crates.io:anchor_beth_converter";
+const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
+
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn instantiate(
     deps: DepsMut,
@@ -31,6 +38,7 @@ pub fn instantiate(
     };
 
     store_config(deps.storage).save(&conf)?;
+    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
 
     Ok(Response::default())
 }
@@ -220,29 +228,22 @@ pub(crate) fn execute_convert_to_wormhole(
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
+pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
     match msg {
         QueryMsg::Config {} => to_binary(&query_config(deps)?),
+        QueryMsg::ExportState {} => to_binary(&query_export_state(deps, env)?),
     }
 }
 
 fn query_config(deps: Deps) -> StdResult<ConfigResponse> {
     let config: Config = read_config(deps.storage)?;
-    let anchor_token = if config.anchor_token_address.is_some() {
-        Some(
-            deps.api
-                .addr_humanize(&config.anchor_token_address.unwrap())?
-                .to_string(),
-        )
+    let anchor_token = if let Some(anchor_token_address) = config.anchor_token_address {
+        Some(deps.api.addr_humanize(&anchor_token_address)?.to_string())
     } else {
         None
     };
-    let wormhole_token = if config.wormhole_token_address.is_some() {
-        Some(
-            deps.api
-                .addr_humanize(&config.wormhole_token_address.unwrap())?
-                .to_string(),
-        )
+    let wormhole_token = if let Some(wormhole_token_address) = config.wormhole_token_address {
+        Some(deps.api.addr_humanize(&wormhole_token_address)?.to_string())
     } else {
         None
     };
@@ -253,7 +254,43 @@ fn query_config(deps: Deps) -> StdResult<ConfigResponse> {
     })
 }
 
+fn query_export_state(deps: Deps, env: Env) -> StdResult<ExportStateResponse> {
+    let config = read_config(deps.storage)?;
+
+    let mut assets = vec![];
+    if let (Some(anchor_token), Some(wormhole_token)) =
+        (config.anchor_token_address, config.wormhole_token_address)
+    {
+        let anchor_token = deps.api.addr_humanize(&anchor_token)?;
+        let wormhole_token = deps.api.addr_humanize(&wormhole_token)?;
+        let anchor_info = query_token_info(deps, anchor_token.clone())?;
+        let wormhole_info = query_token_info(deps, wormhole_token.clone())?;
+
+        assets.push(AssetReserveResponse {
+            asset: Asset {
+                asset_name: anchor_info.symbol,
+                wormhole_token_address: wormhole_token.to_string(),
+                anchor_token_address: anchor_token.to_string(),
+            },
+            wormhole_decimals: wormhole_info.decimals,
+            anchor_decimals: anchor_info.decimals,
+            wormhole_reserve: query_token_balance(deps, wormhole_token, env.contract.address)?,
+            anchor_supply: anchor_info.total_supply,
+        });
+    }
+
+    Ok(ExportStateResponse {
+        version: EXPORT_VERSION,
+        contract: CONTRACT.may_load(deps.storage)?,
+        config: query_config(deps)?,
+        assets,
+    })
+}
+
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn migrate(_deps: DepsMut, _env: Env, _msg: MigrateMsg) -> StdResult<Response> {
+pub fn migrate(deps: DepsMut, _env: Env, _msg: MigrateMsg) -> StdResult<Response> {
+    // the state layout is unchanged since the deployments that predate cw2
+    migrate_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION, &[])?;
+
     Ok(Response::default())
 }
@@ -1,13 +1,24 @@
-use cosmwasm_std::{to_binary, Addr, Deps, QueryRequest, StdResult, WasmQuery};
-use cw20::{Cw20QueryMsg, TokenInfoResponse};
+use cosmwasm_std::{to_binary, Addr, Deps, QueryRequest, StdResult, Uint128, WasmQuery};
+use cw20::{BalanceResponse, Cw20QueryMsg, TokenInfoResponse};
 
 pub fn query_decimals(deps: Deps, contract_addr: Addr) -> StdResult<u8> {
-    // load price form the oracle
-    let token_info: TokenInfoResponse =
-        deps.querier.query(&QueryRequest::Wasm(WasmQuery::Smart {
-            contract_addr: contract_addr.to_string(),
-            msg: to_binary(&Cw20QueryMsg::TokenInfo {})?,
-        }))?;
-
-    Ok(token_info.decimals)
+    Ok(query_token_info(deps, contract_addr)?.decimals)
+}
+
+pub fn query_token_info(deps: Deps, contract_addr: Addr) -> StdResult<TokenInfoResponse> {
+    deps.querier.query(&QueryRequest::Wasm(WasmQuery::Smart {
+        contract_addr: contract_addr.to_string(),
+        msg: to_binary(&Cw20QueryMsg::TokenInfo {})?,
+    }))
+}
+
+pub fn query_token_balance(deps: Deps, contract_addr: Addr, address: Addr) -> StdResult<Uint128> {
+    let res: BalanceResponse = deps.querier.query(&QueryRequest::Wasm(WasmQuery::Smart {
+        contract_addr: contract_addr.to_string(),
+        msg: to_binary(&Cw20QueryMsg::Balance {
+            address: address.to_string(),
+        })?,
+    }))?;
+
+    Ok(res.balance)
 }
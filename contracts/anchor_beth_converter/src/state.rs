@@ -12,7 +12,7 @@ pub struct Config {
     pub wormhole_token_address: Option<CanonicalAddr>,
 }
 
-pub fn store_config(storage: &mut dyn Storage) -> Singleton<Config> {
+pub fn store_config(storage: &mut dyn Storage) -> Singleton<'_, Config> {
     singleton(storage, KEY_CONFIG)
 }
 
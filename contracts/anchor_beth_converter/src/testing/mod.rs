@@ -1,3 +1 @@
 mod tests;
-
-mod mock_querier;
@@ -1,14 +1,18 @@
-use cosmwasm_std::testing::{mock_env, mock_info};
+use cosmwasm_std::testing::{mock_env, mock_info, MOCK_CONTRACT_ADDR};
 use cosmwasm_std::{
     from_binary, to_binary, Attribute, CosmosMsg, StdError, SubMsg, Uint128, WasmMsg,
 };
 
-use crate::contract::{execute, instantiate, query};
-use crate::testing::mock_querier::mock_dependencies;
+use crate::contract::{execute, instantiate, migrate as contract_migrate, query};
 use beth::converter::Cw20HookMsg::{ConvertAnchorToWormhole, ConvertWormholeToAnchor};
 use beth::converter::ExecuteMsg::{Receive, RegisterTokens};
-use beth::converter::{ConfigResponse, InstantiateMsg, QueryMsg};
-use cw20::{Cw20ExecuteMsg, Cw20ReceiveMsg};
+use beth::converter::{
+    Asset, AssetReserveResponse, ConfigResponse, ExportStateResponse, InstantiateMsg, MigrateMsg,
+    QueryMsg, EXPORT_VERSION,
+};
+use beth::mock_querier::{mock_dependencies, WasmMockQuerier};
+use cw2::{get_contract_version, set_contract_version};
+use cw20::{Cw20ExecuteMsg, Cw20ReceiveMsg, TokenInfoResponse};
 
 const MOCK_OWNER_ADDR: &str = "owner0000";
 const MOCK_ANCHOR_TOKEN_CONTRACT_ADDR: &str = "beth_token0000";
@@ -20,6 +24,27 @@ fn default_init() -> InstantiateMsg {
     }
 }
 
+fn set_decimals(querier: &mut WasmMockQuerier, anchor_decimals: u8, wormhole_decimals: u8) {
+    querier.update_token_info(
+        MOCK_ANCHOR_TOKEN_CONTRACT_ADDR,
+        TokenInfoResponse {
+            name: "anchor_token".to_string(),
+            symbol: "ANC".to_string(),
+            decimals: anchor_decimals,
+            total_supply: Uint128::zero(),
+        },
+    );
+    querier.update_token_info(
+        MOCK_WORMHOLE_TOKEN_CONTRACT_ADDR,
+        TokenInfoResponse {
+            name: "wormhole_token".to_string(),
+            symbol: "WORM".to_string(),
+            decimals: wormhole_decimals,
+            total_supply: Uint128::zero(),
+        },
+    );
+}
+
 #[test]
 fn proper_init() {
     let mut deps = mock_dependencies(&[]);
@@ -59,7 +84,7 @@ fn proper_convert_to_anchor() {
     };
 
     // set anchor and wormhole decimals
-    deps.querier.set_decimals(6, 8);
+    set_decimals(&mut deps.querier, 6, 8);
 
     let res = execute(
         deps.as_mut(),
@@ -142,7 +167,7 @@ fn proper_convert_to_wormhole() {
     };
 
     // set anchor and wormhole decimals
-    deps.querier.set_decimals(6, 8);
+    set_decimals(&mut deps.querier, 6, 8);
 
     let res = execute(
         deps.as_mut(),
@@ -215,7 +240,7 @@ fn proper_convert_to_anchor_with_more_decimals() {
     };
 
     // set anchor and wormhole decimals
-    deps.querier.set_decimals(10, 8);
+    set_decimals(&mut deps.querier, 10, 8);
 
     let res = execute(
         deps.as_mut(),
@@ -278,7 +303,7 @@ fn proper_convert_to_wormhole_with_less_decimals() {
     };
 
     // set anchor and wormhole decimals
-    deps.querier.set_decimals(10, 8);
+    set_decimals(&mut deps.querier, 10, 8);
 
     let res = execute(
         deps.as_mut(),
@@ -396,3 +421,123 @@ fn proper_update_config() {
         }
     );
 }
+
+#[test]
+fn export_state() {
+    let mut deps = mock_dependencies(&[]);
+    let info = mock_info("addr0000", &[]);
+    instantiate(deps.as_mut(), mock_env(), info, default_init()).unwrap();
+
+    // nothing is registered yet
+    let res: ExportStateResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::ExportState {}).unwrap()).unwrap();
+    assert_eq!(res.version, EXPORT_VERSION);
+    assert_eq!(
+        res.contract.unwrap().contract,
+        "crates.io:anchor_beth_converter"
+    );
+    assert_eq!(res.config.anchor_token_address, None);
+    assert!(res.assets.is_empty());
+
+    let msg = RegisterTokens {
+        anchor_token_address: MOCK_ANCHOR_TOKEN_CONTRACT_ADDR.to_string(),
+        wormhole_token_address: MOCK_WORMHOLE_TOKEN_CONTRACT_ADDR.to_string(),
+    };
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(MOCK_OWNER_ADDR, &[]),
+        msg,
+    )
+    .unwrap();
+
+    deps.querier.update_token_info(
+        MOCK_ANCHOR_TOKEN_CONTRACT_ADDR,
+        TokenInfoResponse {
+            name: "bonded ETH".to_string(),
+            symbol: "BETH".to_string(),
+            decimals: 6,
+            total_supply: Uint128::new(1000000),
+        },
+    );
+    deps.querier.update_token_info(
+        MOCK_WORMHOLE_TOKEN_CONTRACT_ADDR,
+        TokenInfoResponse {
+            name: "wormhole ETH".to_string(),
+            symbol: "WETH".to_string(),
+            decimals: 8,
+            total_supply: Uint128::new(500000000),
+        },
+    );
+    deps.querier.update_token_balance(
+        MOCK_WORMHOLE_TOKEN_CONTRACT_ADDR,
+        MOCK_CONTRACT_ADDR,
+        Uint128::new(100000000),
+    );
+
+    let res: ExportStateResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::ExportState {}).unwrap()).unwrap();
+    assert_eq!(
+        res.config.anchor_token_address,
+        Some(MOCK_ANCHOR_TOKEN_CONTRACT_ADDR.to_string())
+    );
+    assert_eq!(
+        res.assets,
+        vec![AssetReserveResponse {
+            asset: Asset {
+                asset_name: "BETH".to_string(),
+                wormhole_token_address: MOCK_WORMHOLE_TOKEN_CONTRACT_ADDR.to_string(),
+                anchor_token_address: MOCK_ANCHOR_TOKEN_CONTRACT_ADDR.to_string(),
+            },
+            wormhole_decimals: 8,
+            anchor_decimals: 6,
+            wormhole_reserve: Uint128::new(100000000),
+            anchor_supply: Uint128::new(1000000),
+        }]
+    );
+}
+
+#[test]
+fn migrate() {
+    let mut deps = mock_dependencies(&[]);
+    let info = mock_info("addr0000", &[]);
+    instantiate(deps.as_mut(), mock_env(), info, default_init()).unwrap();
+
+    let version = get_contract_version(&deps.storage).unwrap();
+    assert_eq!(version.contract, "crates.io:anchor_beth_converter");
+    assert_eq!(version.version, env!("CARGO_PKG_VERSION"));
+
+    // same version
+    contract_migrate(deps.as_mut(), mock_env(), MigrateMsg {}).unwrap();
+
+    // deployments that predate cw2
+    cw2::CONTRACT.remove(&mut deps.storage);
+    contract_migrate(deps.as_mut(), mock_env(), MigrateMsg {}).unwrap();
+    assert_eq!(get_contract_version(&deps.storage).unwrap(), version);
+
+    // downgrades
+    set_contract_version(
+        &mut deps.storage,
+        "crates.io:anchor_beth_converter",
+        "99.0.0",
+    )
+    .unwrap();
+    let res = contract_migrate(deps.as_mut(), mock_env(), MigrateMsg {});
+    assert_eq!(
+        res.unwrap_err(),
+        StdError::generic_err(format!(
+            "Cannot downgrade from 99.0.0 to {}",
+            env!("CARGO_PKG_VERSION")
+        ))
+    );
+
+    // other contracts
+    set_contract_version(&mut deps.storage, "crates.io:other", "0.1.0").unwrap();
+    let res = contract_migrate(deps.as_mut(), mock_env(), MigrateMsg {});
+    assert_eq!(
+        res.unwrap_err(),
+        StdError::generic_err(
+            "Cannot migrate from crates.io:other to crates.io:anchor_beth_converter"
+        )
+    );
+}
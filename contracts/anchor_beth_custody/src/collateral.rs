@@ -0,0 +1,157 @@
+use crate::rewards::{claim_custody_rewards, update_borrower_rewards};
+use crate::state::{read_borrower, read_config, read_state, store_borrower, store_state, Config};
+
+use cosmwasm_std::{
+    attr, to_binary, Addr, CosmosMsg, DepsMut, Env, MessageInfo, Response, StdError, StdResult,
+    Uint128, WasmMsg,
+};
+use cw20::Cw20ExecuteMsg;
+
+pub fn execute_deposit_collateral(
+    deps: DepsMut,
+    env: Env,
+    borrower_addr: Addr,
+    amount: Uint128,
+) -> StdResult<Response> {
+    let config = read_config(deps.storage)?;
+    let mut state = read_state(deps.storage)?;
+    let borrower_raw = deps.api.addr_canonicalize(borrower_addr.as_str())?;
+    let mut borrower = read_borrower(deps.storage, &borrower_raw)?;
+
+    // the rewards accrued so far belong to the collateral deposited before
+    let claim_msg = claim_custody_rewards(deps.as_ref(), &env, &config, &mut state)?;
+    update_borrower_rewards(&state, &mut borrower)?;
+
+    borrower.balance += amount;
+    borrower.spendable += amount;
+    state.total_collateral += amount;
+
+    store_borrower(deps.storage, &borrower_raw, &borrower)?;
+    store_state(deps.storage, &state)?;
+
+    Ok(Response::new().add_messages(claim_msg).add_attributes(vec![
+        attr("action", "deposit_collateral"),
+        attr("borrower", borrower_addr),
+        attr("amount", amount),
+    ]))
+}
+
+pub fn execute_withdraw_collateral(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    amount: Option<Uint128>,
+) -> StdResult<Response> {
+    let config = read_config(deps.storage)?;
+    let mut state = read_state(deps.storage)?;
+    let borrower_raw = deps.api.addr_canonicalize(info.sender.as_str())?;
+    let mut borrower = read_borrower(deps.storage, &borrower_raw)?;
+
+    let amount = amount.unwrap_or(borrower.spendable);
+    if amount > borrower.spendable {
+        return Err(StdError::generic_err(format!(
+            "Withdraw amount cannot exceed the spendable collateral: {}",
+            borrower.spendable
+        )));
+    }
+    if amount.is_zero() {
+        return Err(StdError::generic_err("No collateral to withdraw"));
+    }
+
+    let claim_msg = claim_custody_rewards(deps.as_ref(), &env, &config, &mut state)?;
+    update_borrower_rewards(&state, &mut borrower)?;
+
+    borrower.balance = borrower.balance.checked_sub(amount)?;
+    borrower.spendable = borrower.spendable.checked_sub(amount)?;
+    state.total_collateral = state.total_collateral.checked_sub(amount)?;
+
+    store_borrower(deps.storage, &borrower_raw, &borrower)?;
+    store_state(deps.storage, &state)?;
+
+    let transfer_msg: CosmosMsg = WasmMsg::Execute {
+        contract_addr: deps
+            .api
+            .addr_humanize(&config.collateral_token)?
+            .to_string(),
+        msg: to_binary(&Cw20ExecuteMsg::Transfer {
+            recipient: info.sender.to_string(),
+            amount,
+        })?,
+        funds: vec![],
+    }
+    .into();
+
+    Ok(Response::new()
+        .add_messages(claim_msg)
+        .add_message(transfer_msg)
+        .add_attributes(vec![
+            attr("action", "withdraw_collateral"),
+            attr("borrower", info.sender),
+            attr("amount", amount),
+        ]))
+}
+
+pub fn execute_lock_collateral(
+    deps: DepsMut,
+    info: MessageInfo,
+    borrower_addr: Addr,
+    amount: Uint128,
+) -> StdResult<Response> {
+    let config = read_config(deps.storage)?;
+    assert_overseer(&deps, &config, &info)?;
+
+    // the collateral share is unchanged, so are the rewards
+    let borrower_raw = deps.api.addr_canonicalize(borrower_addr.as_str())?;
+    let mut borrower = read_borrower(deps.storage, &borrower_raw)?;
+    if amount > borrower.spendable {
+        return Err(StdError::generic_err(format!(
+            "Lock amount cannot exceed the spendable collateral: {}",
+            borrower.spendable
+        )));
+    }
+
+    borrower.spendable = borrower.spendable.checked_sub(amount)?;
+    store_borrower(deps.storage, &borrower_raw, &borrower)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "lock_collateral"),
+        attr("borrower", borrower_addr),
+        attr("amount", amount),
+    ]))
+}
+
+pub fn execute_unlock_collateral(
+    deps: DepsMut,
+    info: MessageInfo,
+    borrower_addr: Addr,
+    amount: Uint128,
+) -> StdResult<Response> {
+    let config = read_config(deps.storage)?;
+    assert_overseer(&deps, &config, &info)?;
+
+    let borrower_raw = deps.api.addr_canonicalize(borrower_addr.as_str())?;
+    let mut borrower = read_borrower(deps.storage, &borrower_raw)?;
+    let locked = borrower.balance.checked_sub(borrower.spendable)?;
+    if amount > locked {
+        return Err(StdError::generic_err(format!(
+            "Unlock amount cannot exceed the locked collateral: {}",
+            locked
+        )));
+    }
+
+    borrower.spendable += amount;
+    store_borrower(deps.storage, &borrower_raw, &borrower)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "unlock_collateral"),
+        attr("borrower", borrower_addr),
+        attr("amount", amount),
+    ]))
+}
+
+fn assert_overseer(deps: &DepsMut, config: &Config, info: &MessageInfo) -> StdResult<()> {
+    if config.overseer != deps.api.addr_canonicalize(info.sender.as_str())? {
+        return Err(StdError::generic_err("unauthorized"));
+    }
+    Ok(())
+}
@@ -0,0 +1,223 @@
+#[cfg(not(feature = "library"))]
+use cosmwasm_std::entry_point;
+
+use crate::collateral::{
+    execute_deposit_collateral, execute_lock_collateral, execute_unlock_collateral,
+    execute_withdraw_collateral,
+};
+use crate::rewards::{
+    execute_claim_penalties, execute_claim_rewards, execute_distribute_rewards,
+    query_accrued_penalties, query_accrued_rewards,
+};
+use crate::state::{
+    read_borrower, read_borrowers, read_config, read_state, store_config, store_state, Config,
+    State,
+};
+
+use beth::custody::{
+    BorrowerResponse, BorrowersResponse, ConfigResponse, Cw20HookMsg, ExecuteMsg, InstantiateMsg,
+    MigrateMsg, QueryMsg, StateResponse,
+};
+use beth::migration::migrate_contract_version;
+use cosmwasm_bignumber::Decimal256;
+use cosmwasm_std::{
+    attr, from_binary, to_binary, Binary, Deps, DepsMut, Env, MessageInfo, Response, StdError,
+    StdResult, Uint128,
+};
+use cw2::set_contract_version;
+use cw20::Cw20ReceiveMsg;
+
+// version info for migration info
+const CONTRACT_NAME: &str = "crates.io:anchor_beth_custody";
+const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn instantiate(
+    deps: DepsMut,
+    _env: Env,
+    _info: MessageInfo,
+    msg: InstantiateMsg,
+) -> StdResult<Response> {
+    let conf = Config {
+        owner: deps.api.addr_canonicalize(&msg.owner)?,
+        collateral_token: deps.api.addr_canonicalize(&msg.collateral_token)?,
+        overseer: deps.api.addr_canonicalize(&msg.overseer)?,
+        reward_contract: deps.api.addr_canonicalize(&msg.reward_contract)?,
+        reward_denom: msg.reward_denom,
+    };
+
+    store_config(deps.storage, &conf)?;
+    store_state(
+        deps.storage,
+        &State {
+            total_collateral: Uint128::zero(),
+            global_index: Decimal256::zero(),
+            penalty_index: Decimal256::zero(),
+        },
+    )?;
+    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+
+    Ok(Response::default())
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn execute(deps: DepsMut, env: Env, info: MessageInfo, msg: ExecuteMsg) -> StdResult<Response> {
+    match msg {
+        ExecuteMsg::Receive(msg) => receive_cw20(deps, env, info, msg),
+        ExecuteMsg::UpdateConfig { owner, overseer } => {
+            execute_update_config(deps, info, owner, overseer)
+        }
+        ExecuteMsg::LockCollateral { borrower, amount } => {
+            let borrower_addr = deps.api.addr_validate(&borrower)?;
+            execute_lock_collateral(deps, info, borrower_addr, amount)
+        }
+        ExecuteMsg::UnlockCollateral { borrower, amount } => {
+            let borrower_addr = deps.api.addr_validate(&borrower)?;
+            execute_unlock_collateral(deps, info, borrower_addr, amount)
+        }
+        ExecuteMsg::WithdrawCollateral { amount } => {
+            execute_withdraw_collateral(deps, env, info, amount)
+        }
+        ExecuteMsg::DistributeRewards {} => execute_distribute_rewards(deps, env),
+        ExecuteMsg::ClaimRewards { recipient } => {
+            let recipient = match recipient {
+                Some(recipient) => Some(deps.api.addr_validate(&recipient)?),
+                None => None,
+            };
+            execute_claim_rewards(deps, env, info, recipient)
+        }
+        ExecuteMsg::ClaimPenalties { recipient } => {
+            let recipient = match recipient {
+                Some(recipient) => Some(deps.api.addr_validate(&recipient)?),
+                None => None,
+            };
+            execute_claim_penalties(deps, env, info, recipient)
+        }
+    }
+}
+
+pub fn receive_cw20(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    cw20_msg: Cw20ReceiveMsg,
+) -> StdResult<Response> {
+    match from_binary(&cw20_msg.msg) {
+        Ok(Cw20HookMsg::DepositCollateral {}) => {
+            // only the collateral token contract can execute this message
+            let config = read_config(deps.storage)?;
+            if deps.api.addr_canonicalize(info.sender.as_str())? != config.collateral_token {
+                return Err(StdError::generic_err("unauthorized"));
+            }
+
+            let borrower_addr = deps.api.addr_validate(&cw20_msg.sender)?;
+            execute_deposit_collateral(deps, env, borrower_addr, cw20_msg.amount)
+        }
+        Err(err) => Err(err),
+    }
+}
+
+pub fn execute_update_config(
+    deps: DepsMut,
+    info: MessageInfo,
+    owner: Option<String>,
+    overseer: Option<String>,
+) -> StdResult<Response> {
+    let mut config = read_config(deps.storage)?;
+    if config.owner != deps.api.addr_canonicalize(info.sender.as_str())? {
+        return Err(StdError::generic_err("unauthorized"));
+    }
+
+    if let Some(owner) = owner {
+        config.owner = deps.api.addr_canonicalize(&owner)?;
+    }
+    if let Some(overseer) = overseer {
+        config.overseer = deps.api.addr_canonicalize(&overseer)?;
+    }
+
+    store_config(deps.storage, &config)?;
+
+    Ok(Response::new().add_attributes(vec![attr("action", "update_config")]))
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
+    match msg {
+        QueryMsg::Config {} => to_binary(&query_config(deps)?),
+        QueryMsg::State {} => to_binary(&query_state(deps)?),
+        QueryMsg::AccruedRewards { address } => {
+            let addr = deps.api.addr_validate(&address)?;
+            to_binary(&query_accrued_rewards(deps, env, addr)?)
+        }
+        QueryMsg::AccruedPenalties { address } => {
+            let addr = deps.api.addr_validate(&address)?;
+            to_binary(&query_accrued_penalties(deps, env, addr)?)
+        }
+        QueryMsg::Borrower { address } => to_binary(&query_borrower(deps, address)?),
+        QueryMsg::Borrowers { start_after, limit } => {
+            to_binary(&query_borrowers(deps, start_after, limit)?)
+        }
+    }
+}
+
+fn query_config(deps: Deps) -> StdResult<ConfigResponse> {
+    let config = read_config(deps.storage)?;
+    Ok(ConfigResponse {
+        owner: deps.api.addr_humanize(&config.owner)?.to_string(),
+        collateral_token: deps
+            .api
+            .addr_humanize(&config.collateral_token)?
+            .to_string(),
+        overseer: deps.api.addr_humanize(&config.overseer)?.to_string(),
+        reward_contract: deps.api.addr_humanize(&config.reward_contract)?.to_string(),
+        reward_denom: config.reward_denom,
+    })
+}
+
+fn query_state(deps: Deps) -> StdResult<StateResponse> {
+    let state = read_state(deps.storage)?;
+    Ok(StateResponse {
+        total_collateral: state.total_collateral,
+        global_index: state.global_index,
+        penalty_index: state.penalty_index,
+    })
+}
+
+fn query_borrower(deps: Deps, address: String) -> StdResult<BorrowerResponse> {
+    let borrower = read_borrower(
+        deps.storage,
+        &deps
+            .api
+            .addr_canonicalize(deps.api.addr_validate(&address)?.as_str())?,
+    )?;
+    Ok(BorrowerResponse {
+        borrower: address,
+        balance: borrower.balance,
+        spendable: borrower.spendable,
+        index: borrower.index,
+        pending_rewards: borrower.pending_rewards,
+        penalty_index: borrower.penalty_index,
+        pending_penalties: borrower.pending_penalties,
+    })
+}
+
+fn query_borrowers(
+    deps: Deps,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<BorrowersResponse> {
+    let start_after = match start_after {
+        Some(start_after) => Some(deps.api.addr_canonicalize(&start_after)?),
+        None => None,
+    };
+    Ok(BorrowersResponse {
+        borrowers: read_borrowers(deps, start_after, limit)?,
+    })
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn migrate(deps: DepsMut, _env: Env, _msg: MigrateMsg) -> StdResult<Response> {
+    migrate_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION, &[])?;
+
+    Ok(Response::default())
+}
@@ -0,0 +1,9 @@
+pub mod contract;
+pub mod state;
+
+mod collateral;
+mod math;
+mod rewards;
+
+#[cfg(test)]
+mod testing;
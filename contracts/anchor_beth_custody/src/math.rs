@@ -0,0 +1,61 @@
+use cosmwasm_bignumber::{Decimal256, Uint256};
+use cosmwasm_std::{OverflowError, OverflowOperation, StdError, StdResult, Uint128};
+
+/// return a * b
+pub fn decimal_multiplication_in_256(a: Decimal256, b: Decimal256) -> StdResult<Decimal256> {
+    let (product, overflow) = a.0.overflowing_mul(b.0);
+    if overflow {
+        return Err(OverflowError::new(OverflowOperation::Mul, a, b).into());
+    }
+    Ok(Decimal256(product / Decimal256::DECIMAL_FRACTIONAL))
+}
+
+/// return a + b
+pub fn decimal_summation_in_256(a: Decimal256, b: Decimal256) -> StdResult<Decimal256> {
+    let (sum, overflow) = a.0.overflowing_add(b.0);
+    if overflow {
+        return Err(OverflowError::new(OverflowOperation::Add, a, b).into());
+    }
+    Ok(Decimal256(sum))
+}
+
+/// return a - b
+pub fn decimal_subtraction_in_256(a: Decimal256, b: Decimal256) -> StdResult<Decimal256> {
+    let (difference, overflow) = a.0.overflowing_sub(b.0);
+    if overflow {
+        return Err(OverflowError::new(OverflowOperation::Sub, a, b).into());
+    }
+    Ok(Decimal256(difference))
+}
+
+/// return a / b
+pub fn decimal_from_ratio_in_256(a: Uint128, b: Uint128) -> StdResult<Decimal256> {
+    if b.is_zero() {
+        return Err(StdError::generic_err(format!(
+            "Cannot divide {} by zero",
+            a
+        )));
+    }
+    Ok(Decimal256::from_ratio(
+        Uint256::from(a).0,
+        Uint256::from(b).0,
+    ))
+}
+
+/// return a as a Decimal256
+pub fn decimal_from_uint128(a: Uint128) -> Decimal256 {
+    Decimal256::from_uint256(a)
+}
+
+/// return the integer part of a as an amount, and the fractional part of a
+pub fn decimal_split_in_256(a: Decimal256) -> StdResult<(Uint128, Decimal256)> {
+    let integer = a.0 / Decimal256::DECIMAL_FRACTIONAL;
+    let fraction = a.0 % Decimal256::DECIMAL_FRACTIONAL;
+    if integer.0[2] != 0 || integer.0[3] != 0 {
+        return Err(StdError::generic_err(format!(
+            "Cannot convert {} to Uint128",
+            a
+        )));
+    }
+    Ok((Uint256(integer).into(), Decimal256(fraction)))
+}
@@ -0,0 +1,255 @@
+use crate::math::{
+    decimal_from_ratio_in_256, decimal_from_uint128, decimal_multiplication_in_256,
+    decimal_split_in_256, decimal_subtraction_in_256, decimal_summation_in_256,
+};
+use crate::state::{
+    read_borrower, read_config, read_state, store_borrower, store_state, Borrower, Config, State,
+};
+
+use beth::custody::AccruedPenaltiesResponse;
+use beth::deduct_tax;
+use beth::reward::{
+    AccruedRewardsResponse, ExecuteMsg as RewardExecuteMsg, HolderResponse,
+    QueryMsg as RewardQueryMsg,
+};
+use cosmwasm_std::{
+    attr, to_binary, Addr, BankMsg, Coin, CosmosMsg, Deps, DepsMut, Env, MessageInfo, Response,
+    StdError, StdResult, WasmMsg,
+};
+use cw20::Cw20ExecuteMsg;
+
+/// Add the rewards and the early unlock penalties accrued by the custody in the reward
+/// contract to the global indexes and return the messages claiming them, so they are split
+/// by the collateral held while they accrued. Must run before any collateral balance changes.
+pub fn claim_custody_rewards(
+    deps: Deps,
+    env: &Env,
+    config: &Config,
+    state: &mut State,
+) -> StdResult<Vec<CosmosMsg>> {
+    // nobody to distribute to, the rewards wait in the reward contract
+    if state.total_collateral.is_zero() {
+        return Ok(vec![]);
+    }
+
+    let reward_contract = deps.api.addr_humanize(&config.reward_contract)?;
+    let mut messages = vec![];
+    let accrued: AccruedRewardsResponse = deps.querier.query_wasm_smart(
+        reward_contract.to_string(),
+        &RewardQueryMsg::AccruedRewards {
+            address: env.contract.address.to_string(),
+        },
+    )?;
+    if !accrued.rewards.is_zero() {
+        // the reward contract sends the rewards net of tax
+        let received = deduct_tax(
+            deps,
+            Coin {
+                denom: config.reward_denom.clone(),
+                amount: accrued.rewards,
+            },
+        )?;
+        state.global_index = decimal_summation_in_256(
+            state.global_index,
+            decimal_from_ratio_in_256(received.amount, state.total_collateral)?,
+        )?;
+        messages.push(
+            WasmMsg::Execute {
+                contract_addr: reward_contract.to_string(),
+                msg: to_binary(&RewardExecuteMsg::ClaimRewards { recipient: None })?,
+                funds: vec![],
+            }
+            .into(),
+        );
+    }
+
+    // the custody holds bETH, so it gets a share of the early unlock penalties
+    let holder: HolderResponse = deps.querier.query_wasm_smart(
+        reward_contract.to_string(),
+        &RewardQueryMsg::Holder {
+            address: env.contract.address.to_string(),
+        },
+    )?;
+    if !holder.accrued_penalties.is_zero() {
+        state.penalty_index = decimal_summation_in_256(
+            state.penalty_index,
+            decimal_from_ratio_in_256(holder.accrued_penalties, state.total_collateral)?,
+        )?;
+        messages.push(
+            WasmMsg::Execute {
+                contract_addr: reward_contract.to_string(),
+                msg: to_binary(&RewardExecuteMsg::ClaimPenalties { recipient: None })?,
+                funds: vec![],
+            }
+            .into(),
+        );
+    }
+
+    Ok(messages)
+}
+
+/// Move the rewards and the penalties of the borrower up to the global indexes to pending
+pub fn update_borrower_rewards(state: &State, borrower: &mut Borrower) -> StdResult<()> {
+    let balance = decimal_from_uint128(borrower.balance);
+    let rewards = decimal_multiplication_in_256(
+        decimal_subtraction_in_256(state.global_index, borrower.index)?,
+        balance,
+    )?;
+    borrower.pending_rewards = decimal_summation_in_256(borrower.pending_rewards, rewards)?;
+    borrower.index = state.global_index;
+
+    let penalties = decimal_multiplication_in_256(
+        decimal_subtraction_in_256(state.penalty_index, borrower.penalty_index)?,
+        balance,
+    )?;
+    borrower.pending_penalties = decimal_summation_in_256(borrower.pending_penalties, penalties)?;
+    borrower.penalty_index = state.penalty_index;
+    Ok(())
+}
+
+pub fn execute_distribute_rewards(deps: DepsMut, env: Env) -> StdResult<Response> {
+    let config = read_config(deps.storage)?;
+    let mut state = read_state(deps.storage)?;
+
+    let claim_msgs = claim_custody_rewards(deps.as_ref(), &env, &config, &mut state)?;
+    if claim_msgs.is_empty() {
+        return Err(StdError::generic_err("No rewards have accrued yet"));
+    }
+    store_state(deps.storage, &state)?;
+
+    Ok(Response::new()
+        .add_messages(claim_msgs)
+        .add_attributes(vec![
+            attr("action", "distribute_rewards"),
+            attr("global_index", state.global_index.to_string()),
+            attr("penalty_index", state.penalty_index.to_string()),
+        ]))
+}
+
+pub fn execute_claim_rewards(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    recipient: Option<Addr>,
+) -> StdResult<Response> {
+    let config = read_config(deps.storage)?;
+    let mut state = read_state(deps.storage)?;
+    let borrower_raw = deps.api.addr_canonicalize(info.sender.as_str())?;
+    let mut borrower = read_borrower(deps.storage, &borrower_raw)?;
+    let recipient = recipient.unwrap_or_else(|| info.sender.clone());
+
+    let claim_msgs = claim_custody_rewards(deps.as_ref(), &env, &config, &mut state)?;
+    update_borrower_rewards(&state, &mut borrower)?;
+
+    // keep the decimals for the next claim
+    let (rewards, remainder) = decimal_split_in_256(borrower.pending_rewards)?;
+    if rewards.is_zero() {
+        return Err(StdError::generic_err("No rewards have accrued yet"));
+    }
+    borrower.pending_rewards = remainder;
+    store_borrower(deps.storage, &borrower_raw, &borrower)?;
+    store_state(deps.storage, &state)?;
+
+    // the custody claims its own rewards before paying the borrower
+    let bank_msg: CosmosMsg = BankMsg::Send {
+        to_address: recipient.to_string(),
+        amount: vec![deduct_tax(
+            deps.as_ref(),
+            Coin {
+                denom: config.reward_denom,
+                amount: rewards,
+            },
+        )?],
+    }
+    .into();
+
+    Ok(Response::new()
+        .add_messages(claim_msgs)
+        .add_message(bank_msg)
+        .add_attributes(vec![
+            attr("action", "claim_rewards"),
+            attr("borrower", info.sender),
+            attr("rewards", rewards),
+        ]))
+}
+
+pub fn execute_claim_penalties(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    recipient: Option<Addr>,
+) -> StdResult<Response> {
+    let config = read_config(deps.storage)?;
+    let mut state = read_state(deps.storage)?;
+    let borrower_raw = deps.api.addr_canonicalize(info.sender.as_str())?;
+    let mut borrower = read_borrower(deps.storage, &borrower_raw)?;
+    let recipient = recipient.unwrap_or_else(|| info.sender.clone());
+
+    let claim_msgs = claim_custody_rewards(deps.as_ref(), &env, &config, &mut state)?;
+    update_borrower_rewards(&state, &mut borrower)?;
+
+    let (penalties, remainder) = decimal_split_in_256(borrower.pending_penalties)?;
+    if penalties.is_zero() {
+        return Err(StdError::generic_err("No penalties have accrued yet"));
+    }
+    borrower.pending_penalties = remainder;
+    store_borrower(deps.storage, &borrower_raw, &borrower)?;
+    store_state(deps.storage, &state)?;
+
+    // the penalties are not collateral, the custody pays them from its bETH balance
+    let transfer_msg: CosmosMsg = WasmMsg::Execute {
+        contract_addr: deps
+            .api
+            .addr_humanize(&config.collateral_token)?
+            .to_string(),
+        msg: to_binary(&Cw20ExecuteMsg::Transfer {
+            recipient: recipient.to_string(),
+            amount: penalties,
+        })?,
+        funds: vec![],
+    }
+    .into();
+
+    Ok(Response::new()
+        .add_messages(claim_msgs)
+        .add_message(transfer_msg)
+        .add_attributes(vec![
+            attr("action", "claim_penalties"),
+            attr("borrower", info.sender),
+            attr("penalties", penalties),
+        ]))
+}
+
+pub fn query_accrued_rewards(
+    deps: Deps,
+    env: Env,
+    address: Addr,
+) -> StdResult<AccruedRewardsResponse> {
+    let config = read_config(deps.storage)?;
+    let mut state = read_state(deps.storage)?;
+    let mut borrower = read_borrower(deps.storage, &deps.api.addr_canonicalize(address.as_str())?)?;
+
+    claim_custody_rewards(deps, &env, &config, &mut state)?;
+    update_borrower_rewards(&state, &mut borrower)?;
+
+    Ok(AccruedRewardsResponse {
+        rewards: decimal_split_in_256(borrower.pending_rewards)?.0,
+    })
+}
+
+pub fn query_accrued_penalties(
+    deps: Deps,
+    env: Env,
+    address: Addr,
+) -> StdResult<AccruedPenaltiesResponse> {
+    let config = read_config(deps.storage)?;
+    let mut state = read_state(deps.storage)?;
+    let mut borrower = read_borrower(deps.storage, &deps.api.addr_canonicalize(address.as_str())?)?;
+
+    claim_custody_rewards(deps, &env, &config, &mut state)?;
+    update_borrower_rewards(&state, &mut borrower)?;
+
+    Ok(AccruedPenaltiesResponse {
+        penalties: decimal_split_in_256(borrower.pending_penalties)?.0,
+    })
+}
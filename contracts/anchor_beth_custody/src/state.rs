@@ -0,0 +1,127 @@
+use cosmwasm_bignumber::Decimal256;
+use cosmwasm_std::{CanonicalAddr, Deps, Order, StdResult, Storage, Uint128};
+use cosmwasm_storage::{bucket, bucket_read, singleton, singleton_read, ReadonlyBucket};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use beth::custody::BorrowerResponse;
+
+pub static KEY_CONFIG: &[u8] = b"config";
+pub static KEY_STATE: &[u8] = b"state";
+
+pub static PREFIX_BORROWERS: &[u8] = b"borrowers";
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Config {
+    pub owner: CanonicalAddr,
+    pub collateral_token: CanonicalAddr,
+    pub overseer: CanonicalAddr,
+    pub reward_contract: CanonicalAddr,
+    pub reward_denom: String,
+}
+
+pub fn store_config(storage: &mut dyn Storage, config: &Config) -> StdResult<()> {
+    singleton(storage, KEY_CONFIG).save(config)
+}
+
+pub fn read_config(storage: &dyn Storage) -> StdResult<Config> {
+    singleton_read(storage, KEY_CONFIG).load()
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct State {
+    pub total_collateral: Uint128,
+    /// rewards per unit of collateral distributed since instantiation
+    pub global_index: Decimal256,
+    /// early unlock penalties in bETH per unit of collateral, paid by the reward
+    /// contract to the custody as a holder
+    pub penalty_index: Decimal256,
+}
+
+pub fn store_state(storage: &mut dyn Storage, state: &State) -> StdResult<()> {
+    singleton(storage, KEY_STATE).save(state)
+}
+
+pub fn read_state(storage: &dyn Storage) -> StdResult<State> {
+    singleton_read(storage, KEY_STATE).load()
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Borrower {
+    /// deposited collateral, locked or not
+    pub balance: Uint128,
+    /// collateral that is not locked by the overseer
+    pub spendable: Uint128,
+    pub index: Decimal256,
+    pub pending_rewards: Decimal256,
+    pub penalty_index: Decimal256,
+    /// early unlock penalties owed to the borrower in bETH
+    pub pending_penalties: Decimal256,
+}
+
+pub fn store_borrower(
+    storage: &mut dyn Storage,
+    borrower_address: &CanonicalAddr,
+    borrower: &Borrower,
+) -> StdResult<()> {
+    bucket(storage, PREFIX_BORROWERS).save(borrower_address.as_slice(), borrower)
+}
+
+pub fn read_borrower(
+    storage: &dyn Storage,
+    borrower_address: &CanonicalAddr,
+) -> StdResult<Borrower> {
+    let res: Option<Borrower> =
+        bucket_read(storage, PREFIX_BORROWERS).may_load(borrower_address.as_slice())?;
+    match res {
+        Some(borrower) => Ok(borrower),
+        None => Ok(Borrower {
+            balance: Uint128::zero(),
+            spendable: Uint128::zero(),
+            index: Decimal256::zero(),
+            pending_rewards: Decimal256::zero(),
+            penalty_index: Decimal256::zero(),
+            pending_penalties: Decimal256::zero(),
+        }),
+    }
+}
+
+// settings for pagination
+const MAX_LIMIT: u32 = 30;
+const DEFAULT_LIMIT: u32 = 10;
+pub fn read_borrowers(
+    deps: Deps,
+    start_after: Option<CanonicalAddr>,
+    limit: Option<u32>,
+) -> StdResult<Vec<BorrowerResponse>> {
+    let borrower_bucket: ReadonlyBucket<Borrower> = bucket_read(deps.storage, PREFIX_BORROWERS);
+
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = calc_range_start(start_after);
+
+    borrower_bucket
+        .range(start.as_deref(), None, Order::Ascending)
+        .take(limit)
+        .map(|elem| {
+            let (k, v) = elem?;
+            Ok(BorrowerResponse {
+                borrower: deps.api.addr_humanize(&CanonicalAddr::from(k))?.to_string(),
+                balance: v.balance,
+                spendable: v.spendable,
+                index: v.index,
+                pending_rewards: v.pending_rewards,
+                penalty_index: v.penalty_index,
+                pending_penalties: v.pending_penalties,
+            })
+        })
+        .collect()
+}
+
+// this will set the first key after the provided key, by appending a 1 byte
+fn calc_range_start(start_after: Option<CanonicalAddr>) -> Option<Vec<u8>> {
+    start_after.map(|addr| {
+        let mut v = addr.as_slice().to_vec();
+        v.push(1);
+        v
+    })
+}
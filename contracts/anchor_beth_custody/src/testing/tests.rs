@@ -0,0 +1,503 @@
+use cosmwasm_bignumber::Decimal256;
+use cosmwasm_std::testing::{mock_env, mock_info, MockApi, MockStorage};
+use cosmwasm_std::{
+    from_binary, to_binary, BankMsg, Coin, CosmosMsg, Decimal, OwnedDeps, StdError, SubMsg,
+    Uint128, WasmMsg,
+};
+
+use crate::contract::{execute, instantiate, query};
+use beth::custody::{
+    AccruedPenaltiesResponse, BorrowerResponse, BorrowersResponse, ConfigResponse, Cw20HookMsg,
+    ExecuteMsg, InstantiateMsg, QueryMsg, StateResponse,
+};
+use beth::mock_querier::{mock_dependencies_with_querier, WasmMockQuerier};
+use beth::reward::{
+    AccruedRewardsResponse, ExecuteMsg as RewardExecuteMsg, HolderResponse,
+    QueryMsg as RewardQueryMsg,
+};
+use cw20::{Cw20ExecuteMsg, Cw20ReceiveMsg};
+
+const MOCK_OWNER_ADDR: &str = "owner0000";
+const MOCK_OVERSEER_ADDR: &str = "overseer0000";
+const MOCK_TOKEN_CONTRACT_ADDR: &str = "token0000";
+const MOCK_REWARD_ADDR: &str = "reward0000";
+
+/// Mock dependencies with a 1% tax capped at 1 UST, the custody has accrued
+/// `accrued_rewards` in the reward contract
+fn mock_dependencies(accrued_rewards: u128) -> OwnedDeps<MockStorage, MockApi, WasmMockQuerier> {
+    let mut deps = mock_dependencies_with_querier(
+        WasmMockQuerier::builder()
+            .with_tax(Decimal::percent(1), &[("uusd", 1000000u128)])
+            .build(),
+    );
+    set_accrued_rewards(&mut deps.querier, accrued_rewards);
+    deps
+}
+
+fn set_accrued_rewards(querier: &mut WasmMockQuerier, accrued_rewards: u128) {
+    set_accrued(querier, accrued_rewards, 0);
+}
+
+/// The custody has accrued `accrued_rewards` uusd and `accrued_penalties` bETH
+/// in the reward contract
+fn set_accrued(querier: &mut WasmMockQuerier, accrued_rewards: u128, accrued_penalties: u128) {
+    querier.update_smart_handler(MOCK_REWARD_ADDR, move |msg| match from_binary(msg)? {
+        RewardQueryMsg::AccruedRewards { .. } => to_binary(&AccruedRewardsResponse {
+            rewards: Uint128::new(accrued_rewards),
+        }),
+        RewardQueryMsg::Holder { address } => to_binary(&HolderResponse {
+            address,
+            balance: Uint128::zero(),
+            index: Decimal256::zero(),
+            pending_rewards: Decimal256::zero(),
+            accrued_rewards: Uint128::new(accrued_rewards),
+            total_claimed: Uint128::zero(),
+            last_claim_time: None,
+            locked_amount: Uint128::zero(),
+            locked_weight: Uint128::zero(),
+            penalty_index: Decimal256::zero(),
+            pending_penalties: Decimal256::zero(),
+            accrued_penalties: Uint128::new(accrued_penalties),
+        }),
+        _ => panic!("unexpected reward query"),
+    });
+}
+
+fn default_init() -> InstantiateMsg {
+    InstantiateMsg {
+        owner: MOCK_OWNER_ADDR.to_string(),
+        collateral_token: MOCK_TOKEN_CONTRACT_ADDR.to_string(),
+        overseer: MOCK_OVERSEER_ADDR.to_string(),
+        reward_contract: MOCK_REWARD_ADDR.to_string(),
+        reward_denom: "uusd".to_string(),
+    }
+}
+
+fn deposit_msg(borrower: &str, amount: u128) -> ExecuteMsg {
+    ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: borrower.to_string(),
+        amount: Uint128::new(amount),
+        msg: to_binary(&Cw20HookMsg::DepositCollateral {}).unwrap(),
+    })
+}
+
+fn claim_custody_rewards_msg() -> SubMsg {
+    SubMsg::new(WasmMsg::Execute {
+        contract_addr: MOCK_REWARD_ADDR.to_string(),
+        msg: to_binary(&RewardExecuteMsg::ClaimRewards { recipient: None }).unwrap(),
+        funds: vec![],
+    })
+}
+
+fn claim_custody_penalties_msg() -> SubMsg {
+    SubMsg::new(WasmMsg::Execute {
+        contract_addr: MOCK_REWARD_ADDR.to_string(),
+        msg: to_binary(&RewardExecuteMsg::ClaimPenalties { recipient: None }).unwrap(),
+        funds: vec![],
+    })
+}
+
+fn query_borrower(
+    deps: &OwnedDeps<MockStorage, MockApi, WasmMockQuerier>,
+    address: &str,
+) -> BorrowerResponse {
+    let msg = QueryMsg::Borrower {
+        address: address.to_string(),
+    };
+    from_binary(&query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap()
+}
+
+#[test]
+fn proper_init() {
+    let mut deps = mock_dependencies(0);
+    let info = mock_info("addr0000", &[]);
+    let res = instantiate(deps.as_mut(), mock_env(), info, default_init()).unwrap();
+    assert_eq!(0, res.messages.len());
+
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap();
+    let config: ConfigResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        config,
+        ConfigResponse {
+            owner: MOCK_OWNER_ADDR.to_string(),
+            collateral_token: MOCK_TOKEN_CONTRACT_ADDR.to_string(),
+            overseer: MOCK_OVERSEER_ADDR.to_string(),
+            reward_contract: MOCK_REWARD_ADDR.to_string(),
+            reward_denom: "uusd".to_string(),
+        }
+    );
+
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::State {}).unwrap();
+    let state: StateResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        state,
+        StateResponse {
+            total_collateral: Uint128::zero(),
+            global_index: Decimal256::zero(),
+            penalty_index: Decimal256::zero(),
+        }
+    );
+}
+
+#[test]
+fn deposit_lock_and_withdraw_collateral() {
+    let mut deps = mock_dependencies(0);
+    let info = mock_info("addr0000", &[]);
+    instantiate(deps.as_mut(), mock_env(), info, default_init()).unwrap();
+
+    // only the collateral token can deposit
+    let info = mock_info("other_token0000", &[]);
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        deposit_msg("addr0000", 100),
+    );
+    assert_eq!(res.unwrap_err(), StdError::generic_err("unauthorized"));
+
+    let info = mock_info(MOCK_TOKEN_CONTRACT_ADDR, &[]);
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        deposit_msg("addr0000", 100),
+    )
+    .unwrap();
+    assert_eq!(res.messages.len(), 0);
+
+    let lock_msg = ExecuteMsg::LockCollateral {
+        borrower: "addr0000".to_string(),
+        amount: Uint128::new(60),
+    };
+    let info = mock_info("addr0000", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, lock_msg.clone());
+    assert_eq!(res.unwrap_err(), StdError::generic_err("unauthorized"));
+
+    let info = mock_info(MOCK_OVERSEER_ADDR, &[]);
+    execute(deps.as_mut(), mock_env(), info, lock_msg.clone()).unwrap();
+    let info = mock_info(MOCK_OVERSEER_ADDR, &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, lock_msg);
+    assert_eq!(
+        res.unwrap_err(),
+        StdError::generic_err("Lock amount cannot exceed the spendable collateral: 40")
+    );
+
+    let msg = ExecuteMsg::WithdrawCollateral {
+        amount: Some(Uint128::new(50)),
+    };
+    let info = mock_info("addr0000", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg);
+    assert_eq!(
+        res.unwrap_err(),
+        StdError::generic_err("Withdraw amount cannot exceed the spendable collateral: 40")
+    );
+
+    // withdraw all the spendable collateral
+    let msg = ExecuteMsg::WithdrawCollateral { amount: None };
+    let info = mock_info("addr0000", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(WasmMsg::Execute {
+            contract_addr: MOCK_TOKEN_CONTRACT_ADDR.to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: "addr0000".to_string(),
+                amount: Uint128::new(40),
+            })
+            .unwrap(),
+            funds: vec![],
+        })]
+    );
+    let borrower = query_borrower(&deps, "addr0000");
+    assert_eq!(borrower.balance, Uint128::new(60));
+    assert_eq!(borrower.spendable, Uint128::zero());
+
+    let unlock_msg = ExecuteMsg::UnlockCollateral {
+        borrower: "addr0000".to_string(),
+        amount: Uint128::new(70),
+    };
+    let info = mock_info(MOCK_OVERSEER_ADDR, &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, unlock_msg);
+    assert_eq!(
+        res.unwrap_err(),
+        StdError::generic_err("Unlock amount cannot exceed the locked collateral: 60")
+    );
+
+    let unlock_msg = ExecuteMsg::UnlockCollateral {
+        borrower: "addr0000".to_string(),
+        amount: Uint128::new(60),
+    };
+    let info = mock_info(MOCK_OVERSEER_ADDR, &[]);
+    execute(deps.as_mut(), mock_env(), info, unlock_msg).unwrap();
+    let borrower = query_borrower(&deps, "addr0000");
+    assert_eq!(borrower.spendable, Uint128::new(60));
+
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::State {}).unwrap();
+    let state: StateResponse = from_binary(&res).unwrap();
+    assert_eq!(state.total_collateral, Uint128::new(60));
+}
+
+#[test]
+fn distribute_and_claim_rewards() {
+    let mut deps = mock_dependencies(0);
+    let info = mock_info("addr0000", &[]);
+    instantiate(deps.as_mut(), mock_env(), info, default_init()).unwrap();
+
+    let info = mock_info(MOCK_TOKEN_CONTRACT_ADDR, &[]);
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        deposit_msg("addr0000", 100),
+    )
+    .unwrap();
+    let info = mock_info(MOCK_TOKEN_CONTRACT_ADDR, &[]);
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        deposit_msg("addr0001", 300),
+    )
+    .unwrap();
+
+    let info = mock_info("addr0002", &[]);
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        ExecuteMsg::DistributeRewards {},
+    );
+    assert_eq!(
+        res.unwrap_err(),
+        StdError::generic_err("No rewards have accrued yet")
+    );
+
+    // the custody receives 1000 uusd net of tax
+    set_accrued_rewards(&mut deps.querier, 1000);
+    let info = mock_info("addr0002", &[]);
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        ExecuteMsg::DistributeRewards {},
+    )
+    .unwrap();
+    assert_eq!(res.messages, vec![claim_custody_rewards_msg()]);
+    set_accrued_rewards(&mut deps.querier, 0);
+
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::State {}).unwrap();
+    let state: StateResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        state.global_index,
+        if cfg!(feature = "terra") {
+            // 990 / 400
+            Decimal256::permille(2475)
+        } else {
+            Decimal256::permille(2500)
+        }
+    );
+
+    let msg = QueryMsg::AccruedRewards {
+        address: "addr0001".to_string(),
+    };
+    let res: AccruedRewardsResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+    assert_eq!(
+        res.rewards,
+        Uint128::new(if cfg!(feature = "terra") { 742 } else { 750 })
+    );
+
+    let msg = ExecuteMsg::ClaimRewards {
+        recipient: Some("addr0003".to_string()),
+    };
+    let info = mock_info("addr0000", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+            to_address: "addr0003".to_string(),
+            amount: vec![Coin {
+                denom: "uusd".to_string(),
+                // 247 with 1% tax on Terra
+                amount: Uint128::new(if cfg!(feature = "terra") { 244 } else { 250 }),
+            }],
+        }))]
+    );
+    // the decimals are kept for the next claim
+    let borrower = query_borrower(&deps, "addr0000");
+    assert_eq!(
+        borrower.pending_rewards,
+        if cfg!(feature = "terra") {
+            Decimal256::percent(50)
+        } else {
+            Decimal256::zero()
+        }
+    );
+
+    let info = mock_info("addr0000", &[]);
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        ExecuteMsg::ClaimRewards { recipient: None },
+    );
+    assert_eq!(
+        res.unwrap_err(),
+        StdError::generic_err("No rewards have accrued yet")
+    );
+
+    // rewards accrued before a deposit only go to the collateral held before
+    set_accrued_rewards(&mut deps.querier, 4000);
+    let info = mock_info(MOCK_TOKEN_CONTRACT_ADDR, &[]);
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        deposit_msg("addr0002", 400),
+    )
+    .unwrap();
+    assert_eq!(res.messages, vec![claim_custody_rewards_msg()]);
+    set_accrued_rewards(&mut deps.querier, 0);
+
+    let borrower = query_borrower(&deps, "addr0002");
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::State {}).unwrap();
+    let state: StateResponse = from_binary(&res).unwrap();
+    assert_eq!(borrower.index, state.global_index);
+    assert_eq!(state.total_collateral, Uint128::new(800));
+
+    let msg = QueryMsg::Borrowers {
+        start_after: None,
+        limit: None,
+    };
+    let res: BorrowersResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+    assert_eq!(res.borrowers.len(), 3);
+}
+
+#[test]
+fn distribute_and_claim_penalties() {
+    let mut deps = mock_dependencies(0);
+    let info = mock_info("addr0000", &[]);
+    instantiate(deps.as_mut(), mock_env(), info, default_init()).unwrap();
+
+    let info = mock_info(MOCK_TOKEN_CONTRACT_ADDR, &[]);
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        deposit_msg("addr0000", 100),
+    )
+    .unwrap();
+    let info = mock_info(MOCK_TOKEN_CONTRACT_ADDR, &[]);
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        deposit_msg("addr0001", 300),
+    )
+    .unwrap();
+
+    // the custody is owed 40 bETH of early unlock penalties and 1000 uusd
+    set_accrued(&mut deps.querier, 1000, 40);
+    let info = mock_info("addr0002", &[]);
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        ExecuteMsg::DistributeRewards {},
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages,
+        vec![claim_custody_rewards_msg(), claim_custody_penalties_msg()]
+    );
+    set_accrued(&mut deps.querier, 0, 0);
+
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::State {}).unwrap();
+    let state: StateResponse = from_binary(&res).unwrap();
+    assert_eq!(state.penalty_index, Decimal256::percent(10));
+
+    let msg = QueryMsg::AccruedPenalties {
+        address: "addr0001".to_string(),
+    };
+    let res: AccruedPenaltiesResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+    assert_eq!(res.penalties, Uint128::new(30));
+
+    // the penalties are paid in bETH without tax
+    let msg = ExecuteMsg::ClaimPenalties {
+        recipient: Some("addr0003".to_string()),
+    };
+    let info = mock_info("addr0000", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(WasmMsg::Execute {
+            contract_addr: MOCK_TOKEN_CONTRACT_ADDR.to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: "addr0003".to_string(),
+                amount: Uint128::new(10),
+            })
+            .unwrap(),
+            funds: vec![],
+        })]
+    );
+    let info = mock_info("addr0000", &[]);
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        ExecuteMsg::ClaimPenalties { recipient: None },
+    );
+    assert_eq!(
+        res.unwrap_err(),
+        StdError::generic_err("No penalties have accrued yet")
+    );
+
+    // penalties accrued before a deposit only go to the collateral held before
+    set_accrued(&mut deps.querier, 0, 80);
+    let info = mock_info(MOCK_TOKEN_CONTRACT_ADDR, &[]);
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        deposit_msg("addr0002", 400),
+    )
+    .unwrap();
+    assert_eq!(res.messages, vec![claim_custody_penalties_msg()]);
+    set_accrued(&mut deps.querier, 0, 0);
+
+    let borrower = query_borrower(&deps, "addr0002");
+    assert_eq!(borrower.penalty_index, Decimal256::percent(30));
+    assert!(borrower.pending_penalties.is_zero());
+
+    let msg = QueryMsg::AccruedPenalties {
+        address: "addr0001".to_string(),
+    };
+    let res: AccruedPenaltiesResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+    assert_eq!(res.penalties, Uint128::new(90));
+}
+
+#[test]
+fn update_config() {
+    let mut deps = mock_dependencies(0);
+    let info = mock_info("addr0000", &[]);
+    instantiate(deps.as_mut(), mock_env(), info, default_init()).unwrap();
+
+    let msg = ExecuteMsg::UpdateConfig {
+        owner: Some("owner0001".to_string()),
+        overseer: Some("overseer0001".to_string()),
+    };
+    let info = mock_info("addr0000", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg.clone());
+    assert_eq!(res.unwrap_err(), StdError::generic_err("unauthorized"));
+
+    let info = mock_info(MOCK_OWNER_ADDR, &[]);
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap();
+    let config: ConfigResponse = from_binary(&res).unwrap();
+    assert_eq!(config.owner, "owner0001");
+    assert_eq!(config.overseer, "overseer0001");
+}
@@ -0,0 +1,348 @@
+#[cfg(not(feature = "library"))]
+use cosmwasm_std::entry_point;
+
+use crate::state::{
+    deployment_response, read_config, read_deployment, read_deployments, read_pending_deployment,
+    remove_pending_deployment, store_config, store_deployment, store_pending_deployment, Config,
+    Deployment, PendingDeployment,
+};
+
+use anchor_beth_token::msg::TokenInstantiateMsg;
+use beth::converter::{
+    ExecuteMsg as ConverterExecuteMsg, InstantiateMsg as ConverterInstantiateMsg,
+};
+use beth::factory::{
+    ConfigResponse, DeploymentResponse, DeploymentsResponse, ExecuteMsg, InstantiateMsg,
+    MigrateMsg, QueryMsg,
+};
+use beth::migration::migrate_contract_version;
+use beth::reward::{ExecuteMsg as RewardExecuteMsg, InstantiateMsg as RewardInstantiateMsg};
+use cosmwasm_std::{
+    attr, to_binary, Binary, CanonicalAddr, Deps, DepsMut, Env, MessageInfo, Reply, Response,
+    StdError, StdResult, SubMsg, SubMsgExecutionResponse, WasmMsg,
+};
+use cw2::set_contract_version;
+use cw20::MinterResponse;
+
+// version info for migration info
+const CONTRACT_NAME: &str = "crates.io:anchor_beth_factory";
+const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+pub const INSTANTIATE_REWARD_REPLY_ID: u64 = 1;
+pub const INSTANTIATE_CONVERTER_REPLY_ID: u64 = 2;
+pub const INSTANTIATE_TOKEN_REPLY_ID: u64 = 3;
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn instantiate(
+    deps: DepsMut,
+    _env: Env,
+    _info: MessageInfo,
+    msg: InstantiateMsg,
+) -> StdResult<Response> {
+    let conf = Config {
+        owner: deps.api.addr_canonicalize(&msg.owner)?,
+        token_code_id: msg.token_code_id,
+        reward_code_id: msg.reward_code_id,
+        converter_code_id: msg.converter_code_id,
+    };
+
+    store_config(deps.storage, &conf)?;
+    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+
+    Ok(Response::default())
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn execute(deps: DepsMut, env: Env, info: MessageInfo, msg: ExecuteMsg) -> StdResult<Response> {
+    match msg {
+        ExecuteMsg::Deploy {
+            asset_name,
+            wormhole_token,
+            token_name,
+            token_symbol,
+            token_decimals,
+            reward_denom,
+        } => {
+            let pending = PendingDeployment {
+                asset_name,
+                wormhole_token: deps.api.addr_canonicalize(&wormhole_token)?,
+                token_name,
+                token_symbol,
+                token_decimals,
+                reward_denom,
+                reward_contract: None,
+                converter_contract: None,
+            };
+            execute_deploy(deps, env, info, pending)
+        }
+        ExecuteMsg::UpdateConfig {
+            owner,
+            token_code_id,
+            reward_code_id,
+            converter_code_id,
+        } => execute_update_config(
+            deps,
+            info,
+            owner,
+            token_code_id,
+            reward_code_id,
+            converter_code_id,
+        ),
+    }
+}
+
+pub fn execute_deploy(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    pending: PendingDeployment,
+) -> StdResult<Response> {
+    let config = read_config(deps.storage)?;
+    if config.owner != deps.api.addr_canonicalize(info.sender.as_str())? {
+        return Err(StdError::generic_err("unauthorized"));
+    }
+
+    if read_deployment(deps.storage, &pending.asset_name)?.is_some() {
+        return Err(StdError::generic_err(format!(
+            "Asset {} is already deployed",
+            pending.asset_name
+        )));
+    }
+
+    // the factory owns the reward contract until it is wired to the token
+    let reward_instantiate = WasmMsg::Instantiate {
+        admin: Some(deps.api.addr_humanize(&config.owner)?.to_string()),
+        code_id: config.reward_code_id,
+        msg: to_binary(&RewardInstantiateMsg {
+            owner: env.contract.address.to_string(),
+            reward_denom: pending.reward_denom.clone(),
+        })?,
+        funds: vec![],
+        label: format!("{} reward", pending.asset_name),
+    };
+    store_pending_deployment(deps.storage, &pending)?;
+
+    Ok(Response::new()
+        .add_submessage(SubMsg::reply_on_success(
+            reward_instantiate,
+            INSTANTIATE_REWARD_REPLY_ID,
+        ))
+        .add_attributes(vec![
+            attr("action", "deploy"),
+            attr("asset_name", pending.asset_name),
+        ]))
+}
+
+pub fn execute_update_config(
+    deps: DepsMut,
+    info: MessageInfo,
+    owner: Option<String>,
+    token_code_id: Option<u64>,
+    reward_code_id: Option<u64>,
+    converter_code_id: Option<u64>,
+) -> StdResult<Response> {
+    let mut config = read_config(deps.storage)?;
+    if config.owner != deps.api.addr_canonicalize(info.sender.as_str())? {
+        return Err(StdError::generic_err("unauthorized"));
+    }
+
+    if let Some(owner) = owner {
+        config.owner = deps.api.addr_canonicalize(&owner)?;
+    }
+    if let Some(token_code_id) = token_code_id {
+        config.token_code_id = token_code_id;
+    }
+    if let Some(reward_code_id) = reward_code_id {
+        config.reward_code_id = reward_code_id;
+    }
+    if let Some(converter_code_id) = converter_code_id {
+        config.converter_code_id = converter_code_id;
+    }
+
+    store_config(deps.storage, &config)?;
+
+    Ok(Response::new().add_attributes(vec![attr("action", "update_config")]))
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn reply(deps: DepsMut, env: Env, msg: Reply) -> StdResult<Response> {
+    let response = msg.result.into_result().map_err(StdError::generic_err)?;
+    let contract_addr = deps
+        .api
+        .addr_canonicalize(&parse_instantiated_address(&response)?)?;
+    let mut pending = read_pending_deployment(deps.storage)?;
+    let config = read_config(deps.storage)?;
+    let owner = deps.api.addr_humanize(&config.owner)?;
+
+    match msg.id {
+        INSTANTIATE_REWARD_REPLY_ID => {
+            pending.reward_contract = Some(contract_addr);
+            store_pending_deployment(deps.storage, &pending)?;
+
+            // the factory registers the tokens once the anchor token exists
+            let converter_instantiate = WasmMsg::Instantiate {
+                admin: Some(owner.to_string()),
+                code_id: config.converter_code_id,
+                msg: to_binary(&ConverterInstantiateMsg {
+                    owner: env.contract.address.to_string(),
+                })?,
+                funds: vec![],
+                label: format!("{} converter", pending.asset_name),
+            };
+            Ok(Response::new().add_submessage(SubMsg::reply_on_success(
+                converter_instantiate,
+                INSTANTIATE_CONVERTER_REPLY_ID,
+            )))
+        }
+        INSTANTIATE_CONVERTER_REPLY_ID => {
+            pending.converter_contract = Some(contract_addr.clone());
+            store_pending_deployment(deps.storage, &pending)?;
+
+            // the converter mints from the start, so the token never needs a migration
+            let token_instantiate = WasmMsg::Instantiate {
+                admin: Some(owner.to_string()),
+                code_id: config.token_code_id,
+                msg: to_binary(&TokenInstantiateMsg {
+                    name: pending.token_name.clone(),
+                    symbol: pending.token_symbol.clone(),
+                    decimals: pending.token_decimals,
+                    initial_balances: vec![],
+                    mint: Some(MinterResponse {
+                        minter: deps.api.addr_humanize(&contract_addr)?.to_string(),
+                        cap: None,
+                    }),
+                    reward_contract: deps
+                        .api
+                        .addr_humanize(&pending_contract(&pending.reward_contract)?)?
+                        .to_string(),
+                    admin: Some(owner.to_string()),
+                    marketing: None,
+                })?,
+                funds: vec![],
+                label: format!("{} token", pending.asset_name),
+            };
+            Ok(Response::new().add_submessage(SubMsg::reply_on_success(
+                token_instantiate,
+                INSTANTIATE_TOKEN_REPLY_ID,
+            )))
+        }
+        INSTANTIATE_TOKEN_REPLY_ID => {
+            let deployment = Deployment {
+                wormhole_token: pending.wormhole_token.clone(),
+                anchor_token: contract_addr,
+                reward_contract: pending_contract(&pending.reward_contract)?,
+                converter_contract: pending_contract(&pending.converter_contract)?,
+            };
+            store_deployment(deps.storage, &pending.asset_name, &deployment)?;
+            remove_pending_deployment(deps.storage);
+            let res = deployment_response(deps.as_ref(), pending.asset_name.clone(), deployment)?;
+
+            Ok(Response::new()
+                .add_messages(vec![
+                    WasmMsg::Execute {
+                        contract_addr: res.reward_contract.clone(),
+                        msg: to_binary(&RewardExecuteMsg::PostInitialize {
+                            token_contract: res.anchor_token.clone(),
+                        })?,
+                        funds: vec![],
+                    },
+                    WasmMsg::Execute {
+                        contract_addr: res.reward_contract.clone(),
+                        msg: to_binary(&RewardExecuteMsg::UpdateConfig {
+                            owner: Some(owner.to_string()),
+                            protocol_fee_rate: None,
+                            treasury: None,
+                        })?,
+                        funds: vec![],
+                    },
+                    WasmMsg::Execute {
+                        contract_addr: res.converter_contract.clone(),
+                        msg: to_binary(&ConverterExecuteMsg::RegisterTokens {
+                            wormhole_token_address: res.wormhole_token.clone(),
+                            anchor_token_address: res.anchor_token.clone(),
+                        })?,
+                        funds: vec![],
+                    },
+                ])
+                .add_attributes(vec![
+                    attr("action", "deployed"),
+                    attr("asset_name", res.asset_name),
+                    attr("anchor_token", res.anchor_token),
+                    attr("reward_contract", res.reward_contract),
+                    attr("converter_contract", res.converter_contract),
+                ]))
+        }
+        id => Err(StdError::generic_err(format!("Unknown reply id: {}", id))),
+    }
+}
+
+// wasmd emits `instantiate._contract_address`, older Terra nodes emit
+// `instantiate_contract.contract_address` and cw-multi-test `instantiate._contract_addr`
+fn parse_instantiated_address(response: &SubMsgExecutionResponse) -> StdResult<String> {
+    response
+        .events
+        .iter()
+        .filter(|event| event.ty == "instantiate" || event.ty == "instantiate_contract")
+        .flat_map(|event| event.attributes.iter())
+        .find(|attr| {
+            attr.key == "_contract_address"
+                || attr.key == "contract_address"
+                || attr.key == "_contract_addr"
+        })
+        .map(|attr| attr.value.clone())
+        .ok_or_else(|| StdError::generic_err("Cannot parse the instantiated contract address"))
+}
+
+fn pending_contract(contract: &Option<CanonicalAddr>) -> StdResult<CanonicalAddr> {
+    contract
+        .clone()
+        .ok_or_else(|| StdError::generic_err("Deployment is not in progress"))
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
+    match msg {
+        QueryMsg::Config {} => to_binary(&query_config(deps)?),
+        QueryMsg::Deployment { asset_name } => to_binary(&query_deployment(deps, asset_name)?),
+        QueryMsg::Deployments { start_after, limit } => {
+            to_binary(&query_deployments(deps, start_after, limit)?)
+        }
+    }
+}
+
+fn query_config(deps: Deps) -> StdResult<ConfigResponse> {
+    let config = read_config(deps.storage)?;
+    Ok(ConfigResponse {
+        owner: deps.api.addr_humanize(&config.owner)?.to_string(),
+        token_code_id: config.token_code_id,
+        reward_code_id: config.reward_code_id,
+        converter_code_id: config.converter_code_id,
+    })
+}
+
+fn query_deployment(deps: Deps, asset_name: String) -> StdResult<DeploymentResponse> {
+    match read_deployment(deps.storage, &asset_name)? {
+        Some(deployment) => deployment_response(deps, asset_name, deployment),
+        None => Err(StdError::generic_err(format!(
+            "Asset {} is not deployed",
+            asset_name
+        ))),
+    }
+}
+
+fn query_deployments(
+    deps: Deps,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<DeploymentsResponse> {
+    Ok(DeploymentsResponse {
+        deployments: read_deployments(deps, start_after, limit)?,
+    })
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn migrate(deps: DepsMut, _env: Env, _msg: MigrateMsg) -> StdResult<Response> {
+    migrate_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION, &[])?;
+
+    Ok(Response::default())
+}
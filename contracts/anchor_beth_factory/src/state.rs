@@ -0,0 +1,135 @@
+use cosmwasm_std::{CanonicalAddr, Deps, Order, StdResult, Storage};
+use cosmwasm_storage::{bucket, bucket_read, singleton, singleton_read, ReadonlyBucket};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use beth::factory::DeploymentResponse;
+
+pub static KEY_CONFIG: &[u8] = b"config";
+pub static KEY_PENDING_DEPLOYMENT: &[u8] = b"pending_deployment";
+
+pub static PREFIX_DEPLOYMENTS: &[u8] = b"deployments";
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Config {
+    pub owner: CanonicalAddr,
+    pub token_code_id: u64,
+    pub reward_code_id: u64,
+    pub converter_code_id: u64,
+}
+
+pub fn store_config(storage: &mut dyn Storage, config: &Config) -> StdResult<()> {
+    singleton(storage, KEY_CONFIG).save(config)
+}
+
+pub fn read_config(storage: &dyn Storage) -> StdResult<Config> {
+    singleton_read(storage, KEY_CONFIG).load()
+}
+
+/// Deployment in progress, the contract addresses are filled in by the replies
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PendingDeployment {
+    pub asset_name: String,
+    pub wormhole_token: CanonicalAddr,
+    pub token_name: String,
+    pub token_symbol: String,
+    pub token_decimals: u8,
+    pub reward_denom: String,
+    pub reward_contract: Option<CanonicalAddr>,
+    pub converter_contract: Option<CanonicalAddr>,
+}
+
+pub fn store_pending_deployment(
+    storage: &mut dyn Storage,
+    pending: &PendingDeployment,
+) -> StdResult<()> {
+    singleton(storage, KEY_PENDING_DEPLOYMENT).save(pending)
+}
+
+pub fn read_pending_deployment(storage: &dyn Storage) -> StdResult<PendingDeployment> {
+    singleton_read(storage, KEY_PENDING_DEPLOYMENT).load()
+}
+
+pub fn remove_pending_deployment(storage: &mut dyn Storage) {
+    singleton::<PendingDeployment>(storage, KEY_PENDING_DEPLOYMENT).remove()
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Deployment {
+    pub wormhole_token: CanonicalAddr,
+    pub anchor_token: CanonicalAddr,
+    pub reward_contract: CanonicalAddr,
+    pub converter_contract: CanonicalAddr,
+}
+
+pub fn store_deployment(
+    storage: &mut dyn Storage,
+    asset_name: &str,
+    deployment: &Deployment,
+) -> StdResult<()> {
+    bucket(storage, PREFIX_DEPLOYMENTS).save(asset_name.as_bytes(), deployment)
+}
+
+pub fn read_deployment(storage: &dyn Storage, asset_name: &str) -> StdResult<Option<Deployment>> {
+    bucket_read(storage, PREFIX_DEPLOYMENTS).may_load(asset_name.as_bytes())
+}
+
+// settings for pagination
+const MAX_LIMIT: u32 = 30;
+const DEFAULT_LIMIT: u32 = 10;
+pub fn read_deployments(
+    deps: Deps,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<Vec<DeploymentResponse>> {
+    let deployment_bucket: ReadonlyBucket<Deployment> =
+        bucket_read(deps.storage, PREFIX_DEPLOYMENTS);
+
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = calc_range_start(start_after);
+
+    deployment_bucket
+        .range(start.as_deref(), None, Order::Ascending)
+        .take(limit)
+        .map(|elem| {
+            let (k, v) = elem?;
+            let asset_name = String::from_utf8(k)?;
+            deployment_response(deps, asset_name, v)
+        })
+        .collect()
+}
+
+pub fn deployment_response(
+    deps: Deps,
+    asset_name: String,
+    deployment: Deployment,
+) -> StdResult<DeploymentResponse> {
+    Ok(DeploymentResponse {
+        asset_name,
+        wormhole_token: deps
+            .api
+            .addr_humanize(&deployment.wormhole_token)?
+            .to_string(),
+        anchor_token: deps
+            .api
+            .addr_humanize(&deployment.anchor_token)?
+            .to_string(),
+        reward_contract: deps
+            .api
+            .addr_humanize(&deployment.reward_contract)?
+            .to_string(),
+        converter_contract: deps
+            .api
+            .addr_humanize(&deployment.converter_contract)?
+            .to_string(),
+    })
+}
+
+// this will set the first key after the provided key, by appending a 1 byte
+fn calc_range_start(start_after: Option<String>) -> Option<Vec<u8>> {
+    start_after.map(|asset_name| {
+        let mut v = asset_name.into_bytes();
+        v.push(1);
+        v
+    })
+}
@@ -0,0 +1,318 @@
+use cosmwasm_std::testing::{mock_env, mock_info, MOCK_CONTRACT_ADDR};
+use cosmwasm_std::{
+    from_binary, to_binary, ContractResult, CosmosMsg, Event, Reply, ReplyOn, StdError, SubMsg,
+    SubMsgExecutionResponse, WasmMsg,
+};
+
+use crate::contract::{
+    execute, instantiate, query, reply, INSTANTIATE_CONVERTER_REPLY_ID,
+    INSTANTIATE_REWARD_REPLY_ID, INSTANTIATE_TOKEN_REPLY_ID,
+};
+use anchor_beth_token::msg::TokenInstantiateMsg;
+use beth::converter::{
+    ExecuteMsg as ConverterExecuteMsg, InstantiateMsg as ConverterInstantiateMsg,
+};
+use beth::factory::{
+    ConfigResponse, DeploymentResponse, DeploymentsResponse, ExecuteMsg, InstantiateMsg, QueryMsg,
+};
+use beth::mock_querier::mock_dependencies;
+use beth::reward::{ExecuteMsg as RewardExecuteMsg, InstantiateMsg as RewardInstantiateMsg};
+use cw20::MinterResponse;
+
+const MOCK_OWNER_ADDR: &str = "owner0000";
+const MOCK_WORMHOLE_TOKEN_ADDR: &str = "wormhole_token0000";
+const MOCK_REWARD_ADDR: &str = "reward0000";
+const MOCK_CONVERTER_ADDR: &str = "converter0000";
+const MOCK_TOKEN_ADDR: &str = "token0000";
+
+fn default_init() -> InstantiateMsg {
+    InstantiateMsg {
+        owner: MOCK_OWNER_ADDR.to_string(),
+        token_code_id: 1,
+        reward_code_id: 2,
+        converter_code_id: 3,
+    }
+}
+
+fn deploy_msg(asset_name: &str) -> ExecuteMsg {
+    ExecuteMsg::Deploy {
+        asset_name: asset_name.to_string(),
+        wormhole_token: MOCK_WORMHOLE_TOKEN_ADDR.to_string(),
+        token_name: "bonded ETH".to_string(),
+        token_symbol: "BETH".to_string(),
+        token_decimals: 6,
+        reward_denom: "uusd".to_string(),
+    }
+}
+
+fn instantiate_reply(id: u64, contract_addr: &str) -> Reply {
+    Reply {
+        id,
+        result: ContractResult::Ok(SubMsgExecutionResponse {
+            events: vec![
+                Event::new("instantiate").add_attribute("_contract_address", contract_addr)
+            ],
+            data: None,
+        }),
+    }
+}
+
+#[test]
+fn proper_init() {
+    let mut deps = mock_dependencies(&[]);
+    let info = mock_info("addr0000", &[]);
+    let res = instantiate(deps.as_mut(), mock_env(), info, default_init()).unwrap();
+    assert_eq!(0, res.messages.len());
+
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap();
+    let config: ConfigResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        config,
+        ConfigResponse {
+            owner: MOCK_OWNER_ADDR.to_string(),
+            token_code_id: 1,
+            reward_code_id: 2,
+            converter_code_id: 3,
+        }
+    );
+}
+
+#[test]
+fn deploy() {
+    let mut deps = mock_dependencies(&[]);
+    let info = mock_info("addr0000", &[]);
+    instantiate(deps.as_mut(), mock_env(), info, default_init()).unwrap();
+
+    let info = mock_info("addr0000", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, deploy_msg("beth"));
+    assert_eq!(res.unwrap_err(), StdError::generic_err("unauthorized"));
+
+    // the reward contract is owned by the factory until it is wired
+    let info = mock_info(MOCK_OWNER_ADDR, &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, deploy_msg("beth")).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg {
+            id: INSTANTIATE_REWARD_REPLY_ID,
+            msg: CosmosMsg::Wasm(WasmMsg::Instantiate {
+                admin: Some(MOCK_OWNER_ADDR.to_string()),
+                code_id: 2,
+                msg: to_binary(&RewardInstantiateMsg {
+                    owner: MOCK_CONTRACT_ADDR.to_string(),
+                    reward_denom: "uusd".to_string(),
+                })
+                .unwrap(),
+                funds: vec![],
+                label: "beth reward".to_string(),
+            }),
+            gas_limit: None,
+            reply_on: ReplyOn::Success,
+        }]
+    );
+
+    let res = reply(
+        deps.as_mut(),
+        mock_env(),
+        instantiate_reply(INSTANTIATE_REWARD_REPLY_ID, MOCK_REWARD_ADDR),
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::reply_on_success(
+            WasmMsg::Instantiate {
+                admin: Some(MOCK_OWNER_ADDR.to_string()),
+                code_id: 3,
+                msg: to_binary(&ConverterInstantiateMsg {
+                    owner: MOCK_CONTRACT_ADDR.to_string(),
+                })
+                .unwrap(),
+                funds: vec![],
+                label: "beth converter".to_string(),
+            },
+            INSTANTIATE_CONVERTER_REPLY_ID,
+        )]
+    );
+
+    // the converter is the minter of the token
+    let res = reply(
+        deps.as_mut(),
+        mock_env(),
+        instantiate_reply(INSTANTIATE_CONVERTER_REPLY_ID, MOCK_CONVERTER_ADDR),
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::reply_on_success(
+            WasmMsg::Instantiate {
+                admin: Some(MOCK_OWNER_ADDR.to_string()),
+                code_id: 1,
+                msg: to_binary(&TokenInstantiateMsg {
+                    name: "bonded ETH".to_string(),
+                    symbol: "BETH".to_string(),
+                    decimals: 6,
+                    initial_balances: vec![],
+                    mint: Some(MinterResponse {
+                        minter: MOCK_CONVERTER_ADDR.to_string(),
+                        cap: None,
+                    }),
+                    reward_contract: MOCK_REWARD_ADDR.to_string(),
+                    admin: Some(MOCK_OWNER_ADDR.to_string()),
+                    marketing: None,
+                })
+                .unwrap(),
+                funds: vec![],
+                label: "beth token".to_string(),
+            },
+            INSTANTIATE_TOKEN_REPLY_ID,
+        )]
+    );
+
+    // older Terra nodes emit a different event
+    let token_reply = Reply {
+        id: INSTANTIATE_TOKEN_REPLY_ID,
+        result: ContractResult::Ok(SubMsgExecutionResponse {
+            events: vec![Event::new("instantiate_contract")
+                .add_attribute("contract_address", MOCK_TOKEN_ADDR)],
+            data: None,
+        }),
+    };
+    let res = reply(deps.as_mut(), mock_env(), token_reply).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![
+            SubMsg::new(WasmMsg::Execute {
+                contract_addr: MOCK_REWARD_ADDR.to_string(),
+                msg: to_binary(&RewardExecuteMsg::PostInitialize {
+                    token_contract: MOCK_TOKEN_ADDR.to_string(),
+                })
+                .unwrap(),
+                funds: vec![],
+            }),
+            SubMsg::new(WasmMsg::Execute {
+                contract_addr: MOCK_REWARD_ADDR.to_string(),
+                msg: to_binary(&RewardExecuteMsg::UpdateConfig {
+                    owner: Some(MOCK_OWNER_ADDR.to_string()),
+                    protocol_fee_rate: None,
+                    treasury: None,
+                })
+                .unwrap(),
+                funds: vec![],
+            }),
+            SubMsg::new(WasmMsg::Execute {
+                contract_addr: MOCK_CONVERTER_ADDR.to_string(),
+                msg: to_binary(&ConverterExecuteMsg::RegisterTokens {
+                    wormhole_token_address: MOCK_WORMHOLE_TOKEN_ADDR.to_string(),
+                    anchor_token_address: MOCK_TOKEN_ADDR.to_string(),
+                })
+                .unwrap(),
+                funds: vec![],
+            }),
+        ]
+    );
+
+    let deployment = DeploymentResponse {
+        asset_name: "beth".to_string(),
+        wormhole_token: MOCK_WORMHOLE_TOKEN_ADDR.to_string(),
+        anchor_token: MOCK_TOKEN_ADDR.to_string(),
+        reward_contract: MOCK_REWARD_ADDR.to_string(),
+        converter_contract: MOCK_CONVERTER_ADDR.to_string(),
+    };
+    let msg = QueryMsg::Deployment {
+        asset_name: "beth".to_string(),
+    };
+    let res: DeploymentResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+    assert_eq!(res, deployment);
+
+    let msg = QueryMsg::Deployments {
+        start_after: None,
+        limit: None,
+    };
+    let res: DeploymentsResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+    assert_eq!(res.deployments, vec![deployment]);
+
+    // every asset is deployed once, others follow the same path
+    let info = mock_info(MOCK_OWNER_ADDR, &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, deploy_msg("beth"));
+    assert_eq!(
+        res.unwrap_err(),
+        StdError::generic_err("Asset beth is already deployed")
+    );
+    let info = mock_info(MOCK_OWNER_ADDR, &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, deploy_msg("bsol")).unwrap();
+    assert_eq!(res.messages[0].id, INSTANTIATE_REWARD_REPLY_ID);
+
+    let msg = QueryMsg::Deployment {
+        asset_name: "bsol".to_string(),
+    };
+    let res = query(deps.as_ref(), mock_env(), msg);
+    assert_eq!(
+        res.unwrap_err(),
+        StdError::generic_err("Asset bsol is not deployed")
+    );
+}
+
+#[test]
+fn reply_without_contract_address() {
+    let mut deps = mock_dependencies(&[]);
+    let info = mock_info("addr0000", &[]);
+    instantiate(deps.as_mut(), mock_env(), info, default_init()).unwrap();
+    let info = mock_info(MOCK_OWNER_ADDR, &[]);
+    execute(deps.as_mut(), mock_env(), info, deploy_msg("beth")).unwrap();
+
+    let msg = Reply {
+        id: INSTANTIATE_REWARD_REPLY_ID,
+        result: ContractResult::Ok(SubMsgExecutionResponse {
+            events: vec![Event::new("wasm").add_attribute("_contract_address", MOCK_REWARD_ADDR)],
+            data: None,
+        }),
+    };
+    let res = reply(deps.as_mut(), mock_env(), msg);
+    assert_eq!(
+        res.unwrap_err(),
+        StdError::generic_err("Cannot parse the instantiated contract address")
+    );
+
+    let res = reply(
+        deps.as_mut(),
+        mock_env(),
+        instantiate_reply(4, MOCK_REWARD_ADDR),
+    );
+    assert_eq!(
+        res.unwrap_err(),
+        StdError::generic_err("Unknown reply id: 4")
+    );
+}
+
+#[test]
+fn update_config() {
+    let mut deps = mock_dependencies(&[]);
+    let info = mock_info("addr0000", &[]);
+    instantiate(deps.as_mut(), mock_env(), info, default_init()).unwrap();
+
+    let msg = ExecuteMsg::UpdateConfig {
+        owner: Some("owner0001".to_string()),
+        token_code_id: Some(4),
+        reward_code_id: None,
+        converter_code_id: None,
+    };
+    let info = mock_info("addr0000", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg.clone());
+    assert_eq!(res.unwrap_err(), StdError::generic_err("unauthorized"));
+
+    let info = mock_info(MOCK_OWNER_ADDR, &[]);
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap();
+    let config: ConfigResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        config,
+        ConfigResponse {
+            owner: "owner0001".to_string(),
+            token_code_id: 4,
+            reward_code_id: 2,
+            converter_code_id: 3,
+        }
+    );
+}
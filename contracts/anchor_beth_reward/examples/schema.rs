@@ -2,8 +2,10 @@ use std::env::current_dir;
 use std::fs::create_dir_all;
 
 use beth::reward::{
-    AccruedRewardsResponse, ConfigResponse, ExecuteMsg, HolderResponse, HoldersResponse,
-    InstantiateMsg, QueryMsg, StateResponse,
+    AccruedRewardsResponse, ConfigResponse, Cw20HookMsg, ExecuteMsg, ExportStateResponse,
+    GlobalIndexAtResponse, HolderResponse, HoldersResponse, InstantiateMsg, LockConfigResponse,
+    LockPositionsResponse, QueryMsg, RewardHistoryResponse, RewardRateResponse, SolvencyResponse,
+    StateResponse, StatsResponse, WeightResponse,
 };
 use cosmwasm_schema::{export_schema, remove_schemas, schema_for};
 
@@ -16,9 +18,19 @@ fn main() {
     export_schema(&schema_for!(InstantiateMsg), &out_dir);
     export_schema(&schema_for!(ExecuteMsg), &out_dir);
     export_schema(&schema_for!(QueryMsg), &out_dir);
+    export_schema(&schema_for!(Cw20HookMsg), &out_dir);
     export_schema(&schema_for!(ConfigResponse), &out_dir);
     export_schema(&schema_for!(StateResponse), &out_dir);
+    export_schema(&schema_for!(StatsResponse), &out_dir);
+    export_schema(&schema_for!(SolvencyResponse), &out_dir);
     export_schema(&schema_for!(AccruedRewardsResponse), &out_dir);
     export_schema(&schema_for!(HolderResponse), &out_dir);
     export_schema(&schema_for!(HoldersResponse), &out_dir);
+    export_schema(&schema_for!(ExportStateResponse), &out_dir);
+    export_schema(&schema_for!(LockConfigResponse), &out_dir);
+    export_schema(&schema_for!(LockPositionsResponse), &out_dir);
+    export_schema(&schema_for!(WeightResponse), &out_dir);
+    export_schema(&schema_for!(RewardHistoryResponse), &out_dir);
+    export_schema(&schema_for!(GlobalIndexAtResponse), &out_dir);
+    export_schema(&schema_for!(RewardRateResponse), &out_dir);
 }
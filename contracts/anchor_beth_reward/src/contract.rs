@@ -1,21 +1,45 @@
 #[cfg(not(feature = "library"))]
 use cosmwasm_std::entry_point;
 
-use crate::owner::{execute_post_initialize, execute_update_config};
+use crate::history::{
+    query_global_index_at, query_reward_history, query_reward_rate, record_global_index,
+};
+use crate::lock::{
+    execute_claim_penalties, execute_lock, execute_unlock, export_lock_positions,
+    query_lock_config, query_lock_positions, query_weight,
+};
+use crate::math::decimal_from_uint128;
+use crate::owner::{
+    execute_import_holders, execute_post_initialize, execute_prune_holders, execute_update_config,
+    execute_update_lock_config, ImportedHolder,
+};
 use crate::state::{
-    read_config, read_state, store_config, store_contract_addr, store_state, Config, State,
+    read_config, read_legacy_config, read_state, read_stats, store_config, store_contract_addr,
+    store_state, Claimant, Config, Holder, LockPosition, State,
 };
 use crate::user::{
-    execute_claim_rewards, execute_decrease_balance, execute_increase_balance,
-    query_accrued_rewards, query_holder, query_holders,
+    execute_batch_transfer_balance, execute_claim_rewards, execute_decrease_balance,
+    execute_deposit_rewards, execute_increase_balance, execute_withdraw_protocol_fee,
+    query_accrued_rewards, query_holder, query_holders, query_holders_by_addresses, query_solvency,
+    query_top_holders,
+};
+use beth::migration::{is_older_than, migrate_contract_version};
+use beth::reward::{
+    ConfigResponse, Cw20HookMsg, ExecuteMsg, ExportStateResponse, InstantiateMsg, MigrateMsg,
+    QueryMsg, StateResponse, StatsResponse, EXPORT_VERSION,
 };
-use beth::reward::{ConfigResponse, ExecuteMsg, InstantiateMsg, QueryMsg, StateResponse};
+use beth::CustomMsg;
+use cosmwasm_bignumber::Decimal256;
 use cosmwasm_std::{
-    to_binary, Addr, Api, Binary, Decimal, Deps, DepsMut, Env, MessageInfo, Response, StdResult,
-    Uint128,
+    from_binary, to_binary, Addr, Api, Binary, Decimal, Deps, DepsMut, Env, MessageInfo, Response,
+    StdError, StdResult, Uint128,
 };
+use cw2::{set_contract_version, CONTRACT};
+use cw20::Cw20ReceiveMsg;
 
-use terra_cosmwasm::TerraMsgWrapper;
+// version info for migration info
+const CONTRACT_NAME: &str = "crates.io:anchor_beth_reward";
+const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
 
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn instantiate(
@@ -28,16 +52,33 @@ pub fn instantiate(
         owner: deps.api.addr_canonicalize(&msg.owner)?,
         reward_denom: msg.reward_denom,
         token_contract: None,
+        protocol_fee_rate: Decimal::zero(),
+        treasury: None,
+    };
+
+    let state = State {
+        global_index: Decimal256::zero(),
+        total_balance: Uint128::zero(),
+        prev_reward_balance: Uint128::zero(),
+        total_locked: Uint128::zero(),
+        total_locked_weight: Uint128::zero(),
+        lock_penalty_index: Decimal256::zero(),
+        protocol_fee: Uint128::zero(),
+        holder_count: 0,
+        holders_indexed: true,
+        holder_index_cursor: None,
+        total_liabilities: Decimal256::zero(),
+        reward_dust: Decimal256::zero(),
     };
 
     store_config(deps.storage, &conf)?;
-    store_state(
+    store_state(deps.storage, &state)?;
+    record_global_index(
         deps.storage,
-        &State {
-            global_index: Decimal::zero(),
-            total_balance: Uint128::zero(),
-            prev_reward_balance: Uint128::zero(),
-        },
+        &env.block,
+        &state,
+        Uint128::zero(),
+        Uint128::zero(),
     )?;
 
     // keep contract address in state to be able to use it in queries
@@ -45,6 +86,7 @@ pub fn instantiate(
         deps.storage,
         &deps.api.addr_canonicalize(env.contract.address.as_str())?,
     )?;
+    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
 
     Ok(Response::default())
 }
@@ -55,20 +97,110 @@ pub fn execute(
     env: Env,
     info: MessageInfo,
     msg: ExecuteMsg,
-) -> StdResult<Response<TerraMsgWrapper>> {
+) -> StdResult<Response<CustomMsg>> {
     match msg {
         ExecuteMsg::ClaimRewards { recipient } => {
             let api = deps.api;
             execute_claim_rewards(deps, env, info, optional_addr_validate(api, recipient)?)
         }
+        ExecuteMsg::DepositRewards {} => execute_deposit_rewards(deps, env, info),
+        ExecuteMsg::WithdrawProtocolFee {} => execute_withdraw_protocol_fee(deps, env, info),
+        ExecuteMsg::ClaimPenalties { recipient } => {
+            let api = deps.api;
+            execute_claim_penalties(deps, info, optional_addr_validate(api, recipient)?)
+        }
         ExecuteMsg::PostInitialize { token_contract } => {
             let token_addr = deps.api.addr_validate(&token_contract)?;
             execute_post_initialize(deps, info, token_addr)
         }
-        ExecuteMsg::UpdateConfig { owner } => {
-            let owner_addr = deps.api.addr_validate(&owner)?;
-            execute_update_config(deps, info, owner_addr)
+        ExecuteMsg::UpdateConfig {
+            owner,
+            protocol_fee_rate,
+            treasury,
+        } => {
+            let api = deps.api;
+            execute_update_config(
+                deps,
+                env,
+                info,
+                optional_addr_validate(api, owner)?,
+                protocol_fee_rate,
+                optional_addr_validate(api, treasury)?,
+            )
+        }
+        ExecuteMsg::ImportHolders {
+            state,
+            holders,
+            lock_positions,
+        } => {
+            let holders = holders
+                .into_iter()
+                .map(|holder| {
+                    let positions = lock_positions
+                        .iter()
+                        .filter(|position| position.address == holder.address)
+                        .map(|position| {
+                            (
+                                position.lock_id,
+                                LockPosition {
+                                    amount: position.amount,
+                                    weight: position.weight,
+                                    start: position.start,
+                                    end: position.end,
+                                },
+                            )
+                        })
+                        .collect();
+                    Ok(ImportedHolder {
+                        address: deps.api.addr_validate(&holder.address)?,
+                        holder: Holder {
+                            balance: holder.balance,
+                            index: holder.index,
+                            pending_rewards: holder.pending_rewards,
+                            locked_amount: holder.locked_amount,
+                            locked_weight: holder.locked_weight,
+                            counted: false,
+                            penalty_index: holder.penalty_index,
+                            pending_penalties: holder.pending_penalties,
+                        },
+                        claimant: holder.last_claim_time.map(|last_claim_time| Claimant {
+                            total_claimed: holder.total_claimed,
+                            last_claim_time,
+                        }),
+                        lock_positions: positions,
+                    })
+                })
+                .collect::<StdResult<Vec<ImportedHolder>>>()?;
+            // a page carries the lock positions of its own holders only
+            if let Some(position) = lock_positions.iter().find(|position| {
+                !holders
+                    .iter()
+                    .any(|imported| imported.address.as_str() == position.address)
+            }) {
+                return Err(StdError::generic_err(format!(
+                    "Lock position {} of {} is not in the imported holders",
+                    position.lock_id, position.address
+                )));
+            }
+            execute_import_holders(deps, env, info, state, holders)
         }
+        ExecuteMsg::PruneHolders { start_after, limit } => {
+            let api = deps.api;
+            execute_prune_holders(deps, info, optional_addr_validate(api, start_after)?, limit)
+        }
+        ExecuteMsg::UpdateLockConfig {
+            max_lock_duration,
+            max_boost,
+            early_unlock_penalty,
+        } => execute_update_lock_config(
+            deps,
+            info,
+            max_lock_duration,
+            max_boost,
+            early_unlock_penalty,
+        ),
+        ExecuteMsg::Receive(msg) => receive_cw20(deps, env, info, msg),
+        ExecuteMsg::Unlock { lock_id } => execute_unlock(deps, env, info, lock_id),
         ExecuteMsg::IncreaseBalance { address, amount } => {
             let addr = deps.api.addr_validate(&address)?;
             execute_increase_balance(deps, env, info, addr, amount)
@@ -77,6 +209,36 @@ pub fn execute(
             let addr = deps.api.addr_validate(&address)?;
             execute_decrease_balance(deps, env, info, addr, amount)
         }
+        ExecuteMsg::BatchTransferBalance { from, transfers } => {
+            let from_addr = deps.api.addr_validate(&from)?;
+            let transfers = transfers
+                .into_iter()
+                .map(|(address, amount)| Ok((deps.api.addr_validate(&address)?, amount)))
+                .collect::<StdResult<Vec<(Addr, Uint128)>>>()?;
+            execute_batch_transfer_balance(deps, env, info, from_addr, transfers)
+        }
+    }
+}
+
+pub fn receive_cw20(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    cw20_msg: Cw20ReceiveMsg,
+) -> StdResult<Response<CustomMsg>> {
+    match from_binary(&cw20_msg.msg) {
+        Ok(Cw20HookMsg::Lock { duration }) => {
+            // only the bETH token contract can execute this message
+            let config = read_config(deps.storage)?;
+            let sender = deps.api.addr_canonicalize(info.sender.as_str())?;
+            if config.token_contract != Some(sender) {
+                return Err(StdError::generic_err("unauthorized"));
+            }
+
+            let holder_addr = deps.api.addr_validate(&cw20_msg.sender)?;
+            execute_lock(deps, env, holder_addr, cw20_msg.amount, duration)
+        }
+        Err(err) => Err(err),
     }
 }
 
@@ -91,10 +253,12 @@ fn optional_addr_validate(api: &dyn Api, addr: Option<String>) -> StdResult<Opti
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
+pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
     match msg {
         QueryMsg::Config {} => to_binary(&query_config(deps)?),
         QueryMsg::State {} => to_binary(&query_state(deps)?),
+        QueryMsg::Stats {} => to_binary(&query_stats(deps)?),
+        QueryMsg::Solvency {} => to_binary(&query_solvency(deps)?),
         QueryMsg::AccruedRewards { address } => {
             let addr = deps.api.addr_validate(&address)?;
             to_binary(&query_accrued_rewards(deps, addr)?)
@@ -103,14 +267,57 @@ pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
             let addr = deps.api.addr_validate(&address)?;
             to_binary(&query_holder(deps, addr)?)
         }
-        QueryMsg::Holders { start_after, limit } => {
+        QueryMsg::Holders {
+            start_after,
+            start_before,
+            limit,
+            order,
+        } => {
             let api = deps.api;
             to_binary(&query_holders(
+                deps,
+                optional_addr_validate(api, start_after)?,
+                optional_addr_validate(api, start_before)?,
+                limit,
+                order,
+            )?)
+        }
+        QueryMsg::HoldersByAddresses { addresses } => {
+            let addresses = addresses
+                .iter()
+                .map(|address| deps.api.addr_validate(address))
+                .collect::<StdResult<Vec<Addr>>>()?;
+            to_binary(&query_holders_by_addresses(deps, addresses)?)
+        }
+        QueryMsg::TopHolders { limit } => to_binary(&query_top_holders(deps, limit)?),
+        QueryMsg::ExportState { start_after, limit } => {
+            let api = deps.api;
+            to_binary(&query_export_state(
                 deps,
                 optional_addr_validate(api, start_after)?,
                 limit,
             )?)
         }
+        QueryMsg::LockConfig {} => to_binary(&query_lock_config(deps)?),
+        QueryMsg::LockPositions {
+            address,
+            start_after,
+            limit,
+        } => {
+            let addr = deps.api.addr_validate(&address)?;
+            to_binary(&query_lock_positions(deps, addr, start_after, limit)?)
+        }
+        QueryMsg::Weight { address } => {
+            let addr = deps.api.addr_validate(&address)?;
+            to_binary(&query_weight(deps, addr)?)
+        }
+        QueryMsg::RewardHistory { start_after, limit } => {
+            to_binary(&query_reward_history(deps, start_after, limit)?)
+        }
+        QueryMsg::GlobalIndexAt { height } => to_binary(&query_global_index_at(deps, height)?),
+        QueryMsg::RewardRate { window_seconds } => {
+            to_binary(&query_reward_rate(deps, env, window_seconds)?)
+        }
     }
 }
 
@@ -120,11 +327,16 @@ fn query_config(deps: Deps) -> StdResult<ConfigResponse> {
         owner: deps.api.addr_humanize(&config.owner)?.to_string(),
         reward_denom: config.reward_denom,
         token_contract: None,
+        protocol_fee_rate: config.protocol_fee_rate,
+        treasury: None,
     };
 
     if let Some(token_contract) = config.token_contract {
         res.token_contract = Some(deps.api.addr_humanize(&token_contract)?.to_string());
     }
+    if let Some(treasury) = config.treasury {
+        res.treasury = Some(deps.api.addr_humanize(&treasury)?.to_string());
+    }
 
     Ok(res)
 }
@@ -135,5 +347,92 @@ fn query_state(deps: Deps) -> StdResult<StateResponse> {
         global_index: state.global_index,
         total_balance: state.total_balance,
         prev_reward_balance: state.prev_reward_balance,
+        total_locked: state.total_locked,
+        total_locked_weight: state.total_locked_weight,
+        total_effective_balance: state.total_effective_balance(),
+        lock_penalty_index: state.lock_penalty_index,
+        protocol_fee: state.protocol_fee,
+        holder_count: state.holder_count,
+        holders_indexed: state.holders_indexed,
     })
 }
+
+fn query_stats(deps: Deps) -> StdResult<StatsResponse> {
+    let stats = read_stats(deps.storage)?;
+    Ok(StatsResponse {
+        total_rewards: stats.total_rewards,
+        total_claimed: stats.total_claimed,
+        total_claimed_net: stats.total_claimed_net,
+        total_tax: stats.total_tax,
+        claims: stats.claims,
+        claimants: stats.claimants,
+    })
+}
+
+fn query_export_state(
+    deps: Deps,
+    start_after: Option<Addr>,
+    limit: Option<u32>,
+) -> StdResult<ExportStateResponse> {
+    let holders = query_holders(deps, start_after, None, limit, None)?.holders;
+    Ok(ExportStateResponse {
+        version: EXPORT_VERSION,
+        contract: CONTRACT.may_load(deps.storage)?,
+        config: query_config(deps)?,
+        state: query_state(deps)?,
+        lock_positions: export_lock_positions(deps, &holders)?,
+        holders,
+    })
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn migrate(deps: DepsMut, env: Env, _msg: MigrateMsg) -> StdResult<Response> {
+    let previous_version =
+        migrate_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION, &[])?;
+    if is_older_than(&previous_version, "1.3.0")? {
+        migrate_to_1_3_0(deps, env)?;
+    }
+
+    Ok(Response::default())
+}
+
+/// 1.3.0 adds the protocol fee to the config, stores the indexes and the pending rewards
+/// as Decimal256, tracks the liabilities and the reward history, and counts the holders
+fn migrate_to_1_3_0(deps: DepsMut, env: Env) -> StdResult<()> {
+    let legacy_config = read_legacy_config(deps.storage)?;
+    store_config(
+        deps.storage,
+        &Config {
+            owner: legacy_config.owner,
+            token_contract: legacy_config.token_contract,
+            reward_denom: legacy_config.reward_denom,
+            protocol_fee_rate: legacy_config.protocol_fee_rate,
+            treasury: legacy_config.treasury,
+        },
+    )?;
+
+    // the lock fields of the state and the holders default to zero. Decimal256 reads the
+    // decimal strings of the 128-bit Decimal, so the state is stored again in the new
+    // layout and the holders convert the next time they are stored
+    let mut state = read_state(deps.storage)?;
+    // the earlier versions owe the holders the recognized rewards that are not claimed yet,
+    // their rounding dust included
+    state.total_liabilities =
+        decimal_from_uint128(state.prev_reward_balance.checked_sub(state.protocol_fee)?);
+    state.reward_dust = Decimal256::zero();
+    // the existing holders are counted by a `PruneHolders` pass
+    state.holders_indexed = false;
+    state.holder_index_cursor = None;
+    store_state(deps.storage, &state)?;
+
+    // the reward history of the earlier versions starts at the migration
+    record_global_index(
+        deps.storage,
+        &env.block,
+        &state,
+        Uint128::zero(),
+        Uint128::zero(),
+    )?;
+
+    Ok(())
+}
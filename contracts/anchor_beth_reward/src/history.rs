@@ -0,0 +1,162 @@
+use crate::math::{
+    decimal_division_in_256, decimal_from_uint128, decimal_multiplication_in_256,
+    decimal_split_in_256, decimal_summation_in_256,
+};
+use crate::state::{
+    read_global_index_at, read_reward_checkpoints, read_reward_checkpoints_after, read_state,
+    store_reward_checkpoint, RewardCheckpoint, State,
+};
+
+use beth::reward::{
+    GlobalIndexAtResponse, RewardCheckpointResponse, RewardHistoryResponse, RewardRateResponse,
+};
+use cosmwasm_bignumber::Decimal256;
+use cosmwasm_std::{BlockInfo, Deps, Env, StdError, StdResult, Storage, Uint128};
+
+const SECONDS_PER_YEAR: u64 = 365 * 24 * 60 * 60;
+// bounds the gas of the reward rate query whatever the window
+const MAX_RATE_CHECKPOINTS: usize = 100;
+
+/// Record the global index of `state` at `block`, after it recognized `amount` of rewards
+pub fn record_global_index(
+    storage: &mut dyn Storage,
+    block: &BlockInfo,
+    state: &State,
+    amount: Uint128,
+    protocol_fee: Uint128,
+) -> StdResult<()> {
+    store_reward_checkpoint(
+        storage,
+        &RewardCheckpoint {
+            height: block.height,
+            time: block.time.seconds(),
+            amount,
+            protocol_fee,
+            total_balance: state.total_effective_balance(),
+            global_index: state.global_index,
+        },
+    )?;
+    Ok(())
+}
+
+pub fn query_reward_history(
+    deps: Deps,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<RewardHistoryResponse> {
+    let checkpoints = read_reward_checkpoints(deps.storage, start_after, limit)?
+        .into_iter()
+        .map(|(id, checkpoint)| RewardCheckpointResponse {
+            id,
+            height: checkpoint.height,
+            time: checkpoint.time,
+            amount: checkpoint.amount,
+            protocol_fee: checkpoint.protocol_fee,
+            total_balance: checkpoint.total_balance,
+            global_index: checkpoint.global_index,
+        })
+        .collect();
+
+    Ok(RewardHistoryResponse { checkpoints })
+}
+
+pub fn query_global_index_at(deps: Deps, height: u64) -> StdResult<GlobalIndexAtResponse> {
+    let global_index = read_global_index_at(deps.storage, height)?.ok_or_else(|| {
+        StdError::generic_err(format!(
+            "No global index recorded at or before height {}",
+            height
+        ))
+    })?;
+
+    Ok(GlobalIndexAtResponse {
+        height,
+        global_index,
+    })
+}
+
+/// Rewards distributed to the holders over the last `window_seconds`, and their annualized
+/// rate per unit of reward weight. The window is cut to the start of the history, or to the
+/// last `MAX_RATE_CHECKPOINTS` checkpoints.
+pub fn query_reward_rate(
+    deps: Deps,
+    env: Env,
+    window_seconds: u64,
+) -> StdResult<RewardRateResponse> {
+    if window_seconds == 0 {
+        return Err(StdError::generic_err("Window must be greater than 0"));
+    }
+
+    let state = read_state(deps.storage)?;
+    let end_time = env.block.time.seconds();
+    let window_start = end_time.saturating_sub(window_seconds);
+    let (mut checkpoints, complete) =
+        read_reward_checkpoints_after(deps.storage, window_start, MAX_RATE_CHECKPOINTS)?;
+    // nothing is known of the period before the checkpoints read, the oldest one only
+    // starts the window since its rewards were received before it
+    let oldest = if complete { None } else { checkpoints.pop() };
+
+    // the rewards of a checkpoint were split over its total balance, which weighs the period
+    // since the previous checkpoint. The current total balance weighs the period since the last.
+    let mut rewards = Uint128::zero();
+    let mut weighted_balance = Decimal256::zero();
+    let mut period_end = end_time;
+    let mut total_balance = state.total_effective_balance();
+    for checkpoint in checkpoints {
+        weighted_balance = decimal_summation_in_256(
+            weighted_balance,
+            weighted_period(total_balance, period_end.saturating_sub(checkpoint.time))?,
+        )?;
+        rewards = rewards.checked_add(checkpoint.amount.checked_sub(checkpoint.protocol_fee)?)?;
+        period_end = checkpoint.time;
+        total_balance = checkpoint.total_balance;
+    }
+
+    let start_time = match oldest {
+        Some(oldest) => oldest.time,
+        None if complete => window_start,
+        None => period_end,
+    };
+    weighted_balance = decimal_summation_in_256(
+        weighted_balance,
+        weighted_period(total_balance, period_end.saturating_sub(start_time))?,
+    )?;
+
+    let elapsed = end_time.saturating_sub(start_time);
+    let average_total_balance = if elapsed == 0 {
+        Uint128::zero()
+    } else {
+        decimal_split_in_256(decimal_division_in_256(
+            weighted_balance,
+            decimal_from_uint128(Uint128::from(elapsed)),
+        )?)?
+        .0
+    };
+    // rewards per year / average total balance
+    let annualized_rate = if weighted_balance.is_zero() {
+        Decimal256::zero()
+    } else {
+        decimal_division_in_256(
+            decimal_multiplication_in_256(
+                decimal_from_uint128(rewards),
+                decimal_from_uint128(Uint128::from(SECONDS_PER_YEAR)),
+            )?,
+            weighted_balance,
+        )?
+    };
+
+    Ok(RewardRateResponse {
+        start_time,
+        end_time,
+        rewards,
+        average_total_balance,
+        annualized_rate,
+        complete,
+    })
+}
+
+fn weighted_period(total_balance: Uint128, seconds: u64) -> StdResult<Decimal256> {
+    decimal_multiplication_in_256(
+        decimal_from_uint128(total_balance),
+        decimal_from_uint128(Uint128::from(seconds)),
+    )
+}
@@ -1,6 +1,8 @@
 pub mod contract;
 pub mod state;
 
+mod history;
+mod lock;
 mod math;
 mod owner;
 mod user;
@@ -0,0 +1,335 @@
+use crate::math::{
+    decimal_from_ratio_in_256, decimal_from_uint128, decimal_multiplication_in_256,
+    decimal_split_in_256, decimal_summation_in_256,
+};
+use crate::state::{
+    next_lock_id, read_config, read_holder, read_lock_config, read_lock_position,
+    read_lock_positions, read_state, remove_lock_position, store_holder, store_lock_position,
+    store_state, Holder, LockPosition, State,
+};
+use crate::user::{calculate_decimal_rewards, update_global_index};
+
+use beth::reward::{
+    ExportedLockPosition, HolderResponse, LockConfigResponse, LockPositionResponse,
+    LockPositionsResponse, WeightResponse,
+};
+use beth::CustomMsg;
+use cosmwasm_bignumber::Decimal256;
+use cosmwasm_std::{
+    attr, to_binary, Addr, CanonicalAddr, CosmosMsg, Deps, DepsMut, Env, MessageInfo, Response,
+    StdError, StdResult, Uint128, WasmMsg,
+};
+use cw20::Cw20ExecuteMsg;
+
+// lock positions of a holder, all of them fit in one page of `read_lock_positions`
+const MAX_LOCK_POSITIONS: u32 = 30;
+
+/// Lock `amount` of bETH sent by `holder_addr` for `duration` seconds. The token contract
+/// already moved the amount out of the holder balance, it moves to a lock position
+/// weighted by the boost of the duration.
+pub fn execute_lock(
+    mut deps: DepsMut,
+    env: Env,
+    holder_addr: Addr,
+    amount: Uint128,
+    duration: u64,
+) -> StdResult<Response<CustomMsg>> {
+    let lock_config = read_lock_config(deps.storage)?;
+    if lock_config.max_lock_duration == 0 {
+        return Err(StdError::generic_err("Locking is disabled"));
+    }
+    if duration == 0 || duration > lock_config.max_lock_duration {
+        return Err(StdError::generic_err(format!(
+            "Lock duration must be between 1 and {} seconds",
+            lock_config.max_lock_duration
+        )));
+    }
+    if amount.is_zero() {
+        return Err(StdError::generic_err("Lock amount must be greater than 0"));
+    }
+
+    // boost = 1 + max_boost * duration / max_lock_duration
+    let boost = decimal_summation_in_256(
+        Decimal256::one(),
+        decimal_multiplication_in_256(
+            lock_config.max_boost.into(),
+            decimal_from_ratio_in_256(
+                Uint128::from(duration),
+                Uint128::from(lock_config.max_lock_duration),
+            )?,
+        )?,
+    )?;
+    let (weight, _) = decimal_split_in_256(decimal_multiplication_in_256(
+        boost,
+        decimal_from_uint128(amount),
+    )?)?;
+
+    let holder_raw = deps.api.addr_canonicalize(holder_addr.as_str())?;
+    if read_lock_positions(deps.storage, &holder_raw, None, Some(MAX_LOCK_POSITIONS))?.len()
+        == MAX_LOCK_POSITIONS as usize
+    {
+        return Err(StdError::generic_err(format!(
+            "Cannot hold more than {} lock positions",
+            MAX_LOCK_POSITIONS
+        )));
+    }
+
+    let mut state = read_state(deps.storage)?;
+    let mut holder = read_holder(deps.storage, &holder_raw)?;
+    settle_holder_rewards(deps.branch(), &env, &mut state, &mut holder)?;
+
+    holder.locked_amount += amount;
+    holder.locked_weight += weight;
+    state.total_locked += amount;
+    state.total_locked_weight += weight;
+
+    let lock_id = next_lock_id(deps.storage)?;
+    let now = env.block.time.seconds();
+    store_lock_position(
+        deps.storage,
+        &holder_raw,
+        lock_id,
+        &LockPosition {
+            amount,
+            weight,
+            start: now,
+            end: now + duration,
+        },
+    )?;
+    store_holder(deps.storage, &mut state, &holder_raw, &holder)?;
+    store_state(deps.storage, &state)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "lock"),
+        attr("holder_address", holder_addr),
+        attr("lock_id", lock_id.to_string()),
+        attr("amount", amount),
+        attr("weight", weight),
+        attr("end", (now + duration).to_string()),
+    ]))
+}
+
+pub fn execute_unlock(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    lock_id: u64,
+) -> StdResult<Response<CustomMsg>> {
+    let config = read_config(deps.storage)?;
+    let lock_config = read_lock_config(deps.storage)?;
+    let holder_raw = deps.api.addr_canonicalize(info.sender.as_str())?;
+    let position = read_lock_position(deps.storage, &holder_raw, lock_id)?
+        .ok_or_else(|| StdError::generic_err(format!("Lock {} does not exist", lock_id)))?;
+
+    let mut state = read_state(deps.storage)?;
+    let mut holder = read_holder(deps.storage, &holder_raw)?;
+    settle_holder_rewards(deps.branch(), &env, &mut state, &mut holder)?;
+
+    holder.locked_amount = holder.locked_amount.checked_sub(position.amount)?;
+    holder.locked_weight = holder.locked_weight.checked_sub(position.weight)?;
+    state.total_locked = state.total_locked.checked_sub(position.amount)?;
+    state.total_locked_weight = state.total_locked_weight.checked_sub(position.weight)?;
+
+    // the penalty goes to the reward weight of the other holders, without any it is waived
+    let mut penalty = Uint128::zero();
+    let other_effective_balance = state
+        .total_effective_balance()
+        .checked_sub(holder.effective_balance())?;
+    if env.block.time.seconds() < position.end && !other_effective_balance.is_zero() {
+        penalty = lock_config.early_unlock_penalty * position.amount;
+        state.lock_penalty_index = decimal_summation_in_256(
+            state.lock_penalty_index,
+            decimal_from_ratio_in_256(penalty, other_effective_balance)?,
+        )?;
+        // the holder was settled before, it gets no share of its own penalty
+        holder.penalty_index = state.lock_penalty_index;
+    }
+
+    remove_lock_position(deps.storage, &holder_raw, lock_id);
+    store_holder(deps.storage, &mut state, &holder_raw, &holder)?;
+    store_state(deps.storage, &state)?;
+
+    let amount = position.amount.checked_sub(penalty)?;
+    let mut messages: Vec<CosmosMsg<CustomMsg>> = vec![];
+    if !amount.is_zero() {
+        messages.push(transfer_beth(
+            deps.as_ref(),
+            config.token_contract,
+            &info.sender,
+            amount,
+        )?);
+    }
+
+    Ok(Response::new().add_messages(messages).add_attributes(vec![
+        attr("action", "unlock"),
+        attr("holder_address", info.sender),
+        attr("lock_id", lock_id.to_string()),
+        attr("amount", amount),
+        attr("penalty", penalty),
+    ]))
+}
+
+pub fn execute_claim_penalties(
+    deps: DepsMut,
+    info: MessageInfo,
+    recipient: Option<Addr>,
+) -> StdResult<Response<CustomMsg>> {
+    let config = read_config(deps.storage)?;
+    let holder_raw = deps.api.addr_canonicalize(info.sender.as_str())?;
+    let recipient = recipient.unwrap_or_else(|| info.sender.clone());
+
+    let mut state = read_state(deps.storage)?;
+    let mut holder = read_holder(deps.storage, &holder_raw)?;
+    settle_holder_penalties(&state, &mut holder)?;
+
+    let (penalties, decimals) = decimal_split_in_256(holder.pending_penalties)?;
+    if penalties.is_zero() {
+        return Err(StdError::generic_err("No penalties have accrued yet"));
+    }
+    holder.pending_penalties = decimals;
+    store_holder(deps.storage, &mut state, &holder_raw, &holder)?;
+    store_state(deps.storage, &state)?;
+
+    let message = transfer_beth(deps.as_ref(), config.token_contract, &recipient, penalties)?;
+    Ok(Response::new().add_message(message).add_attributes(vec![
+        attr("action", "claim_penalties"),
+        attr("holder_address", info.sender),
+        attr("penalties", penalties),
+    ]))
+}
+
+fn transfer_beth(
+    deps: Deps,
+    token_contract: Option<CanonicalAddr>,
+    recipient: &Addr,
+    amount: Uint128,
+) -> StdResult<CosmosMsg<CustomMsg>> {
+    let token_contract = match token_contract {
+        Some(token_contract) => deps.api.addr_humanize(&token_contract)?,
+        None => return Err(StdError::generic_err("Token contract has not been set")),
+    };
+    Ok(WasmMsg::Execute {
+        contract_addr: token_contract.to_string(),
+        msg: to_binary(&Cw20ExecuteMsg::Transfer {
+            recipient: recipient.to_string(),
+            amount,
+        })?,
+        funds: vec![],
+    }
+    .into())
+}
+
+/// Move the rewards of the holder up to the current global index to pending rewards,
+/// before its reward weight changes
+fn settle_holder_rewards(
+    deps: DepsMut,
+    env: &Env,
+    state: &mut State,
+    holder: &mut Holder,
+) -> StdResult<()> {
+    let config = read_config(deps.storage)?;
+    let reward_balance = deps
+        .querier
+        .query_balance(env.contract.address.clone(), config.reward_denom.as_str())?;
+    update_global_index(
+        deps.storage,
+        &env.block,
+        state,
+        reward_balance.amount,
+        config.protocol_fee_rate,
+    )?;
+
+    let rewards =
+        calculate_decimal_rewards(state.global_index, holder.index, holder.effective_balance())?;
+    holder.index = state.global_index;
+    holder.pending_rewards = decimal_summation_in_256(rewards, holder.pending_rewards)?;
+    settle_holder_penalties(state, holder)
+}
+
+/// Move the early unlock penalties owed to the holder up to the current penalty index to
+/// pending penalties, before its reward weight changes
+pub fn settle_holder_penalties(state: &State, holder: &mut Holder) -> StdResult<()> {
+    let penalties = calculate_decimal_rewards(
+        state.lock_penalty_index,
+        holder.penalty_index,
+        holder.effective_balance(),
+    )?;
+    holder.penalty_index = state.lock_penalty_index;
+    holder.pending_penalties = decimal_summation_in_256(penalties, holder.pending_penalties)?;
+    Ok(())
+}
+
+/// Early unlock penalties the holder can claim
+pub fn accrued_penalties(state: &State, holder: &Holder) -> StdResult<Uint128> {
+    let mut holder = holder.clone();
+    settle_holder_penalties(state, &mut holder)?;
+    let (penalties, _) = decimal_split_in_256(holder.pending_penalties)?;
+    Ok(penalties)
+}
+
+pub fn query_lock_config(deps: Deps) -> StdResult<LockConfigResponse> {
+    let lock_config = read_lock_config(deps.storage)?;
+    Ok(LockConfigResponse {
+        max_lock_duration: lock_config.max_lock_duration,
+        max_boost: lock_config.max_boost,
+        early_unlock_penalty: lock_config.early_unlock_penalty,
+    })
+}
+
+pub fn query_lock_positions(
+    deps: Deps,
+    address: Addr,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<LockPositionsResponse> {
+    let holder_raw = deps.api.addr_canonicalize(address.as_str())?;
+    let positions = read_lock_positions(deps.storage, &holder_raw, start_after, limit)?
+        .into_iter()
+        .map(|(lock_id, position)| LockPositionResponse {
+            lock_id,
+            amount: position.amount,
+            weight: position.weight,
+            start: position.start,
+            end: position.end,
+        })
+        .collect();
+
+    Ok(LockPositionsResponse { positions })
+}
+
+/// All the lock positions of `holders`
+pub fn export_lock_positions(
+    deps: Deps,
+    holders: &[HolderResponse],
+) -> StdResult<Vec<ExportedLockPosition>> {
+    let mut positions = vec![];
+    for holder in holders
+        .iter()
+        .filter(|holder| !holder.locked_amount.is_zero())
+    {
+        let holder_raw = deps.api.addr_canonicalize(&holder.address)?;
+        for (lock_id, position) in
+            read_lock_positions(deps.storage, &holder_raw, None, Some(MAX_LOCK_POSITIONS))?
+        {
+            positions.push(ExportedLockPosition {
+                address: holder.address.clone(),
+                lock_id,
+                amount: position.amount,
+                weight: position.weight,
+                start: position.start,
+                end: position.end,
+            });
+        }
+    }
+    Ok(positions)
+}
+
+pub fn query_weight(deps: Deps, address: Addr) -> StdResult<WeightResponse> {
+    let holder = read_holder(deps.storage, &deps.api.addr_canonicalize(address.as_str())?)?;
+    Ok(WeightResponse {
+        address: address.to_string(),
+        balance: holder.balance,
+        locked_amount: holder.locked_amount,
+        effective_balance: holder.effective_balance(),
+    })
+}
@@ -1,82 +1,173 @@
-use cosmwasm_bignumber::Decimal256;
-use cosmwasm_std::Decimal;
+use cosmwasm_bignumber::{Decimal256, Uint256};
+use cosmwasm_std::{OverflowError, OverflowOperation, StdError, StdResult, Uint128};
 
 /// return a * b
-pub fn decimal_multiplication_in_256(a: Decimal, b: Decimal) -> Decimal {
-    let a_u256: Decimal256 = a.into();
-    let b_u256: Decimal256 = b.into();
-    let c_u256: Decimal = (b_u256 * a_u256).into();
-    c_u256
+pub fn decimal_multiplication_in_256(a: Decimal256, b: Decimal256) -> StdResult<Decimal256> {
+    let (product, overflow) = a.0.overflowing_mul(b.0);
+    if overflow {
+        return Err(OverflowError::new(OverflowOperation::Mul, a, b).into());
+    }
+    Ok(Decimal256(product / Decimal256::DECIMAL_FRACTIONAL))
 }
 
 /// return a + b
-pub fn decimal_summation_in_256(a: Decimal, b: Decimal) -> Decimal {
-    let a_u256: Decimal256 = a.into();
-    let b_u256: Decimal256 = b.into();
-    let c_u256: Decimal = (b_u256 + a_u256).into();
-    c_u256
+pub fn decimal_summation_in_256(a: Decimal256, b: Decimal256) -> StdResult<Decimal256> {
+    let (sum, overflow) = a.0.overflowing_add(b.0);
+    if overflow {
+        return Err(OverflowError::new(OverflowOperation::Add, a, b).into());
+    }
+    Ok(Decimal256(sum))
 }
 
 /// return a - b
-pub fn decimal_subtraction_in_256(a: Decimal, b: Decimal) -> Decimal {
-    let a_u256: Decimal256 = a.into();
-    let b_u256: Decimal256 = b.into();
-    let c_u256: Decimal = (a_u256 - b_u256).into();
-    c_u256
+pub fn decimal_subtraction_in_256(a: Decimal256, b: Decimal256) -> StdResult<Decimal256> {
+    let (difference, overflow) = a.0.overflowing_sub(b.0);
+    if overflow {
+        return Err(OverflowError::new(OverflowOperation::Sub, a, b).into());
+    }
+    Ok(Decimal256(difference))
+}
+
+/// return a / b
+pub fn decimal_division_in_256(a: Decimal256, b: Decimal256) -> StdResult<Decimal256> {
+    if b.is_zero() {
+        return Err(StdError::generic_err(format!(
+            "Cannot divide {} by zero",
+            a
+        )));
+    }
+    let (numerator, overflow) = a.0.overflowing_mul(Decimal256::DECIMAL_FRACTIONAL);
+    if overflow {
+        return Err(OverflowError::new(OverflowOperation::Mul, a, Decimal256::one()).into());
+    }
+    Ok(Decimal256(numerator / b.0))
+}
+
+/// return a / b
+pub fn decimal_from_ratio_in_256(a: Uint128, b: Uint128) -> StdResult<Decimal256> {
+    if b.is_zero() {
+        return Err(StdError::generic_err(format!(
+            "Cannot divide {} by zero",
+            a
+        )));
+    }
+    Ok(Decimal256::from_ratio(
+        Uint256::from(a).0,
+        Uint256::from(b).0,
+    ))
+}
+
+/// return a as a Decimal256
+pub fn decimal_from_uint128(a: Uint128) -> Decimal256 {
+    Decimal256::from_uint256(a)
+}
+
+/// return the integer part of a as an amount, and the fractional part of a
+pub fn decimal_split_in_256(a: Decimal256) -> StdResult<(Uint128, Decimal256)> {
+    let integer = a.0 / Decimal256::DECIMAL_FRACTIONAL;
+    let fraction = a.0 % Decimal256::DECIMAL_FRACTIONAL;
+    if integer.0[2] != 0 || integer.0[3] != 0 {
+        return Err(StdError::generic_err(format!(
+            "Cannot convert {} to Uint128",
+            a
+        )));
+    }
+    Ok((Uint256(integer).into(), Decimal256(fraction)))
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use cosmwasm_std::{Decimal, Uint128};
+    use std::str::FromStr;
 
     #[test]
-    fn test_decimal_multiplication() {
-        let a = Uint128::new(100);
-        let b = Decimal::from_ratio(Uint128::new(1111111), Uint128::new(10000000));
-        let multiplication =
-            decimal_multiplication_in_256(Decimal::from_ratio(a, Uint128::new(1)), b);
+    fn test_decimal_multiplication_in_256() {
+        let a = decimal_from_uint128(Uint128::new(100));
+        let b = decimal_from_ratio_in_256(Uint128::new(1111111), Uint128::new(10000000)).unwrap();
+        let multiplication = decimal_multiplication_in_256(a, b).unwrap();
         assert_eq!(multiplication.to_string(), "11.11111");
     }
 
     #[test]
-    fn test_decimal_sumation() {
-        let a = Decimal::from_ratio(Uint128::new(20), Uint128::new(50));
-        let b = Decimal::from_ratio(Uint128::new(10), Uint128::new(50));
-        let res = decimal_summation_in_256(a, b);
+    fn test_decimal_sumation_in_256() {
+        let a = decimal_from_ratio_in_256(Uint128::new(20), Uint128::new(50)).unwrap();
+        let b = decimal_from_ratio_in_256(Uint128::new(10), Uint128::new(50)).unwrap();
+        let res = decimal_summation_in_256(a, b).unwrap();
         assert_eq!(res.to_string(), "0.6");
     }
 
     #[test]
-    fn test_decimal_subtraction() {
-        let a = Decimal::from_ratio(Uint128::new(20), Uint128::new(50));
-        let b = Decimal::from_ratio(Uint128::new(10), Uint128::new(50));
-        let res = decimal_subtraction_in_256(a, b);
+    fn test_decimal_subtraction_in_256() {
+        let a = decimal_from_ratio_in_256(Uint128::new(20), Uint128::new(50)).unwrap();
+        let b = decimal_from_ratio_in_256(Uint128::new(10), Uint128::new(50)).unwrap();
+        let res = decimal_subtraction_in_256(a, b).unwrap();
         assert_eq!(res.to_string(), "0.2");
+
+        let err = decimal_subtraction_in_256(b, a).unwrap_err();
+        assert_eq!(
+            err,
+            StdError::from(OverflowError::new(OverflowOperation::Sub, b, a))
+        );
     }
 
     #[test]
-    fn test_decimal_multiplication_in_256() {
-        let a = Uint128::new(100);
-        let b = Decimal::from_ratio(Uint128::new(1111111), Uint128::new(10000000));
-        let multiplication =
-            decimal_multiplication_in_256(Decimal::from_ratio(a, Uint128::new(1)), b);
-        assert_eq!(multiplication.to_string(), "11.11111");
+    fn test_decimal_from_ratio_in_256() {
+        let err = decimal_from_ratio_in_256(Uint128::new(1), Uint128::zero()).unwrap_err();
+        assert_eq!(err, StdError::generic_err("Cannot divide 1 by zero"));
     }
 
     #[test]
-    fn test_decimal_sumation_in_256() {
-        let a = Decimal::from_ratio(Uint128::new(20), Uint128::new(50));
-        let b = Decimal::from_ratio(Uint128::new(10), Uint128::new(50));
-        let res = decimal_summation_in_256(a, b);
-        assert_eq!(res.to_string(), "0.6");
+    fn test_decimal_division_in_256() {
+        let a = decimal_from_uint128(Uint128::new(3));
+        let b = decimal_from_ratio_in_256(Uint128::new(1), Uint128::new(4)).unwrap();
+        let res = decimal_division_in_256(a, b).unwrap();
+        assert_eq!(res.to_string(), "12");
+
+        let err = decimal_division_in_256(a, Decimal256::zero()).unwrap_err();
+        assert_eq!(err, StdError::generic_err("Cannot divide 3 by zero"));
     }
 
     #[test]
-    fn test_decimal_subtraction_in_256() {
-        let a = Decimal::from_ratio(Uint128::new(20), Uint128::new(50));
-        let b = Decimal::from_ratio(Uint128::new(10), Uint128::new(50));
-        let res = decimal_subtraction_in_256(a, b);
-        assert_eq!(res.to_string(), "0.2");
+    fn test_large_values_in_256() {
+        // beyond the range of the 128-bit Decimal
+        let a = decimal_from_uint128(Uint128::new(u128::MAX));
+        let b = Decimal256::from_str("2.5").unwrap();
+        let res = decimal_multiplication_in_256(a, b).unwrap();
+        assert_eq!(res.to_string(), "850705917302346158658436518579420528637.5");
+        // the integer part of the product does not fit an amount
+        assert!(decimal_split_in_256(res).is_err());
+
+        let err = decimal_multiplication_in_256(Decimal256::MAX, b).unwrap_err();
+        assert_eq!(
+            err,
+            StdError::from(OverflowError::new(
+                OverflowOperation::Mul,
+                Decimal256::MAX,
+                b
+            ))
+        );
+        let err = decimal_summation_in_256(Decimal256::MAX, b).unwrap_err();
+        assert_eq!(
+            err,
+            StdError::from(OverflowError::new(
+                OverflowOperation::Add,
+                Decimal256::MAX,
+                b
+            ))
+        );
+    }
+
+    #[test]
+    fn test_decimal_split_in_256() {
+        let a = decimal_from_ratio_in_256(Uint128::new(9999999), Uint128::new(10000000)).unwrap();
+        let res = decimal_multiplication_in_256(a, decimal_from_uint128(Uint128::new(10))).unwrap();
+        let (integer, fraction) = decimal_split_in_256(res).unwrap();
+        assert_eq!(integer, Uint128::new(9));
+        assert_eq!(fraction.to_string(), "0.999999");
+
+        let (integer, fraction) =
+            decimal_split_in_256(decimal_from_uint128(Uint128::new(5))).unwrap();
+        assert_eq!(integer, Uint128::new(5));
+        assert!(fraction.is_zero());
     }
 }
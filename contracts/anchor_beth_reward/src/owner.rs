@@ -1,13 +1,23 @@
-use crate::state::{read_config, store_config};
+use crate::history::record_global_index;
+use crate::math::{decimal_from_uint128, decimal_subtraction_in_256};
+use crate::state::{
+    is_holder, read_config, read_holder_entries, read_lock_config, read_state, reserve_lock_id,
+    store_claimant, store_config, store_holder, store_lock_config, store_lock_position,
+    store_state, Claimant, Holder, LockPosition,
+};
+use crate::user::update_global_index;
 
-use cosmwasm_std::{attr, Addr, DepsMut, MessageInfo, Response, StdError, StdResult};
-use terra_cosmwasm::TerraMsgWrapper;
+use beth::reward::StateResponse;
+use beth::CustomMsg;
+use cosmwasm_std::{
+    attr, Addr, Decimal, DepsMut, Env, MessageInfo, Order, Response, StdError, StdResult, Uint128,
+};
 
 pub fn execute_post_initialize(
     deps: DepsMut,
     info: MessageInfo,
     token_contract: Addr,
-) -> StdResult<Response<TerraMsgWrapper>> {
+) -> StdResult<Response<CustomMsg>> {
     let mut config = read_config(deps.storage)?;
     let owner_addr = deps.api.addr_humanize(&config.owner)?;
 
@@ -24,9 +34,12 @@ pub fn execute_post_initialize(
 
 pub fn execute_update_config(
     deps: DepsMut,
+    env: Env,
     info: MessageInfo,
-    owner: Addr,
-) -> StdResult<Response<TerraMsgWrapper>> {
+    owner: Option<Addr>,
+    protocol_fee_rate: Option<Decimal>,
+    treasury: Option<Addr>,
+) -> StdResult<Response<CustomMsg>> {
     let mut config = read_config(deps.storage)?;
     let owner_addr = deps.api.addr_humanize(&config.owner)?;
 
@@ -34,9 +47,246 @@ pub fn execute_update_config(
         return Err(StdError::generic_err("unauthorized"));
     }
 
-    config.owner = deps.api.addr_canonicalize(owner.as_str())?;
+    if let Some(owner) = owner {
+        config.owner = deps.api.addr_canonicalize(owner.as_str())?;
+    }
+
+    if let Some(treasury) = treasury {
+        config.treasury = Some(deps.api.addr_canonicalize(treasury.as_str())?);
+    }
+
+    if let Some(protocol_fee_rate) = protocol_fee_rate {
+        if protocol_fee_rate > Decimal::one() {
+            return Err(StdError::generic_err(
+                "Protocol fee rate cannot be greater than 1",
+            ));
+        }
+
+        // the rewards received so far are distributed at the previous rate
+        let mut state = read_state(deps.storage)?;
+        let reward_balance = deps
+            .querier
+            .query_balance(env.contract.address, config.reward_denom.as_str())?;
+        update_global_index(
+            deps.storage,
+            &env.block,
+            &mut state,
+            reward_balance.amount,
+            config.protocol_fee_rate,
+        )?;
+        store_state(deps.storage, &state)?;
+
+        config.protocol_fee_rate = protocol_fee_rate;
+    }
 
     store_config(deps.storage, &config)?;
 
     Ok(Response::new().add_attributes(vec![attr("action", "update_config")]))
 }
+
+/// A holder of another deployment with its claim stats and its lock positions by lock id
+pub struct ImportedHolder {
+    pub address: Addr,
+    pub holder: Holder,
+    pub claimant: Option<Claimant>,
+    pub lock_positions: Vec<(u64, LockPosition)>,
+}
+
+pub fn execute_import_holders(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    exported: StateResponse,
+    holders: Vec<ImportedHolder>,
+) -> StdResult<Response<CustomMsg>> {
+    let config = read_config(deps.storage)?;
+    let owner_addr = deps.api.addr_humanize(&config.owner)?;
+
+    if info.sender != owner_addr {
+        return Err(StdError::generic_err("unauthorized"));
+    }
+
+    // balances can only change through the token contract, so none of them moves during the import
+    if config.token_contract.is_some() {
+        return Err(StdError::generic_err(
+            "Holders can only be imported before the token contract is set",
+        ));
+    }
+
+    // every page of one export carries the same state
+    let mut state = read_state(deps.storage)?;
+    state.global_index = exported.global_index;
+    state.prev_reward_balance = exported.prev_reward_balance;
+    state.lock_penalty_index = exported.lock_penalty_index;
+    state.protocol_fee = exported.protocol_fee;
+    // the imported holders are owed the reward balance of the export less the protocol fee,
+    // and less the dust of the holders pruned by the previous pages
+    state.total_liabilities = decimal_subtraction_in_256(
+        decimal_from_uint128(
+            exported
+                .prev_reward_balance
+                .checked_sub(exported.protocol_fee)?,
+        ),
+        state.reward_dust,
+    )?;
+
+    for imported in holders.iter() {
+        let address = &imported.address;
+        let holder = &imported.holder;
+        let address_raw = deps.api.addr_canonicalize(address.as_str())?;
+        if is_holder(deps.storage, &address_raw)? {
+            return Err(StdError::generic_err(format!(
+                "Holder {} is already imported",
+                address
+            )));
+        }
+
+        let mut locked_amount = Uint128::zero();
+        let mut locked_weight = Uint128::zero();
+        for (lock_id, position) in imported.lock_positions.iter() {
+            locked_amount = locked_amount.checked_add(position.amount)?;
+            locked_weight = locked_weight.checked_add(position.weight)?;
+            store_lock_position(deps.storage, &address_raw, *lock_id, position)?;
+            reserve_lock_id(deps.storage, *lock_id)?;
+        }
+        if locked_amount != holder.locked_amount || locked_weight != holder.locked_weight {
+            return Err(StdError::generic_err(format!(
+                "Lock positions of {} do not match its locked amount",
+                address
+            )));
+        }
+
+        state.total_balance = state.total_balance.checked_add(holder.balance)?;
+        state.total_locked = state.total_locked.checked_add(holder.locked_amount)?;
+        state.total_locked_weight = state
+            .total_locked_weight
+            .checked_add(holder.locked_weight)?;
+        store_holder(deps.storage, &mut state, &address_raw, holder)?;
+        if let Some(claimant) = &imported.claimant {
+            store_claimant(deps.storage, &address_raw, claimant)?;
+        }
+    }
+
+    store_state(deps.storage, &state)?;
+    // the imported index carries the rewards of the other deployment
+    record_global_index(
+        deps.storage,
+        &env.block,
+        &state,
+        Uint128::zero(),
+        Uint128::zero(),
+    )?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "import_holders"),
+        attr("holders", holders.len().to_string()),
+    ]))
+}
+
+pub fn execute_prune_holders(
+    deps: DepsMut,
+    info: MessageInfo,
+    start_after: Option<Addr>,
+    limit: Option<u32>,
+) -> StdResult<Response<CustomMsg>> {
+    let config = read_config(deps.storage)?;
+    let owner_addr = deps.api.addr_humanize(&config.owner)?;
+
+    if info.sender != owner_addr {
+        return Err(StdError::generic_err("unauthorized"));
+    }
+
+    let start_after = match start_after {
+        Some(start_after) => Some(deps.api.addr_canonicalize(start_after.as_str())?),
+        None => None,
+    };
+    let holders = read_holder_entries(
+        deps.storage,
+        start_after.clone(),
+        None,
+        limit,
+        Order::Ascending,
+    )?;
+
+    // storing the holders again removes the prunable ones and counts the others
+    let mut state = read_state(deps.storage)?;
+    let mut pruned = 0u64;
+    for (address, holder) in holders.iter() {
+        if holder.is_prunable() {
+            pruned += 1;
+        }
+        store_holder(deps.storage, &mut state, address, holder)?;
+    }
+
+    // the pages continuing the pass from the first holder complete the count
+    if !state.holders_indexed && start_after == state.holder_index_cursor {
+        let last = holders.last().map(|(address, _)| address.clone());
+        let more = match &last {
+            Some(last) => !read_holder_entries(
+                deps.storage,
+                Some(last.clone()),
+                None,
+                Some(1),
+                Order::Ascending,
+            )?
+            .is_empty(),
+            None => false,
+        };
+        if more {
+            state.holder_index_cursor = last;
+        } else {
+            state.holders_indexed = true;
+            state.holder_index_cursor = None;
+        }
+    }
+    store_state(deps.storage, &state)?;
+
+    let mut attrs = vec![
+        attr("action", "prune_holders"),
+        attr("pruned", pruned.to_string()),
+        attr("holder_count", state.holder_count.to_string()),
+        attr("holders_indexed", state.holders_indexed.to_string()),
+    ];
+    // the next page starts after the last holder of this one
+    if let Some((address, _)) = holders.last() {
+        attrs.push(attr("last_holder", deps.api.addr_humanize(address)?));
+    }
+
+    Ok(Response::new().add_attributes(attrs))
+}
+
+pub fn execute_update_lock_config(
+    deps: DepsMut,
+    info: MessageInfo,
+    max_lock_duration: Option<u64>,
+    max_boost: Option<Decimal>,
+    early_unlock_penalty: Option<Decimal>,
+) -> StdResult<Response<CustomMsg>> {
+    let config = read_config(deps.storage)?;
+    let owner_addr = deps.api.addr_humanize(&config.owner)?;
+
+    if info.sender != owner_addr {
+        return Err(StdError::generic_err("unauthorized"));
+    }
+
+    // the weight of the existing positions is fixed when they are locked
+    let mut lock_config = read_lock_config(deps.storage)?;
+    if let Some(max_lock_duration) = max_lock_duration {
+        lock_config.max_lock_duration = max_lock_duration;
+    }
+    if let Some(max_boost) = max_boost {
+        lock_config.max_boost = max_boost;
+    }
+    if let Some(early_unlock_penalty) = early_unlock_penalty {
+        if early_unlock_penalty > Decimal::one() {
+            return Err(StdError::generic_err(
+                "Early unlock penalty cannot be greater than 1",
+            ));
+        }
+        lock_config.early_unlock_penalty = early_unlock_penalty;
+    }
+
+    store_lock_config(deps.storage, &lock_config)?;
+
+    Ok(Response::new().add_attributes(vec![attr("action", "update_lock_config")]))
+}
@@ -1,14 +1,24 @@
-use cosmwasm_std::{CanonicalAddr, Decimal, Deps, Order, StdResult, Storage, Uint128};
-use cosmwasm_storage::{bucket, bucket_read, singleton, singleton_read, ReadonlyBucket};
+use crate::math::{decimal_subtraction_in_256, decimal_summation_in_256};
+
+use cosmwasm_bignumber::Decimal256;
+use cosmwasm_std::{CanonicalAddr, Decimal, Order, StdResult, Storage, Uint128};
+use cosmwasm_storage::{bucket, bucket_read, singleton, singleton_read, Bucket, ReadonlyBucket};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-use beth::reward::HolderResponse;
-
 pub static KEY_CONFIG: &[u8] = b"config";
 pub static KEY_STATE: &[u8] = b"state";
+pub static KEY_LOCK_CONFIG: &[u8] = b"lock_config";
+pub static KEY_LAST_LOCK_ID: &[u8] = b"last_lock_id";
+pub static KEY_LAST_CHECKPOINT_ID: &[u8] = b"last_checkpoint_id";
+pub static KEY_STATS: &[u8] = b"stats";
 
 pub static PREFIX_HOLDERS: &[u8] = b"holders";
+pub static PREFIX_HOLDER_BALANCES: &[u8] = b"holder_balances";
+pub static PREFIX_LOCKS: &[u8] = b"locks";
+pub static PREFIX_REWARD_HISTORY: &[u8] = b"reward_history";
+pub static PREFIX_INDEX_HEIGHTS: &[u8] = b"index_heights";
+pub static PREFIX_CLAIMANTS: &[u8] = b"claimants";
 pub static KEY_CONTRACT_ADDR: &[u8] = b"contract_addr";
 
 pub fn read_contract_addr(storage: &dyn Storage) -> StdResult<CanonicalAddr> {
@@ -27,6 +37,8 @@ pub struct Config {
     pub owner: CanonicalAddr,
     pub token_contract: Option<CanonicalAddr>,
     pub reward_denom: String,
+    pub protocol_fee_rate: Decimal,
+    pub treasury: Option<CanonicalAddr>,
 }
 
 pub fn store_config(storage: &mut dyn Storage, config: &Config) -> StdResult<()> {
@@ -37,11 +49,64 @@ pub fn read_config(storage: &dyn Storage) -> StdResult<Config> {
     singleton_read(storage, KEY_CONFIG).load()
 }
 
+/// Config of the releases before 1.3.0, without the protocol fee up to the
+/// deployments that predate cw2
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct LegacyConfig {
+    pub owner: CanonicalAddr,
+    pub token_contract: Option<CanonicalAddr>,
+    pub reward_denom: String,
+    #[serde(default)]
+    pub protocol_fee_rate: Decimal,
+    #[serde(default)]
+    pub treasury: Option<CanonicalAddr>,
+}
+
+pub fn read_legacy_config(storage: &dyn Storage) -> StdResult<LegacyConfig> {
+    singleton_read(storage, KEY_CONFIG).load()
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct State {
-    pub global_index: Decimal,
+    pub global_index: Decimal256,
     pub total_balance: Uint128,
     pub prev_reward_balance: Uint128,
+    /// bETH held by the contract in lock positions, not part of `total_balance`
+    #[serde(default)]
+    pub total_locked: Uint128,
+    /// reward weight of the lock positions
+    #[serde(default)]
+    pub total_locked_weight: Uint128,
+    /// early unlock penalties in bETH per unit of reward weight
+    #[serde(default)]
+    pub lock_penalty_index: Decimal256,
+    /// protocol fee not withdrawn by the treasury yet
+    #[serde(default)]
+    pub protocol_fee: Uint128,
+    /// stored holders, without the ones stored before the count that `PruneHolders`
+    /// has not gone over yet
+    #[serde(default)]
+    pub holder_count: u64,
+    /// `holder_count` and the balance index cover every holder, false for the deployments
+    /// that predate them until a `PruneHolders` pass goes over all their holders
+    #[serde(default)]
+    pub holders_indexed: bool,
+    /// last holder reached by the `PruneHolders` pass completing `holders_indexed`
+    #[serde(default)]
+    pub holder_index_cursor: Option<CanonicalAddr>,
+    /// rewards owed to the holders, pending fractions included
+    #[serde(default)]
+    pub total_liabilities: Decimal256,
+    /// rewards lost to rounding, held by the contract and owed to no one
+    #[serde(default)]
+    pub reward_dust: Decimal256,
+}
+
+impl State {
+    /// reward weight of all the holders
+    pub fn total_effective_balance(&self) -> Uint128 {
+        self.total_balance + self.total_locked_weight
+    }
 }
 
 pub fn store_state(storage: &mut dyn Storage, state: &State) -> StdResult<()> {
@@ -55,17 +120,86 @@ pub fn read_state(storage: &dyn Storage) -> StdResult<State> {
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct Holder {
     pub balance: Uint128,
-    pub index: Decimal,
-    pub pending_rewards: Decimal,
+    pub index: Decimal256,
+    pub pending_rewards: Decimal256,
+    #[serde(default)]
+    pub locked_amount: Uint128,
+    #[serde(default)]
+    pub locked_weight: Uint128,
+    /// part of `State.holder_count`, false for the holders stored before the count
+    #[serde(default)]
+    pub counted: bool,
+    /// `State.lock_penalty_index` when the penalties of the holder were last settled
+    #[serde(default)]
+    pub penalty_index: Decimal256,
+    /// early unlock penalties owed to the holder in bETH
+    #[serde(default)]
+    pub pending_penalties: Decimal256,
+}
+
+impl Holder {
+    /// reward weight of the holder
+    pub fn effective_balance(&self) -> Uint128 {
+        self.balance + self.locked_weight
+    }
+
+    /// Nothing left to earn or to claim, the pending rewards and penalties are below one unit
+    pub fn is_prunable(&self) -> bool {
+        self.balance.is_zero()
+            && self.locked_amount.is_zero()
+            && self.pending_rewards < Decimal256::one()
+            && self.pending_penalties < Decimal256::one()
+    }
 }
 
 // This is similar to HashMap<holder's address, Hodler>
+/// Store the holder, or remove it once it is prunable, and update the balance index and
+/// the holder count of `state`. The caller stores the state.
 pub fn store_holder(
     storage: &mut dyn Storage,
+    state: &mut State,
     holder_address: &CanonicalAddr,
     holder: &Holder,
 ) -> StdResult<()> {
-    bucket(storage, PREFIX_HOLDERS).save(holder_address.as_slice(), holder)
+    let prev: Option<Holder> =
+        bucket_read(storage, PREFIX_HOLDERS).may_load(holder_address.as_slice())?;
+    let mut balance_bucket: Bucket<bool> = bucket(storage, PREFIX_HOLDER_BALANCES);
+    if let Some(prev) = prev {
+        balance_bucket.remove(&balance_index_key(prev.balance, holder_address));
+    }
+    if !holder.balance.is_zero() {
+        balance_bucket.save(&balance_index_key(holder.balance, holder_address), &true)?;
+    }
+
+    let mut holder_bucket: Bucket<Holder> = bucket(storage, PREFIX_HOLDERS);
+    if holder.is_prunable() {
+        if holder.counted {
+            state.holder_count -= 1;
+        }
+        // the fraction of a unit left to the holder can no longer be claimed
+        state.total_liabilities =
+            decimal_subtraction_in_256(state.total_liabilities, holder.pending_rewards)?;
+        state.reward_dust = decimal_summation_in_256(state.reward_dust, holder.pending_rewards)?;
+        holder_bucket.remove(holder_address.as_slice());
+        return Ok(());
+    }
+
+    if holder.counted {
+        return holder_bucket.save(holder_address.as_slice(), holder);
+    }
+    state.holder_count += 1;
+    holder_bucket.save(
+        holder_address.as_slice(),
+        &Holder {
+            counted: true,
+            ..holder.clone()
+        },
+    )
+}
+
+// balances sort as big endian bytes, the address keeps the keys of equal balances apart
+fn balance_index_key(balance: Uint128, holder_address: &CanonicalAddr) -> Vec<u8> {
+    [&balance.u128().to_be_bytes(), holder_address.as_slice()].concat()
 }
 
 pub fn read_holder(storage: &dyn Storage, holder_address: &CanonicalAddr) -> StdResult<Holder> {
@@ -75,37 +209,230 @@ pub fn read_holder(storage: &dyn Storage, holder_address: &CanonicalAddr) -> Std
         Some(holder) => Ok(holder),
         None => Ok(Holder {
             balance: Uint128::zero(),
-            index: Decimal::zero(),
-            pending_rewards: Decimal::zero(),
+            index: Decimal256::zero(),
+            pending_rewards: Decimal256::zero(),
+            locked_amount: Uint128::zero(),
+            locked_weight: Uint128::zero(),
+            counted: false,
+            penalty_index: Decimal256::zero(),
+            pending_penalties: Decimal256::zero(),
         }),
     }
 }
 
+pub fn is_holder(storage: &dyn Storage, holder_address: &CanonicalAddr) -> StdResult<bool> {
+    let res: Option<Holder> =
+        bucket_read(storage, PREFIX_HOLDERS).may_load(holder_address.as_slice())?;
+    Ok(res.is_some())
+}
+
+/// Lifetime counters, from the version that introduced them
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema, Default)]
+pub struct Stats {
+    /// rewards recognized by the global index, protocol fee included
+    pub total_rewards: Uint128,
+    /// rewards claimed by the holders before tax
+    pub total_claimed: Uint128,
+    pub total_claimed_net: Uint128,
+    /// tax paid on the claims
+    pub total_tax: Uint128,
+    pub claims: u64,
+    pub claimants: u64,
+}
+
+pub fn store_stats(storage: &mut dyn Storage, stats: &Stats) -> StdResult<()> {
+    singleton(storage, KEY_STATS).save(stats)
+}
+
+pub fn read_stats(storage: &dyn Storage) -> StdResult<Stats> {
+    let res: Option<Stats> = singleton_read(storage, KEY_STATS).may_load()?;
+    Ok(res.unwrap_or_default())
+}
+
+/// Claim stats of a holder, kept apart from the holder so that they outlive its pruning
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Claimant {
+    /// rewards claimed before tax
+    pub total_claimed: Uint128,
+    pub last_claim_time: u64,
+}
+
+pub fn store_claimant(
+    storage: &mut dyn Storage,
+    address: &CanonicalAddr,
+    claimant: &Claimant,
+) -> StdResult<()> {
+    bucket(storage, PREFIX_CLAIMANTS).save(address.as_slice(), claimant)
+}
+
+/// Claim stats of `address`, `None` until it claims
+pub fn read_claimant(
+    storage: &dyn Storage,
+    address: &CanonicalAddr,
+) -> StdResult<Option<Claimant>> {
+    bucket_read(storage, PREFIX_CLAIMANTS).may_load(address.as_slice())
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct LockConfig {
+    pub max_lock_duration: u64,
+    pub max_boost: Decimal,
+    pub early_unlock_penalty: Decimal,
+}
+
+pub fn store_lock_config(storage: &mut dyn Storage, lock_config: &LockConfig) -> StdResult<()> {
+    singleton(storage, KEY_LOCK_CONFIG).save(lock_config)
+}
+
+/// Locking is disabled until the owner sets the lock config
+pub fn read_lock_config(storage: &dyn Storage) -> StdResult<LockConfig> {
+    let res: Option<LockConfig> = singleton_read(storage, KEY_LOCK_CONFIG).may_load()?;
+    Ok(res.unwrap_or(LockConfig {
+        max_lock_duration: 0,
+        max_boost: Decimal::zero(),
+        early_unlock_penalty: Decimal::zero(),
+    }))
+}
+
+pub fn next_lock_id(storage: &mut dyn Storage) -> StdResult<u64> {
+    let last: Option<u64> = singleton_read(storage, KEY_LAST_LOCK_ID).may_load()?;
+    let lock_id = last.unwrap_or_default() + 1;
+    singleton(storage, KEY_LAST_LOCK_ID).save(&lock_id)?;
+    Ok(lock_id)
+}
+
+/// Keep `next_lock_id` from returning `lock_id` again, for the imported positions
+pub fn reserve_lock_id(storage: &mut dyn Storage, lock_id: u64) -> StdResult<()> {
+    let last: Option<u64> = singleton_read(storage, KEY_LAST_LOCK_ID).may_load()?;
+    if last.unwrap_or_default() < lock_id {
+        singleton(storage, KEY_LAST_LOCK_ID).save(&lock_id)?;
+    }
+    Ok(())
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct LockPosition {
+    pub amount: Uint128,
+    pub weight: Uint128,
+    pub start: u64,
+    pub end: u64,
+}
+
+pub fn store_lock_position(
+    storage: &mut dyn Storage,
+    holder_address: &CanonicalAddr,
+    lock_id: u64,
+    position: &LockPosition,
+) -> StdResult<()> {
+    Bucket::multilevel(storage, &[PREFIX_LOCKS, holder_address.as_slice()])
+        .save(&lock_id.to_be_bytes(), position)
+}
+
+pub fn read_lock_position(
+    storage: &dyn Storage,
+    holder_address: &CanonicalAddr,
+    lock_id: u64,
+) -> StdResult<Option<LockPosition>> {
+    ReadonlyBucket::multilevel(storage, &[PREFIX_LOCKS, holder_address.as_slice()])
+        .may_load(&lock_id.to_be_bytes())
+}
+
+pub fn remove_lock_position(
+    storage: &mut dyn Storage,
+    holder_address: &CanonicalAddr,
+    lock_id: u64,
+) {
+    Bucket::<LockPosition>::multilevel(storage, &[PREFIX_LOCKS, holder_address.as_slice()])
+        .remove(&lock_id.to_be_bytes())
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct RewardCheckpoint {
+    pub height: u64,
+    pub time: u64,
+    /// rewards recognized by the update, protocol fee included
+    pub amount: Uint128,
+    pub protocol_fee: Uint128,
+    /// reward weight the rewards were split over
+    pub total_balance: Uint128,
+    /// global index after the update
+    pub global_index: Decimal256,
+}
+
+/// Record `checkpoint` under the next id, the last checkpoint of a block gives the global
+/// index at its height
+pub fn store_reward_checkpoint(
+    storage: &mut dyn Storage,
+    checkpoint: &RewardCheckpoint,
+) -> StdResult<u64> {
+    let last: Option<u64> = singleton_read(storage, KEY_LAST_CHECKPOINT_ID).may_load()?;
+    let checkpoint_id = last.unwrap_or_default() + 1;
+    singleton(storage, KEY_LAST_CHECKPOINT_ID).save(&checkpoint_id)?;
+
+    bucket(storage, PREFIX_REWARD_HISTORY).save(&checkpoint_id.to_be_bytes(), checkpoint)?;
+    bucket(storage, PREFIX_INDEX_HEIGHTS)
+        .save(&checkpoint.height.to_be_bytes(), &checkpoint.global_index)?;
+    Ok(checkpoint_id)
+}
+
+/// Global index at the end of the block at `height`, `None` before the first checkpoint
+pub fn read_global_index_at(storage: &dyn Storage, height: u64) -> StdResult<Option<Decimal256>> {
+    let index_bucket: ReadonlyBucket<Decimal256> = bucket_read(storage, PREFIX_INDEX_HEIGHTS);
+    let end = height
+        .checked_add(1)
+        .map(|height| height.to_be_bytes().to_vec());
+
+    let last = index_bucket
+        .range(None, end.as_deref(), Order::Descending)
+        .next();
+    match last {
+        Some(elem) => Ok(Some(elem?.1)),
+        None => Ok(None),
+    }
+}
+
 // settings for pagination
 const MAX_LIMIT: u32 = 30;
 const DEFAULT_LIMIT: u32 = 10;
-pub fn read_holders(
-    deps: Deps,
+/// Holders with an address between `start_after` and `start_before`, both excluded
+pub fn read_holder_entries(
+    storage: &dyn Storage,
     start_after: Option<CanonicalAddr>,
+    start_before: Option<CanonicalAddr>,
     limit: Option<u32>,
-) -> StdResult<Vec<HolderResponse>> {
-    let holder_bucket: ReadonlyBucket<Holder> = bucket_read(deps.storage, PREFIX_HOLDERS);
+    order: Order,
+) -> StdResult<Vec<(CanonicalAddr, Holder)>> {
+    let holder_bucket: ReadonlyBucket<Holder> = bucket_read(storage, PREFIX_HOLDERS);
 
     let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
     let start = calc_range_start(start_after);
+    let end = start_before.map(|addr| addr.as_slice().to_vec());
 
     holder_bucket
-        .range(start.as_deref(), None, Order::Ascending)
+        .range(start.as_deref(), end.as_deref(), order)
         .take(limit)
         .map(|elem| {
             let (k, v) = elem?;
-            let address = deps.api.addr_humanize(&CanonicalAddr::from(k))?.to_string();
-            Ok(HolderResponse {
-                address,
-                balance: v.balance,
-                index: v.index,
-                pending_rewards: v.pending_rewards,
-            })
+            Ok((CanonicalAddr::from(k), v))
+        })
+        .collect()
+}
+
+/// Addresses of the holders with the largest balances, largest first
+pub fn read_top_holders(
+    storage: &dyn Storage,
+    limit: Option<u32>,
+) -> StdResult<Vec<CanonicalAddr>> {
+    let balance_bucket: ReadonlyBucket<bool> = bucket_read(storage, PREFIX_HOLDER_BALANCES);
+
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+
+    balance_bucket
+        .range(None, None, Order::Descending)
+        .take(limit)
+        .map(|elem| {
+            let (k, _) = elem?;
+            Ok(CanonicalAddr::from(k[16..].to_vec()))
         })
         .collect()
 }
@@ -118,3 +445,76 @@ fn calc_range_start(start_after: Option<CanonicalAddr>) -> Option<Vec<u8>> {
         v
     })
 }
+
+pub fn read_lock_positions(
+    storage: &dyn Storage,
+    holder_address: &CanonicalAddr,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<Vec<(u64, LockPosition)>> {
+    let position_bucket: ReadonlyBucket<LockPosition> =
+        ReadonlyBucket::multilevel(storage, &[PREFIX_LOCKS, holder_address.as_slice()]);
+
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(|lock_id| (lock_id + 1).to_be_bytes().to_vec());
+
+    position_bucket
+        .range(start.as_deref(), None, Order::Ascending)
+        .take(limit)
+        .map(|elem| {
+            let (k, v) = elem?;
+            let mut lock_id = [0u8; 8];
+            lock_id.copy_from_slice(&k);
+            Ok((u64::from_be_bytes(lock_id), v))
+        })
+        .collect()
+}
+
+pub fn read_reward_checkpoints(
+    storage: &dyn Storage,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<Vec<(u64, RewardCheckpoint)>> {
+    let checkpoint_bucket: ReadonlyBucket<RewardCheckpoint> =
+        bucket_read(storage, PREFIX_REWARD_HISTORY);
+
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(|checkpoint_id| (checkpoint_id + 1).to_be_bytes().to_vec());
+
+    checkpoint_bucket
+        .range(start.as_deref(), None, Order::Ascending)
+        .take(limit)
+        .map(|elem| {
+            let (k, v) = elem?;
+            let mut checkpoint_id = [0u8; 8];
+            checkpoint_id.copy_from_slice(&k);
+            Ok((u64::from_be_bytes(checkpoint_id), v))
+        })
+        .collect()
+}
+
+/// Up to `limit` checkpoints recorded after `time`, newest first, and whether they go back
+/// to `time`
+pub fn read_reward_checkpoints_after(
+    storage: &dyn Storage,
+    time: u64,
+    limit: usize,
+) -> StdResult<(Vec<RewardCheckpoint>, bool)> {
+    let checkpoint_bucket: ReadonlyBucket<RewardCheckpoint> =
+        bucket_read(storage, PREFIX_REWARD_HISTORY);
+
+    // the checkpoint after the last one read tells whether they go back to `time`
+    let mut checkpoints = vec![];
+    for elem in checkpoint_bucket
+        .range(None, None, Order::Descending)
+        .take(limit + 1)
+    {
+        let (_, checkpoint) = elem?;
+        if checkpoint.time <= time {
+            return Ok((checkpoints, true));
+        }
+        checkpoints.push(checkpoint);
+    }
+    checkpoints.truncate(limit);
+    Ok((checkpoints, false))
+}
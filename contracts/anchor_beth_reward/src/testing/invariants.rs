@@ -0,0 +1,261 @@
+//! Random sequences of balance updates, reward deposits and claims checked against
+//! a model of the chain, see `Model` for the invariants asserted after every step.
+
+use cosmwasm_bignumber::Decimal256;
+use cosmwasm_std::testing::{mock_env, mock_info, MockApi, MockStorage, MOCK_CONTRACT_ADDR};
+use cosmwasm_std::{coins, from_binary, BankMsg, CosmosMsg, OwnedDeps, StdError, Uint128};
+use proptest::collection::vec;
+use proptest::prelude::*;
+
+use crate::contract::{execute, instantiate, query};
+use crate::testing::mock_dependencies;
+use beth::mock_querier::WasmMockQuerier;
+use beth::reward::{
+    AccruedRewardsResponse, ExecuteMsg, HolderResponse, InstantiateMsg, QueryMsg, SolvencyResponse,
+    StateResponse,
+};
+
+const REWARD_DENOM: &str = "uusd";
+const OWNER: &str = "owner0000";
+const TOKEN_CONTRACT: &str = "token0000";
+const HOLDERS: [&str; 5] = ["addr0000", "addr0001", "addr0002", "addr0003", "addr0004"];
+
+// tax rate (1% on Terra) and cap returned by the mock querier
+const TAX_RATE_PERCENT: u128 = if cfg!(feature = "terra") { 1 } else { 0 };
+const TAX_CAP: u128 = 1_000_000;
+
+// exact rewards are tracked in 1e-18 uusd, the precision of the global index
+const PRECISION: u128 = 1_000_000_000_000_000_000;
+// truncation of the global index on every update, far below 1 uusd
+const INDEX_TOLERANCE: i128 = 1_000_000_000_000;
+
+#[derive(Clone, Debug)]
+enum Op {
+    Increase { holder: usize, amount: u128 },
+    Decrease { holder: usize, amount: u128 },
+    Deposit { amount: u128 },
+    Claim { holder: usize },
+}
+
+fn op_strategy() -> impl Strategy<Value = Op> {
+    prop_oneof![
+        (0..HOLDERS.len(), 1..1_000_000_000u128)
+            .prop_map(|(holder, amount)| Op::Increase { holder, amount }),
+        (0..HOLDERS.len(), 1..1_000_000_000u128)
+            .prop_map(|(holder, amount)| Op::Decrease { holder, amount }),
+        (1..1_000_000_000u128).prop_map(|amount| Op::Deposit { amount }),
+        (0..HOLDERS.len()).prop_map(|holder| Op::Claim { holder }),
+    ]
+}
+
+/// Mirrors the chain: the native balance of the reward contract and the
+/// exact share of every reward inflow each holder is entitled to.
+struct Model {
+    deps: OwnedDeps<MockStorage, MockApi, WasmMockQuerier>,
+    contract_balance: u128,
+    prev_reward_balance: u128,
+    total_deposits: u128,
+    total_paid: u128,
+    balances: [u128; HOLDERS.len()],
+    claimed: [u128; HOLDERS.len()],
+    entitled: [u128; HOLDERS.len()],
+}
+
+impl Model {
+    fn new() -> Self {
+        let mut deps = mock_dependencies(&[]);
+        let msg = InstantiateMsg {
+            owner: OWNER.to_string(),
+            reward_denom: REWARD_DENOM.to_string(),
+        };
+        instantiate(deps.as_mut(), mock_env(), mock_info(OWNER, &[]), msg).unwrap();
+        let msg = ExecuteMsg::PostInitialize {
+            token_contract: TOKEN_CONTRACT.to_string(),
+        };
+        execute(deps.as_mut(), mock_env(), mock_info(OWNER, &[]), msg).unwrap();
+
+        Model {
+            deps,
+            contract_balance: 0,
+            prev_reward_balance: 0,
+            total_deposits: 0,
+            total_paid: 0,
+            balances: [0; HOLDERS.len()],
+            claimed: [0; HOLDERS.len()],
+            entitled: [0; HOLDERS.len()],
+        }
+    }
+
+    fn set_contract_balance(&mut self, balance: u128) {
+        self.contract_balance = balance;
+        self.deps
+            .querier
+            .update_balance(MOCK_CONTRACT_ADDR, &coins(balance, REWARD_DENOM));
+    }
+
+    // new rewards are shared by the holders of the total balance before the operation
+    fn distribute(&mut self) {
+        let total: u128 = self.balances.iter().sum();
+        if total == 0 {
+            return;
+        }
+        let inflow = self.contract_balance - self.prev_reward_balance;
+        for (entitled, balance) in self.entitled.iter_mut().zip(self.balances.iter()) {
+            *entitled += inflow * balance * PRECISION / total;
+        }
+        self.prev_reward_balance = self.contract_balance;
+    }
+
+    fn apply(&mut self, op: Op) {
+        match op {
+            Op::Increase { holder, amount } => {
+                self.distribute();
+                let msg = ExecuteMsg::IncreaseBalance {
+                    address: HOLDERS[holder].to_string(),
+                    amount: Uint128::new(amount),
+                };
+                let info = mock_info(TOKEN_CONTRACT, &[]);
+                execute(self.deps.as_mut(), mock_env(), info, msg).unwrap();
+                self.balances[holder] += amount;
+            }
+            Op::Decrease { holder, amount } => {
+                self.distribute();
+                let msg = ExecuteMsg::DecreaseBalance {
+                    address: HOLDERS[holder].to_string(),
+                    amount: Uint128::new(amount),
+                };
+                let info = mock_info(TOKEN_CONTRACT, &[]);
+                let res = execute(self.deps.as_mut(), mock_env(), info, msg);
+                if amount > self.balances[holder] {
+                    assert!(res.is_err());
+                } else {
+                    res.unwrap();
+                    self.balances[holder] -= amount;
+                }
+            }
+            Op::Deposit { amount } => {
+                self.total_deposits += amount;
+                self.set_contract_balance(self.contract_balance + amount);
+            }
+            Op::Claim { holder } => {
+                self.distribute();
+                let msg = ExecuteMsg::ClaimRewards { recipient: None };
+                let info = mock_info(HOLDERS[holder], &[]);
+                match execute(self.deps.as_mut(), mock_env(), info, msg) {
+                    Ok(res) => {
+                        let rewards: u128 = res
+                            .attributes
+                            .iter()
+                            .find(|attr| attr.key == "rewards")
+                            .unwrap()
+                            .value
+                            .parse()
+                            .unwrap();
+                        let sent = match &res.messages[0].msg {
+                            CosmosMsg::Bank(BankMsg::Send { amount, .. }) => {
+                                amount[0].amount.u128()
+                            }
+                            _ => panic!("DO NOT ENTER HERE"),
+                        };
+                        // the chain charges the tax to the contract on top of the sent amount
+                        let tax = std::cmp::min(sent * TAX_RATE_PERCENT / 100, TAX_CAP);
+                        assert!(sent + tax <= rewards);
+                        assert!(sent + tax <= self.contract_balance);
+
+                        self.claimed[holder] += rewards;
+                        self.total_paid += sent + tax;
+                        self.prev_reward_balance -= rewards;
+                        self.set_contract_balance(self.contract_balance - sent - tax);
+                    }
+                    Err(StdError::GenericErr { msg, .. }) => {
+                        assert_eq!(msg, "No rewards have accrued yet")
+                    }
+                    Err(err) => panic!("unexpected error: {}", err),
+                }
+            }
+        }
+    }
+
+    fn query_accrued(&self, holder: usize) -> u128 {
+        let msg = QueryMsg::AccruedRewards {
+            address: HOLDERS[holder].to_string(),
+        };
+        let res: AccruedRewardsResponse =
+            from_binary(&query(self.deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+        res.rewards.u128()
+    }
+
+    fn query_holder(&self, holder: usize) -> HolderResponse {
+        let msg = QueryMsg::Holder {
+            address: HOLDERS[holder].to_string(),
+        };
+        from_binary(&query(self.deps.as_ref(), mock_env(), msg).unwrap()).unwrap()
+    }
+
+    fn assert_invariants(&mut self) {
+        let state: StateResponse =
+            from_binary(&query(self.deps.as_ref(), mock_env(), QueryMsg::State {}).unwrap())
+                .unwrap();
+
+        // the total balance is the sum of the holder balances, the holders with nothing
+        // left to claim are pruned
+        let mut total_balance = 0u128;
+        let mut holder_count = 0u64;
+        for holder in 0..HOLDERS.len() {
+            let res = self.query_holder(holder);
+            assert_eq!(res.balance.u128(), self.balances[holder]);
+            total_balance += res.balance.u128();
+            if !res.balance.is_zero() || res.pending_rewards >= Decimal256::one() {
+                holder_count += 1;
+            }
+        }
+        assert_eq!(state.total_balance.u128(), total_balance);
+        assert_eq!(state.holder_count, holder_count);
+
+        // the queries see the rewards that are not distributed yet
+        self.distribute();
+
+        // paid and accrued rewards never exceed the deposits, the tax deducted
+        // above what the chain charges stays in the contract as new rewards
+        let mut total_accrued = 0u128;
+        for holder in 0..HOLDERS.len() {
+            let accrued = self.query_accrued(holder);
+            let rewards = self.claimed[holder] + accrued;
+            total_accrued += accrued;
+
+            // each holder loses less than 1 uusd to rounding
+            let loss = self.entitled[holder] as i128 - (rewards * PRECISION) as i128;
+            assert!(
+                loss > -INDEX_TOLERANCE && loss < PRECISION as i128 + INDEX_TOLERANCE,
+                "holder {} is entitled to {} but got {}",
+                holder,
+                self.entitled[holder],
+                rewards
+            );
+        }
+        assert!(self.total_paid + total_accrued <= self.total_deposits);
+
+        // the running total of the liabilities covers the accrued rewards of every holder,
+        // with less than 1 uusd pending to each, and the reward balance covers it
+        let solvency: SolvencyResponse =
+            from_binary(&query(self.deps.as_ref(), mock_env(), QueryMsg::Solvency {}).unwrap())
+                .unwrap();
+        let liabilities = solvency.liabilities.u128();
+        assert!(liabilities >= total_accrued);
+        assert!(liabilities < total_accrued + HOLDERS.len() as u128);
+        assert_eq!(solvency.deficit, Uint128::zero());
+    }
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(128))]
+
+    #[test]
+    fn reward_accounting_invariants(ops in vec(op_strategy(), 1..50)) {
+        let mut model = Model::new();
+        for op in ops {
+            model.apply(op);
+            model.assert_invariants();
+        }
+    }
+}
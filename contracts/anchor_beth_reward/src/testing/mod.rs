@@ -1,2 +1,18 @@
-mod mock_querier;
+use beth::mock_querier::{mock_dependencies_with_querier, WasmMockQuerier};
+use cosmwasm_std::testing::{MockApi, MockStorage, MOCK_CONTRACT_ADDR};
+use cosmwasm_std::{Coin, Decimal, OwnedDeps};
+
+mod invariants;
 mod tests;
+
+/// Mock dependencies holding `contract_balance` with a 1% tax capped at 1 UST
+pub(crate) fn mock_dependencies(
+    contract_balance: &[Coin],
+) -> OwnedDeps<MockStorage, MockApi, WasmMockQuerier> {
+    mock_dependencies_with_querier(
+        WasmMockQuerier::builder()
+            .with_balance(MOCK_CONTRACT_ADDR, contract_balance)
+            .with_tax(Decimal::percent(1), &[("uusd", 1000000u128)])
+            .build(),
+    )
+}
@@ -1,13 +1,31 @@
-use cosmwasm_std::testing::{mock_env, mock_info};
-use cosmwasm_std::{from_binary, BankMsg, Coin, CosmosMsg, Decimal, StdError, SubMsg, Uint128};
+use cosmwasm_std::testing::{mock_env, mock_info, MockApi, MockStorage, MOCK_CONTRACT_ADDR};
+use cosmwasm_std::{
+    attr, from_binary, to_binary, Api, BankMsg, CanonicalAddr, Coin, CosmosMsg, Decimal, Deps,
+    DepsMut, OwnedDeps, StdError, SubMsg, Uint128, WasmMsg,
+};
 
-use crate::contract::{execute, instantiate, query};
-use crate::math::{decimal_multiplication_in_256, decimal_subtraction_in_256};
-use crate::testing::mock_querier::mock_dependencies;
+use crate::contract::{execute, instantiate, migrate as contract_migrate, query};
+use crate::math::{
+    decimal_from_ratio_in_256, decimal_multiplication_in_256, decimal_subtraction_in_256,
+};
+use crate::state::{
+    is_holder, read_holder, read_state, store_state, Holder, KEY_CONFIG, KEY_STATE, PREFIX_HOLDERS,
+};
+use crate::testing::mock_dependencies;
+use beth::mock_querier::WasmMockQuerier;
 use beth::reward::{
-    ConfigResponse, ExecuteMsg, HolderResponse, HoldersResponse, InstantiateMsg, QueryMsg,
-    StateResponse,
+    AccruedRewardsResponse, ConfigResponse, Cw20HookMsg, ExecuteMsg, ExportStateResponse,
+    ExportedLockPosition, GlobalIndexAtResponse, HolderResponse, HoldersResponse, InstantiateMsg,
+    LockConfigResponse, LockPositionResponse, LockPositionsResponse, MigrateMsg, OrderBy, QueryMsg,
+    RewardCheckpointResponse, RewardHistoryResponse, RewardRateResponse, SolvencyResponse,
+    StateResponse, StatsResponse, WeightResponse, EXPORT_VERSION,
 };
+use beth::CustomMsg;
+use cosmwasm_bignumber::Decimal256;
+use cosmwasm_storage::{bucket, singleton};
+use cw2::{get_contract_version, set_contract_version};
+use cw20::{Cw20ExecuteMsg, Cw20ReceiveMsg};
+use serde::{Deserialize, Serialize};
 use std::str::FromStr;
 
 const DEFAULT_REWARD_DENOM: &str = "uusd";
@@ -46,6 +64,8 @@ fn proper_init() {
             owner: MOCK_OWNER_ADDR.to_string(),
             token_contract: Some(MOCK_TOKEN_CONTRACT_ADDR.to_string()),
             reward_denom: DEFAULT_REWARD_DENOM.to_string(),
+            protocol_fee_rate: Decimal::zero(),
+            treasury: None,
         }
     );
 
@@ -54,9 +74,16 @@ fn proper_init() {
     assert_eq!(
         state_response,
         StateResponse {
-            global_index: Decimal::zero(),
+            global_index: Decimal256::zero(),
             total_balance: Uint128::new(0u128),
-            prev_reward_balance: Uint128::zero()
+            prev_reward_balance: Uint128::zero(),
+            total_locked: Uint128::zero(),
+            total_locked_weight: Uint128::zero(),
+            total_effective_balance: Uint128::new(0u128),
+            lock_penalty_index: Decimal256::zero(),
+            protocol_fee: Uint128::zero(),
+            holder_count: 0,
+            holders_indexed: true,
         }
     );
 }
@@ -107,8 +134,16 @@ fn increase_balance() {
         HolderResponse {
             address: "addr0000".to_string(),
             balance: Uint128::from(100u128),
-            index: Decimal::zero(),
-            pending_rewards: Decimal::zero(),
+            index: Decimal256::zero(),
+            pending_rewards: Decimal256::zero(),
+            accrued_rewards: Uint128::new(100u128),
+            total_claimed: Uint128::zero(),
+            last_claim_time: None,
+            locked_amount: Uint128::zero(),
+            locked_weight: Uint128::zero(),
+            penalty_index: Decimal256::zero(),
+            pending_penalties: Decimal256::zero(),
+            accrued_penalties: Uint128::zero(),
         }
     );
 
@@ -133,8 +168,16 @@ fn increase_balance() {
         HolderResponse {
             address: "addr0000".to_string(),
             balance: Uint128::from(200u128),
-            index: Decimal::one(),
-            pending_rewards: Decimal::from_str("100").unwrap(),
+            index: Decimal256::one(),
+            pending_rewards: Decimal256::from_str("100").unwrap(),
+            accrued_rewards: Uint128::new(100u128),
+            total_claimed: Uint128::zero(),
+            last_claim_time: None,
+            locked_amount: Uint128::zero(),
+            locked_weight: Uint128::zero(),
+            penalty_index: Decimal256::zero(),
+            pending_penalties: Decimal256::zero(),
+            accrued_penalties: Uint128::zero(),
         }
     );
 }
@@ -185,8 +228,16 @@ fn increase_balance_with_decimals() {
         HolderResponse {
             address: "addr0000".to_string(),
             balance: Uint128::from(11u128),
-            index: Decimal::zero(),
-            pending_rewards: Decimal::zero(),
+            index: Decimal256::zero(),
+            pending_rewards: Decimal256::zero(),
+            accrued_rewards: Uint128::new(99999u128),
+            total_claimed: Uint128::zero(),
+            last_claim_time: None,
+            locked_amount: Uint128::zero(),
+            locked_weight: Uint128::zero(),
+            penalty_index: Decimal256::zero(),
+            pending_penalties: Decimal256::zero(),
+            accrued_penalties: Uint128::zero(),
         }
     );
 
@@ -206,14 +257,12 @@ fn increase_balance_with_decimals() {
     )
     .unwrap();
     let holder_response: HolderResponse = from_binary(&res).unwrap();
-    let index = decimal_multiplication_in_256(
-        Decimal::from_ratio(Uint128::new(100000), Uint128::new(11)),
-        Decimal::one(),
-    );
+    let index = decimal_from_ratio_in_256(Uint128::new(100000), Uint128::new(11)).unwrap();
     let user_pend_reward = decimal_multiplication_in_256(
-        Decimal::from_str("11").unwrap(),
-        decimal_subtraction_in_256(holder_response.index, Decimal::zero()),
-    );
+        Decimal256::from_str("11").unwrap(),
+        decimal_subtraction_in_256(holder_response.index, Decimal256::zero()).unwrap(),
+    )
+    .unwrap();
     assert_eq!(
         holder_response,
         HolderResponse {
@@ -221,6 +270,14 @@ fn increase_balance_with_decimals() {
             balance: Uint128::from(21u128),
             index,
             pending_rewards: user_pend_reward,
+            accrued_rewards: Uint128::new(99999u128),
+            total_claimed: Uint128::zero(),
+            last_claim_time: None,
+            locked_amount: Uint128::zero(),
+            locked_weight: Uint128::zero(),
+            penalty_index: Decimal256::zero(),
+            pending_penalties: Decimal256::zero(),
+            accrued_penalties: Uint128::zero(),
         }
     );
 }
@@ -295,10 +352,148 @@ fn decrease_balance() {
         HolderResponse {
             address: "addr0000".to_string(),
             balance: Uint128::zero(),
-            index: Decimal::one(),
-            pending_rewards: Decimal::from_str("100").unwrap(),
+            index: Decimal256::one(),
+            pending_rewards: Decimal256::from_str("100").unwrap(),
+            accrued_rewards: Uint128::new(100u128),
+            total_claimed: Uint128::zero(),
+            last_claim_time: None,
+            locked_amount: Uint128::zero(),
+            locked_weight: Uint128::zero(),
+            penalty_index: Decimal256::zero(),
+            pending_penalties: Decimal256::zero(),
+            accrued_penalties: Uint128::zero(),
+        }
+    );
+}
+
+#[test]
+fn batch_transfer_balance() {
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: Uint128::new(100u128),
+    }]);
+
+    let init_msg = default_init();
+    let info = mock_info("addr0000", &[]);
+    instantiate(deps.as_mut(), mock_env(), info, init_msg).unwrap();
+
+    let msg = ExecuteMsg::PostInitialize {
+        token_contract: MOCK_TOKEN_CONTRACT_ADDR.to_string(),
+    };
+    let info = mock_info(MOCK_OWNER_ADDR, &[]);
+    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let info = mock_info(MOCK_TOKEN_CONTRACT_ADDR, &[]);
+    let msg = ExecuteMsg::IncreaseBalance {
+        address: "addr0000".to_string(),
+        amount: Uint128::from(100u128),
+    };
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let msg = ExecuteMsg::BatchTransferBalance {
+        from: "addr0000".to_string(),
+        transfers: vec![
+            ("addr0001".to_string(), Uint128::from(30u128)),
+            ("addr0002".to_string(), Uint128::from(20u128)),
+        ],
+    };
+
+    let info = mock_info("addr0000", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg.clone());
+    match res {
+        Err(StdError::GenericErr { msg, .. }) => assert_eq!(msg, "unauthorized"),
+        _ => panic!("DO NOT ENTER HERE"),
+    };
+
+    let info = mock_info(MOCK_TOKEN_CONTRACT_ADDR, &[]);
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        ExecuteMsg::BatchTransferBalance {
+            from: "addr0000".to_string(),
+            transfers: vec![
+                ("addr0001".to_string(), Uint128::from(80u128)),
+                ("addr0002".to_string(), Uint128::from(30u128)),
+            ],
+        },
+    );
+    match res {
+        Err(StdError::GenericErr { msg, .. }) => {
+            assert_eq!(msg, "Decrease amount cannot exceed user balance: 100")
+        }
+        _ => panic!("DO NOT ENTER HERE"),
+    };
+
+    let info = mock_info(MOCK_TOKEN_CONTRACT_ADDR, &[]);
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let res = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::Holders {
+            start_after: None,
+            start_before: None,
+            limit: None,
+            order: None,
+        },
+    )
+    .unwrap();
+    let holders_response: HoldersResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        holders_response,
+        HoldersResponse {
+            holders: vec![
+                HolderResponse {
+                    address: "addr0000".to_string(),
+                    balance: Uint128::from(50u128),
+                    index: Decimal256::one(),
+                    pending_rewards: Decimal256::from_str("100").unwrap(),
+                    accrued_rewards: Uint128::new(100u128),
+                    total_claimed: Uint128::zero(),
+                    last_claim_time: None,
+                    locked_amount: Uint128::zero(),
+                    locked_weight: Uint128::zero(),
+                    penalty_index: Decimal256::zero(),
+                    pending_penalties: Decimal256::zero(),
+                    accrued_penalties: Uint128::zero(),
+                },
+                HolderResponse {
+                    address: "addr0001".to_string(),
+                    balance: Uint128::from(30u128),
+                    index: Decimal256::one(),
+                    pending_rewards: Decimal256::zero(),
+                    accrued_rewards: Uint128::zero(),
+                    total_claimed: Uint128::zero(),
+                    last_claim_time: None,
+                    locked_amount: Uint128::zero(),
+                    locked_weight: Uint128::zero(),
+                    penalty_index: Decimal256::zero(),
+                    pending_penalties: Decimal256::zero(),
+                    accrued_penalties: Uint128::zero(),
+                },
+                HolderResponse {
+                    address: "addr0002".to_string(),
+                    balance: Uint128::from(20u128),
+                    index: Decimal256::one(),
+                    pending_rewards: Decimal256::zero(),
+                    accrued_rewards: Uint128::zero(),
+                    total_claimed: Uint128::zero(),
+                    last_claim_time: None,
+                    locked_amount: Uint128::zero(),
+                    locked_weight: Uint128::zero(),
+                    penalty_index: Decimal256::zero(),
+                    pending_penalties: Decimal256::zero(),
+                    accrued_penalties: Uint128::zero(),
+                }
+            ],
         }
     );
+
+    // the total balance does not change
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::State {}).unwrap();
+    let state_response: StateResponse = from_binary(&res).unwrap();
+    assert_eq!(state_response.total_balance, Uint128::from(100u128));
 }
 
 #[test]
@@ -340,8 +535,16 @@ fn claim_rewards() {
         HolderResponse {
             address: "addr0000".to_string(),
             balance: Uint128::from(100u128),
-            index: Decimal::zero(),
-            pending_rewards: Decimal::zero(),
+            index: Decimal256::zero(),
+            pending_rewards: Decimal256::zero(),
+            accrued_rewards: Uint128::new(100u128),
+            total_claimed: Uint128::zero(),
+            last_claim_time: None,
+            locked_amount: Uint128::zero(),
+            locked_weight: Uint128::zero(),
+            penalty_index: Decimal256::zero(),
+            pending_penalties: Decimal256::zero(),
+            accrued_penalties: Uint128::zero(),
         }
     );
 
@@ -354,7 +557,12 @@ fn claim_rewards() {
             to_address: "addr0000".to_string(),
             amount: vec![Coin {
                 denom: "uusd".to_string(),
-                amount: Uint128::from(99u128), // 1% tax
+                // 1% tax on Terra
+                amount: Uint128::from(if cfg!(feature = "terra") {
+                    99u128
+                } else {
+                    100u128
+                }),
             },]
         }))]
     );
@@ -370,7 +578,12 @@ fn claim_rewards() {
             to_address: "addr0001".to_string(),
             amount: vec![Coin {
                 denom: "uusd".to_string(),
-                amount: Uint128::from(99u128), // 1% tax
+                // 1% tax on Terra
+                amount: Uint128::from(if cfg!(feature = "terra") {
+                    99u128
+                } else {
+                    100u128
+                }),
             },]
         }))]
     );
@@ -415,8 +628,16 @@ fn claim_rewards_with_decimals() {
         HolderResponse {
             address: "addr0000".to_string(),
             balance: Uint128::from(11u128),
-            index: Decimal::zero(),
-            pending_rewards: Decimal::zero(),
+            index: Decimal256::zero(),
+            pending_rewards: Decimal256::zero(),
+            accrued_rewards: Uint128::new(99998u128),
+            total_claimed: Uint128::zero(),
+            last_claim_time: None,
+            locked_amount: Uint128::zero(),
+            locked_weight: Uint128::zero(),
+            penalty_index: Decimal256::zero(),
+            pending_penalties: Decimal256::zero(),
+            accrued_penalties: Uint128::zero(),
         }
     );
 
@@ -429,7 +650,12 @@ fn claim_rewards_with_decimals() {
             to_address: "addr0000".to_string(),
             amount: vec![Coin {
                 denom: "uusd".to_string(),
-                amount: Uint128::from(99007u128), // 1% tax
+                // 1% tax on Terra
+                amount: Uint128::from(if cfg!(feature = "terra") {
+                    99007u128
+                } else {
+                    99998u128
+                }),
             },]
         }))]
     );
@@ -443,17 +669,22 @@ fn claim_rewards_with_decimals() {
     )
     .unwrap();
     let holder_response: HolderResponse = from_binary(&res).unwrap();
-    let index = decimal_multiplication_in_256(
-        Decimal::from_ratio(Uint128::new(99999), Uint128::new(11)),
-        Decimal::one(),
-    );
+    let index = decimal_from_ratio_in_256(Uint128::new(99999), Uint128::new(11)).unwrap();
     assert_eq!(
         holder_response,
         HolderResponse {
             address: "addr0000".to_string(),
             balance: Uint128::from(11u128),
             index,
-            pending_rewards: Decimal::from_str("0.999999999999999991").unwrap(),
+            pending_rewards: Decimal256::from_str("0.999999999999999991").unwrap(),
+            accrued_rewards: Uint128::new(99998u128),
+            total_claimed: Uint128::new(99998u128),
+            last_claim_time: Some(mock_env().block.time.seconds()),
+            locked_amount: Uint128::zero(),
+            locked_weight: Uint128::zero(),
+            penalty_index: Decimal256::zero(),
+            pending_penalties: Decimal256::zero(),
+            accrued_penalties: Uint128::zero(),
         }
     );
 
@@ -464,7 +695,81 @@ fn claim_rewards_with_decimals() {
         StateResponse {
             global_index: index,
             total_balance: Uint128::new(11u128),
-            prev_reward_balance: Uint128::new(1)
+            prev_reward_balance: Uint128::new(1),
+            total_locked: Uint128::zero(),
+            total_locked_weight: Uint128::zero(),
+            total_effective_balance: Uint128::new(11u128),
+            lock_penalty_index: Decimal256::zero(),
+            protocol_fee: Uint128::zero(),
+            holder_count: 1,
+            holders_indexed: true,
+        }
+    );
+}
+
+#[test]
+fn deposit_rewards() {
+    // the contract balance already includes the deposit
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: Uint128::new(100u128),
+    }]);
+
+    let init_msg = default_init();
+    let info = mock_info("addr0000", &[]);
+    instantiate(deps.as_mut(), mock_env(), info, init_msg).unwrap();
+
+    let msg = ExecuteMsg::PostInitialize {
+        token_contract: MOCK_TOKEN_CONTRACT_ADDR.to_string(),
+    };
+    let info = mock_info(MOCK_OWNER_ADDR, &[]);
+    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let msg = ExecuteMsg::IncreaseBalance {
+        address: "addr0000".to_string(),
+        amount: Uint128::from(100u128),
+    };
+    let info = mock_info(MOCK_TOKEN_CONTRACT_ADDR, &[]);
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let msg = ExecuteMsg::DepositRewards {};
+    let info = mock_info("dispatcher0000", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg.clone());
+    assert_eq!(
+        res.unwrap_err(),
+        StdError::generic_err("No uusd assets are provided to deposit")
+    );
+
+    let info = mock_info(
+        "dispatcher0000",
+        &[Coin::new(100u128, "uusd"), Coin::new(10u128, "ukrw")],
+    );
+    let res = execute(deps.as_mut(), mock_env(), info, msg.clone());
+    assert_eq!(
+        res.unwrap_err(),
+        StdError::generic_err("Only uusd can be deposited")
+    );
+
+    let info = mock_info("dispatcher0000", &[Coin::new(100u128, "uusd")]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(res.messages.len(), 0);
+
+    // the index moves at deposit time
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::State {}).unwrap();
+    let state_response: StateResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        state_response,
+        StateResponse {
+            global_index: Decimal256::one(),
+            total_balance: Uint128::from(100u128),
+            prev_reward_balance: Uint128::from(100u128),
+            total_locked: Uint128::zero(),
+            total_locked_weight: Uint128::zero(),
+            total_effective_balance: Uint128::from(100u128),
+            lock_penalty_index: Decimal256::zero(),
+            protocol_fee: Uint128::zero(),
+            holder_count: 1,
+            holders_indexed: true,
         }
     );
 }
@@ -513,7 +818,9 @@ fn query_holders() {
         mock_env(),
         QueryMsg::Holders {
             start_after: None,
+            start_before: None,
             limit: None,
+            order: None,
         },
     )
     .unwrap();
@@ -525,20 +832,44 @@ fn query_holders() {
                 HolderResponse {
                     address: String::from("addr0000"),
                     balance: Uint128::from(100u128),
-                    index: Decimal::zero(),
-                    pending_rewards: Decimal::zero(),
+                    index: Decimal256::zero(),
+                    pending_rewards: Decimal256::zero(),
+                    accrued_rewards: Uint128::new(100u128),
+                    total_claimed: Uint128::zero(),
+                    last_claim_time: None,
+                    locked_amount: Uint128::zero(),
+                    locked_weight: Uint128::zero(),
+                    penalty_index: Decimal256::zero(),
+                    pending_penalties: Decimal256::zero(),
+                    accrued_penalties: Uint128::zero(),
                 },
                 HolderResponse {
                     address: String::from("addr0001"),
                     balance: Uint128::from(200u128),
-                    index: Decimal::one(),
-                    pending_rewards: Decimal::zero(),
+                    index: Decimal256::one(),
+                    pending_rewards: Decimal256::zero(),
+                    accrued_rewards: Uint128::zero(),
+                    total_claimed: Uint128::zero(),
+                    last_claim_time: None,
+                    locked_amount: Uint128::zero(),
+                    locked_weight: Uint128::zero(),
+                    penalty_index: Decimal256::zero(),
+                    pending_penalties: Decimal256::zero(),
+                    accrued_penalties: Uint128::zero(),
                 },
                 HolderResponse {
                     address: String::from("addr0002"),
                     balance: Uint128::from(300u128),
-                    index: Decimal::one(),
-                    pending_rewards: Decimal::zero(),
+                    index: Decimal256::one(),
+                    pending_rewards: Decimal256::zero(),
+                    accrued_rewards: Uint128::zero(),
+                    total_claimed: Uint128::zero(),
+                    last_claim_time: None,
+                    locked_amount: Uint128::zero(),
+                    locked_weight: Uint128::zero(),
+                    penalty_index: Decimal256::zero(),
+                    pending_penalties: Decimal256::zero(),
+                    accrued_penalties: Uint128::zero(),
                 },
             ],
         }
@@ -550,7 +881,9 @@ fn query_holders() {
         mock_env(),
         QueryMsg::Holders {
             start_after: None,
+            start_before: None,
             limit: Some(1),
+            order: None,
         },
     )
     .unwrap();
@@ -561,8 +894,16 @@ fn query_holders() {
             holders: vec![HolderResponse {
                 address: String::from("addr0000"),
                 balance: Uint128::from(100u128),
-                index: Decimal::zero(),
-                pending_rewards: Decimal::zero(),
+                index: Decimal256::zero(),
+                pending_rewards: Decimal256::zero(),
+                accrued_rewards: Uint128::new(100u128),
+                total_claimed: Uint128::zero(),
+                last_claim_time: None,
+                locked_amount: Uint128::zero(),
+                locked_weight: Uint128::zero(),
+                penalty_index: Decimal256::zero(),
+                pending_penalties: Decimal256::zero(),
+                accrued_penalties: Uint128::zero(),
             }],
         }
     );
@@ -573,7 +914,9 @@ fn query_holders() {
         mock_env(),
         QueryMsg::Holders {
             start_after: Some(String::from("addr0000")),
+            start_before: None,
             limit: None,
+            order: None,
         },
     )
     .unwrap();
@@ -585,14 +928,30 @@ fn query_holders() {
                 HolderResponse {
                     address: String::from("addr0001"),
                     balance: Uint128::from(200u128),
-                    index: Decimal::one(),
-                    pending_rewards: Decimal::zero(),
+                    index: Decimal256::one(),
+                    pending_rewards: Decimal256::zero(),
+                    accrued_rewards: Uint128::zero(),
+                    total_claimed: Uint128::zero(),
+                    last_claim_time: None,
+                    locked_amount: Uint128::zero(),
+                    locked_weight: Uint128::zero(),
+                    penalty_index: Decimal256::zero(),
+                    pending_penalties: Decimal256::zero(),
+                    accrued_penalties: Uint128::zero(),
                 },
                 HolderResponse {
                     address: String::from("addr0002"),
                     balance: Uint128::from(300u128),
-                    index: Decimal::one(),
-                    pending_rewards: Decimal::zero(),
+                    index: Decimal256::one(),
+                    pending_rewards: Decimal256::zero(),
+                    accrued_rewards: Uint128::zero(),
+                    total_claimed: Uint128::zero(),
+                    last_claim_time: None,
+                    locked_amount: Uint128::zero(),
+                    locked_weight: Uint128::zero(),
+                    penalty_index: Decimal256::zero(),
+                    pending_penalties: Decimal256::zero(),
+                    accrued_penalties: Uint128::zero(),
                 }
             ],
         }
@@ -604,7 +963,9 @@ fn query_holders() {
         mock_env(),
         QueryMsg::Holders {
             start_after: Some(String::from("addr0000")),
+            start_before: None,
             limit: Some(1),
+            order: None,
         },
     )
     .unwrap();
@@ -615,9 +976,1897 @@ fn query_holders() {
             holders: vec![HolderResponse {
                 address: String::from("addr0001"),
                 balance: Uint128::from(200u128),
-                index: Decimal::one(),
-                pending_rewards: Decimal::zero(),
+                index: Decimal256::one(),
+                pending_rewards: Decimal256::zero(),
+                accrued_rewards: Uint128::zero(),
+                total_claimed: Uint128::zero(),
+                last_claim_time: None,
+                locked_amount: Uint128::zero(),
+                locked_weight: Uint128::zero(),
+                penalty_index: Decimal256::zero(),
+                pending_penalties: Decimal256::zero(),
+                accrued_penalties: Uint128::zero(),
             }],
         }
     );
 }
+
+#[test]
+fn export_and_import_holders() {
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: Uint128::new(100u128),
+    }]);
+    let info = mock_info("addr0000", &[]);
+    instantiate(deps.as_mut(), mock_env(), info, default_init()).unwrap();
+    let msg = ExecuteMsg::PostInitialize {
+        token_contract: MOCK_TOKEN_CONTRACT_ADDR.to_string(),
+    };
+    let info = mock_info(MOCK_OWNER_ADDR, &[]);
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // addr0000 accrues the 100 uusd before addr0001 joins
+    for (address, amount) in [("addr0000", 100u128), ("addr0001", 300u128)] {
+        let msg = ExecuteMsg::IncreaseBalance {
+            address: address.to_string(),
+            amount: Uint128::new(amount),
+        };
+        let info = mock_info(MOCK_TOKEN_CONTRACT_ADDR, &[]);
+        execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    }
+
+    // addr0001 keeps a lock position, the early unlock of addr0000 is redistributed
+    enable_locking(deps.as_mut());
+    let info = mock_info(MOCK_TOKEN_CONTRACT_ADDR, &[]);
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        info.clone(),
+        lock_msg("addr0001", 100, 1000),
+    )
+    .unwrap();
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        lock_msg("addr0000", 100, 500),
+    )
+    .unwrap();
+    let info = mock_info("addr0000", &[]);
+    let msg = ExecuteMsg::Unlock { lock_id: 2 };
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let export = |deps: Deps, start_after: Option<&str>, limit: u32| -> ExportStateResponse {
+        let msg = QueryMsg::ExportState {
+            start_after: start_after.map(|address| address.to_string()),
+            limit: Some(limit),
+        };
+        from_binary(&query(deps, mock_env(), msg).unwrap()).unwrap()
+    };
+
+    let first_page = export(deps.as_ref(), None, 1);
+    assert_eq!(first_page.version, EXPORT_VERSION);
+    assert_eq!(
+        first_page.contract.as_ref().unwrap().contract,
+        "crates.io:anchor_beth_reward"
+    );
+    assert_eq!(
+        first_page.config.token_contract,
+        Some(MOCK_TOKEN_CONTRACT_ADDR.to_string())
+    );
+    assert_eq!(
+        first_page.state,
+        StateResponse {
+            global_index: Decimal256::one(),
+            total_balance: Uint128::new(400u128),
+            prev_reward_balance: Uint128::new(100u128),
+            total_locked: Uint128::new(100u128),
+            total_locked_weight: Uint128::new(200u128),
+            total_effective_balance: Uint128::new(600u128),
+            lock_penalty_index: Decimal256::from_ratio(50, 500),
+            protocol_fee: Uint128::zero(),
+            holder_count: 2,
+            holders_indexed: true,
+        }
+    );
+    assert_eq!(first_page.holders.len(), 1);
+    assert!(first_page.lock_positions.is_empty());
+    let second_page = export(deps.as_ref(), Some("addr0000"), 1);
+    assert_eq!(second_page.holders.len(), 1);
+    let start = mock_env().block.time.seconds();
+    assert_eq!(
+        second_page.lock_positions,
+        vec![ExportedLockPosition {
+            address: "addr0001".to_string(),
+            lock_id: 1,
+            amount: Uint128::new(100u128),
+            weight: Uint128::new(200u128),
+            start,
+            end: start + 1000,
+        }]
+    );
+    assert!(export(deps.as_ref(), Some("addr0001"), 1)
+        .holders
+        .is_empty());
+
+    // seed a fresh deployment holding the same rewards
+    let mut new_deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: Uint128::new(100u128),
+    }]);
+    let info = mock_info("addr0000", &[]);
+    instantiate(new_deps.as_mut(), mock_env(), info, default_init()).unwrap();
+
+    let import_msg = |holders: &[HolderResponse], lock_positions: &[ExportedLockPosition]| {
+        ExecuteMsg::ImportHolders {
+            state: first_page.state.clone(),
+            holders: holders.to_vec(),
+            lock_positions: lock_positions.to_vec(),
+        }
+    };
+
+    let info = mock_info("addr0000", &[]);
+    let res = execute(
+        new_deps.as_mut(),
+        mock_env(),
+        info,
+        import_msg(&first_page.holders, &first_page.lock_positions),
+    );
+    assert_eq!(res.unwrap_err(), StdError::generic_err("unauthorized"));
+
+    // the lock positions go with their holders
+    let info = mock_info(MOCK_OWNER_ADDR, &[]);
+    let res = execute(
+        new_deps.as_mut(),
+        mock_env(),
+        info.clone(),
+        import_msg(&first_page.holders, &second_page.lock_positions),
+    );
+    assert_eq!(
+        res.unwrap_err(),
+        StdError::generic_err("Lock position 1 of addr0001 is not in the imported holders")
+    );
+    let res = execute(
+        new_deps.as_mut(),
+        mock_env(),
+        info,
+        import_msg(&second_page.holders, &[]),
+    );
+    assert_eq!(
+        res.unwrap_err(),
+        StdError::generic_err("Lock positions of addr0001 do not match its locked amount")
+    );
+
+    for page in [&first_page, &second_page] {
+        let info = mock_info(MOCK_OWNER_ADDR, &[]);
+        let res = execute(
+            new_deps.as_mut(),
+            mock_env(),
+            info,
+            import_msg(&page.holders, &page.lock_positions),
+        )
+        .unwrap();
+        assert_eq!(res.attributes[1].value, "1");
+    }
+
+    let info = mock_info(MOCK_OWNER_ADDR, &[]);
+    let res = execute(
+        new_deps.as_mut(),
+        mock_env(),
+        info,
+        import_msg(&first_page.holders, &[]),
+    );
+    assert_eq!(
+        res.unwrap_err(),
+        StdError::generic_err("Holder addr0000 is already imported")
+    );
+
+    let imported = export(new_deps.as_ref(), None, 10);
+    assert_eq!(imported.state, first_page.state);
+    assert_eq!(
+        imported.holders,
+        [first_page.holders.clone(), second_page.holders.clone()].concat()
+    );
+    assert_eq!(imported.lock_positions, second_page.lock_positions);
+
+    let msg = QueryMsg::AccruedRewards {
+        address: "addr0000".to_string(),
+    };
+    let res: AccruedRewardsResponse =
+        from_binary(&query(new_deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+    assert_eq!(res.rewards, Uint128::new(100u128));
+
+    // the token contract is registered once the import is complete
+    let msg = ExecuteMsg::PostInitialize {
+        token_contract: MOCK_TOKEN_CONTRACT_ADDR.to_string(),
+    };
+    let info = mock_info(MOCK_OWNER_ADDR, &[]);
+    execute(new_deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // the imported penalties can be claimed, the new positions do not reuse the lock ids
+    let info = mock_info("addr0001", &[]);
+    let msg = ExecuteMsg::ClaimPenalties { recipient: None };
+    let res = execute(new_deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(res.messages, unlock_transfer("addr0001", 50));
+    enable_locking(new_deps.as_mut());
+    let info = mock_info(MOCK_TOKEN_CONTRACT_ADDR, &[]);
+    let msg = lock_msg("addr0000", 100, 1000);
+    let res = execute(new_deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert!(res.attributes.contains(&attr("lock_id", "2")));
+
+    let info = mock_info(MOCK_OWNER_ADDR, &[]);
+    let res = execute(new_deps.as_mut(), mock_env(), info, import_msg(&[], &[]));
+    assert_eq!(
+        res.unwrap_err(),
+        StdError::generic_err("Holders can only be imported before the token contract is set")
+    );
+}
+
+#[test]
+fn export_and_import_protocol_fee() {
+    let mut deps = mock_dependencies(&[]);
+    let info = mock_info("addr0000", &[]);
+    instantiate(deps.as_mut(), mock_env(), info, default_init()).unwrap();
+    let msg = ExecuteMsg::PostInitialize {
+        token_contract: MOCK_TOKEN_CONTRACT_ADDR.to_string(),
+    };
+    let info = mock_info(MOCK_OWNER_ADDR, &[]);
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    let msg = ExecuteMsg::UpdateConfig {
+        owner: None,
+        protocol_fee_rate: Some(Decimal::percent(10)),
+        treasury: Some("treasury0000".to_string()),
+    };
+    let info = mock_info(MOCK_OWNER_ADDR, &[]);
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // 100 uusd of fee on 1000 uusd of rewards, the rest goes to addr0000
+    let info = mock_info(MOCK_TOKEN_CONTRACT_ADDR, &[]);
+    let msg = ExecuteMsg::IncreaseBalance {
+        address: "addr0000".to_string(),
+        amount: Uint128::new(100u128),
+    };
+    execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+    deps.querier
+        .update_balance(MOCK_CONTRACT_ADDR, &[Coin::new(1000u128, "uusd")]);
+    let msg = ExecuteMsg::IncreaseBalance {
+        address: "addr0001".to_string(),
+        amount: Uint128::new(100u128),
+    };
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let msg = QueryMsg::ExportState {
+        start_after: None,
+        limit: None,
+    };
+    let exported: ExportStateResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+    assert_eq!(exported.state.protocol_fee, Uint128::new(100u128));
+
+    let mut new_deps = mock_dependencies(&[Coin::new(1000u128, "uusd")]);
+    let info = mock_info("addr0000", &[]);
+    instantiate(new_deps.as_mut(), mock_env(), info, default_init()).unwrap();
+    let msg = ExecuteMsg::ImportHolders {
+        state: exported.state.clone(),
+        holders: exported.holders.clone(),
+        lock_positions: exported.lock_positions.clone(),
+    };
+    let info = mock_info(MOCK_OWNER_ADDR, &[]);
+    execute(new_deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let msg = QueryMsg::ExportState {
+        start_after: None,
+        limit: None,
+    };
+    let imported: ExportStateResponse =
+        from_binary(&query(new_deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+    assert_eq!(imported.state, exported.state);
+    assert_eq!(imported.holders, exported.holders);
+
+    // the fee stays with the treasury, the holders are owed the rest
+    let res = query(new_deps.as_ref(), mock_env(), QueryMsg::Solvency {}).unwrap();
+    let solvency: SolvencyResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        solvency,
+        SolvencyResponse {
+            reward_balance: Uint128::new(1000u128),
+            liabilities: Uint128::new(900u128),
+            protocol_fee: Uint128::new(100u128),
+            surplus: Uint128::zero(),
+            deficit: Uint128::zero(),
+            reward_dust: Decimal256::zero(),
+        }
+    );
+}
+
+#[test]
+fn migrate() {
+    let mut deps = mock_dependencies(&[]);
+    let info = mock_info("addr0000", &[]);
+    instantiate(deps.as_mut(), mock_env(), info, default_init()).unwrap();
+
+    let version = get_contract_version(&deps.storage).unwrap();
+    assert_eq!(version.contract, "crates.io:anchor_beth_reward");
+    assert_eq!(version.version, env!("CARGO_PKG_VERSION"));
+
+    // same version
+    contract_migrate(deps.as_mut(), mock_env(), MigrateMsg {}).unwrap();
+
+    // deployments that predate cw2
+    cw2::CONTRACT.remove(&mut deps.storage);
+    contract_migrate(deps.as_mut(), mock_env(), MigrateMsg {}).unwrap();
+    assert_eq!(get_contract_version(&deps.storage).unwrap(), version);
+
+    // downgrades
+    set_contract_version(&mut deps.storage, "crates.io:anchor_beth_reward", "99.0.0").unwrap();
+    let res = contract_migrate(deps.as_mut(), mock_env(), MigrateMsg {});
+    assert_eq!(
+        res.unwrap_err(),
+        StdError::generic_err(format!(
+            "Cannot downgrade from 99.0.0 to {}",
+            env!("CARGO_PKG_VERSION")
+        ))
+    );
+
+    // other contracts
+    set_contract_version(&mut deps.storage, "crates.io:other", "0.1.0").unwrap();
+    let res = contract_migrate(deps.as_mut(), mock_env(), MigrateMsg {});
+    assert_eq!(
+        res.unwrap_err(),
+        StdError::generic_err(
+            "Cannot migrate from crates.io:other to crates.io:anchor_beth_reward"
+        )
+    );
+}
+
+#[test]
+fn migrate_decimal_state() {
+    // the 1.2.0 layout, the indexes and the pending rewards in 128-bit Decimal
+    #[derive(Serialize, Deserialize)]
+    struct LegacyState {
+        global_index: Decimal,
+        total_balance: Uint128,
+        prev_reward_balance: Uint128,
+        total_locked: Uint128,
+        total_locked_weight: Uint128,
+        lock_penalty_index: Decimal,
+        protocol_fee: Uint128,
+        holder_count: u64,
+    }
+    #[derive(Serialize, Deserialize)]
+    struct LegacyHolder {
+        balance: Uint128,
+        index: Decimal,
+        pending_rewards: Decimal,
+        locked_amount: Uint128,
+        locked_weight: Uint128,
+        counted: bool,
+    }
+
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: Uint128::new(150u128),
+    }]);
+    let info = mock_info("addr0000", &[]);
+    instantiate(deps.as_mut(), mock_env(), info, default_init()).unwrap();
+    let msg = ExecuteMsg::PostInitialize {
+        token_contract: MOCK_TOKEN_CONTRACT_ADDR.to_string(),
+    };
+    let info = mock_info(MOCK_OWNER_ADDR, &[]);
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    singleton(&mut deps.storage, KEY_STATE)
+        .save(&LegacyState {
+            global_index: Decimal::from_str("1.5").unwrap(),
+            total_balance: Uint128::new(100u128),
+            prev_reward_balance: Uint128::new(150u128),
+            total_locked: Uint128::zero(),
+            total_locked_weight: Uint128::zero(),
+            lock_penalty_index: Decimal::zero(),
+            protocol_fee: Uint128::zero(),
+            holder_count: 1,
+        })
+        .unwrap();
+    let address_raw = deps.api.addr_canonicalize("addr0000").unwrap();
+    bucket(&mut deps.storage, PREFIX_HOLDERS)
+        .save(
+            address_raw.as_slice(),
+            &LegacyHolder {
+                balance: Uint128::new(100u128),
+                index: Decimal::zero(),
+                pending_rewards: Decimal::percent(50),
+                locked_amount: Uint128::zero(),
+                locked_weight: Uint128::zero(),
+                counted: true,
+            },
+        )
+        .unwrap();
+    set_contract_version(&mut deps.storage, "crates.io:anchor_beth_reward", "1.2.0").unwrap();
+
+    contract_migrate(deps.as_mut(), mock_env(), MigrateMsg {}).unwrap();
+    let state = read_state(&deps.storage).unwrap();
+    assert_eq!(state.global_index, Decimal256::from_str("1.5").unwrap());
+    // the recognized rewards are owed to the holders
+    assert_eq!(
+        state.total_liabilities,
+        Decimal256::from_str("150").unwrap()
+    );
+    assert!(state.reward_dust.is_zero());
+
+    // 100 * 1.5 + 0.5, the fraction stays pending
+    let msg = ExecuteMsg::ClaimRewards { recipient: None };
+    let info = mock_info("addr0000", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert!(res.attributes.contains(&attr("rewards", "150")));
+
+    let holder = read_holder(&deps.storage, &address_raw).unwrap();
+    assert_eq!(holder.index, Decimal256::from_str("1.5").unwrap());
+    assert_eq!(holder.pending_rewards, Decimal256::percent(50));
+}
+
+#[test]
+fn migrate_pre_cw2_state() {
+    // the layout of the deployments that predate cw2
+    #[derive(Serialize, Deserialize)]
+    struct LegacyConfig {
+        owner: CanonicalAddr,
+        token_contract: Option<CanonicalAddr>,
+        reward_denom: String,
+    }
+    #[derive(Serialize, Deserialize)]
+    struct LegacyState {
+        global_index: Decimal,
+        total_balance: Uint128,
+        prev_reward_balance: Uint128,
+    }
+    #[derive(Serialize, Deserialize)]
+    struct LegacyHolder {
+        balance: Uint128,
+        index: Decimal,
+        pending_rewards: Decimal,
+    }
+
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: Uint128::new(100u128),
+    }]);
+    let owner_raw = deps.api.addr_canonicalize(MOCK_OWNER_ADDR).unwrap();
+    let token_raw = deps
+        .api
+        .addr_canonicalize(MOCK_TOKEN_CONTRACT_ADDR)
+        .unwrap();
+    singleton(&mut deps.storage, KEY_CONFIG)
+        .save(&LegacyConfig {
+            owner: owner_raw,
+            token_contract: Some(token_raw),
+            reward_denom: DEFAULT_REWARD_DENOM.to_string(),
+        })
+        .unwrap();
+    singleton(&mut deps.storage, KEY_STATE)
+        .save(&LegacyState {
+            global_index: Decimal::one(),
+            total_balance: Uint128::new(100u128),
+            prev_reward_balance: Uint128::new(100u128),
+        })
+        .unwrap();
+    let address_raw = deps.api.addr_canonicalize("addr0000").unwrap();
+    bucket(&mut deps.storage, PREFIX_HOLDERS)
+        .save(
+            address_raw.as_slice(),
+            &LegacyHolder {
+                balance: Uint128::new(100u128),
+                index: Decimal::zero(),
+                pending_rewards: Decimal::zero(),
+            },
+        )
+        .unwrap();
+
+    // the config can not be read before the migration
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::Config {});
+    assert!(res.is_err());
+
+    contract_migrate(deps.as_mut(), mock_env(), MigrateMsg {}).unwrap();
+    let version = get_contract_version(&deps.storage).unwrap();
+    assert_eq!(version.contract, "crates.io:anchor_beth_reward");
+    assert_eq!(version.version, env!("CARGO_PKG_VERSION"));
+
+    // the protocol fee is off
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap();
+    let config: ConfigResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        config,
+        ConfigResponse {
+            owner: MOCK_OWNER_ADDR.to_string(),
+            reward_denom: DEFAULT_REWARD_DENOM.to_string(),
+            token_contract: Some(MOCK_TOKEN_CONTRACT_ADDR.to_string()),
+            protocol_fee_rate: Decimal::zero(),
+            treasury: None,
+        }
+    );
+    let state = read_state(&deps.storage).unwrap();
+    assert_eq!(state.global_index, Decimal256::one());
+    assert_eq!(
+        state.total_liabilities,
+        Decimal256::from_str("100").unwrap()
+    );
+    // the existing holders are not counted until a `PruneHolders` pass
+    assert_eq!(state.holder_count, 0);
+    assert!(!state.holders_indexed);
+
+    let msg = ExecuteMsg::ClaimRewards { recipient: None };
+    let info = mock_info("addr0000", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert!(res.attributes.contains(&attr("rewards", "100")));
+}
+
+fn lock_msg(sender: &str, amount: u128, duration: u64) -> ExecuteMsg {
+    ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: sender.to_string(),
+        amount: Uint128::new(amount),
+        msg: to_binary(&Cw20HookMsg::Lock { duration }).unwrap(),
+    })
+}
+
+fn unlock_transfer(recipient: &str, amount: u128) -> Vec<SubMsg<CustomMsg>> {
+    vec![SubMsg::new(WasmMsg::Execute {
+        contract_addr: MOCK_TOKEN_CONTRACT_ADDR.to_string(),
+        msg: to_binary(&Cw20ExecuteMsg::Transfer {
+            recipient: recipient.to_string(),
+            amount: Uint128::new(amount),
+        })
+        .unwrap(),
+        funds: vec![],
+    })]
+}
+
+fn enable_locking(deps: DepsMut) {
+    let msg = ExecuteMsg::UpdateLockConfig {
+        max_lock_duration: Some(1000),
+        max_boost: Some(Decimal::one()),
+        early_unlock_penalty: Some(Decimal::percent(50)),
+    };
+    let info = mock_info(MOCK_OWNER_ADDR, &[]);
+    execute(deps, mock_env(), info, msg).unwrap();
+}
+
+#[test]
+fn update_lock_config() {
+    let mut deps = mock_dependencies(&[]);
+    let info = mock_info("addr0000", &[]);
+    instantiate(deps.as_mut(), mock_env(), info, default_init()).unwrap();
+
+    // locking is disabled by default
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::LockConfig {}).unwrap();
+    let lock_config: LockConfigResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        lock_config,
+        LockConfigResponse {
+            max_lock_duration: 0,
+            max_boost: Decimal::zero(),
+            early_unlock_penalty: Decimal::zero(),
+        }
+    );
+
+    let msg = ExecuteMsg::UpdateLockConfig {
+        max_lock_duration: Some(1000),
+        max_boost: None,
+        early_unlock_penalty: None,
+    };
+    let info = mock_info("addr0000", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg);
+    assert_eq!(res.unwrap_err(), StdError::generic_err("unauthorized"));
+
+    let msg = ExecuteMsg::UpdateLockConfig {
+        max_lock_duration: None,
+        max_boost: None,
+        early_unlock_penalty: Some(Decimal::percent(101)),
+    };
+    let info = mock_info(MOCK_OWNER_ADDR, &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg);
+    assert_eq!(
+        res.unwrap_err(),
+        StdError::generic_err("Early unlock penalty cannot be greater than 1")
+    );
+
+    enable_locking(deps.as_mut());
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::LockConfig {}).unwrap();
+    let lock_config: LockConfigResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        lock_config,
+        LockConfigResponse {
+            max_lock_duration: 1000,
+            max_boost: Decimal::one(),
+            early_unlock_penalty: Decimal::percent(50),
+        }
+    );
+}
+
+#[test]
+fn lock_boosts_rewards() {
+    let mut deps = mock_dependencies(&[]);
+    let info = mock_info("addr0000", &[]);
+    instantiate(deps.as_mut(), mock_env(), info, default_init()).unwrap();
+
+    let msg = ExecuteMsg::PostInitialize {
+        token_contract: MOCK_TOKEN_CONTRACT_ADDR.to_string(),
+    };
+    let info = mock_info(MOCK_OWNER_ADDR, &[]);
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    for address in ["addr0000", "addr0001"] {
+        let msg = ExecuteMsg::IncreaseBalance {
+            address: address.to_string(),
+            amount: Uint128::new(100u128),
+        };
+        let info = mock_info(MOCK_TOKEN_CONTRACT_ADDR, &[]);
+        execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    }
+
+    let info = mock_info(MOCK_TOKEN_CONTRACT_ADDR, &[]);
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        lock_msg("addr0000", 100, 1000),
+    );
+    assert_eq!(
+        res.unwrap_err(),
+        StdError::generic_err("Locking is disabled")
+    );
+
+    enable_locking(deps.as_mut());
+
+    let info = mock_info("addr0000", &[]);
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        lock_msg("addr0000", 100, 1000),
+    );
+    assert_eq!(res.unwrap_err(), StdError::generic_err("unauthorized"));
+
+    let info = mock_info(MOCK_TOKEN_CONTRACT_ADDR, &[]);
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        lock_msg("addr0000", 100, 1001),
+    );
+    assert_eq!(
+        res.unwrap_err(),
+        StdError::generic_err("Lock duration must be between 1 and 1000 seconds")
+    );
+
+    // Send moves the balance to the contract, which is not a holder itself
+    let msg = ExecuteMsg::DecreaseBalance {
+        address: "addr0000".to_string(),
+        amount: Uint128::new(100u128),
+    };
+    let info = mock_info(MOCK_TOKEN_CONTRACT_ADDR, &[]);
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    let msg = ExecuteMsg::IncreaseBalance {
+        address: MOCK_CONTRACT_ADDR.to_string(),
+        amount: Uint128::new(100u128),
+    };
+    let info = mock_info(MOCK_TOKEN_CONTRACT_ADDR, &[]);
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let info = mock_info(MOCK_TOKEN_CONTRACT_ADDR, &[]);
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        lock_msg("addr0000", 100, 1000),
+    )
+    .unwrap();
+    assert!(res.attributes.contains(&attr("lock_id", "1")));
+    assert!(res.attributes.contains(&attr("weight", "200")));
+
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::State {}).unwrap();
+    let state_response: StateResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        state_response,
+        StateResponse {
+            global_index: Decimal256::zero(),
+            total_balance: Uint128::new(100u128),
+            prev_reward_balance: Uint128::zero(),
+            total_locked: Uint128::new(100u128),
+            total_locked_weight: Uint128::new(200u128),
+            total_effective_balance: Uint128::new(300u128),
+            lock_penalty_index: Decimal256::zero(),
+            protocol_fee: Uint128::zero(),
+            holder_count: 2,
+            holders_indexed: true,
+        }
+    );
+
+    let res = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::Weight {
+            address: "addr0000".to_string(),
+        },
+    )
+    .unwrap();
+    let weight: WeightResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        weight,
+        WeightResponse {
+            address: "addr0000".to_string(),
+            balance: Uint128::zero(),
+            locked_amount: Uint128::new(100u128),
+            effective_balance: Uint128::new(200u128),
+        }
+    );
+
+    // rewards are split by effective balance
+    deps.querier
+        .update_balance(MOCK_CONTRACT_ADDR, &[Coin::new(300u128, "uusd")]);
+    for (address, rewards) in [("addr0000", 200u128), ("addr0001", 100u128)] {
+        let res = query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::AccruedRewards {
+                address: address.to_string(),
+            },
+        )
+        .unwrap();
+        let accrued: AccruedRewardsResponse = from_binary(&res).unwrap();
+        assert_eq!(accrued.rewards, Uint128::new(rewards));
+    }
+
+    let start = mock_env().block.time.seconds();
+    let res = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::LockPositions {
+            address: "addr0000".to_string(),
+            start_after: None,
+            limit: None,
+        },
+    )
+    .unwrap();
+    let positions: LockPositionsResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        positions.positions,
+        vec![LockPositionResponse {
+            lock_id: 1,
+            amount: Uint128::new(100u128),
+            weight: Uint128::new(200u128),
+            start,
+            end: start + 1000,
+        }]
+    );
+
+    let mut env = mock_env();
+    env.block.time = env.block.time.plus_seconds(1000);
+    let info = mock_info("addr0000", &[]);
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        info,
+        ExecuteMsg::Unlock { lock_id: 1 },
+    )
+    .unwrap();
+    assert_eq!(res.messages, unlock_transfer("addr0000", 100));
+
+    let info = mock_info("addr0000", &[]);
+    let res = execute(deps.as_mut(), env, info, ExecuteMsg::Unlock { lock_id: 1 });
+    assert_eq!(
+        res.unwrap_err(),
+        StdError::generic_err("Lock 1 does not exist")
+    );
+
+    // the rewards accrued while locked are kept
+    let res = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::AccruedRewards {
+            address: "addr0000".to_string(),
+        },
+    )
+    .unwrap();
+    let accrued: AccruedRewardsResponse = from_binary(&res).unwrap();
+    assert_eq!(accrued.rewards, Uint128::new(200u128));
+}
+
+#[test]
+fn early_unlock_penalty() {
+    let mut deps = mock_dependencies(&[]);
+    let info = mock_info("addr0000", &[]);
+    instantiate(deps.as_mut(), mock_env(), info, default_init()).unwrap();
+
+    let msg = ExecuteMsg::PostInitialize {
+        token_contract: MOCK_TOKEN_CONTRACT_ADDR.to_string(),
+    };
+    let info = mock_info(MOCK_OWNER_ADDR, &[]);
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    enable_locking(deps.as_mut());
+
+    let info = mock_info(MOCK_TOKEN_CONTRACT_ADDR, &[]);
+    let msg = ExecuteMsg::IncreaseBalance {
+        address: "addr0002".to_string(),
+        amount: Uint128::new(100u128),
+    };
+    execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        info.clone(),
+        lock_msg("addr0000", 100, 1000),
+    )
+    .unwrap();
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        lock_msg("addr0001", 100, 500),
+    )
+    .unwrap();
+    assert!(res.attributes.contains(&attr("lock_id", "2")));
+    assert!(res.attributes.contains(&attr("weight", "150")));
+
+    // half of the early unlock goes to the reward weight of the other holders
+    let mut env = mock_env();
+    env.block.time = env.block.time.plus_seconds(100);
+    let info = mock_info("addr0001", &[]);
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        info,
+        ExecuteMsg::Unlock { lock_id: 2 },
+    )
+    .unwrap();
+    assert_eq!(res.messages, unlock_transfer("addr0001", 50));
+    assert!(res.attributes.contains(&attr("penalty", "50")));
+
+    let accrued_penalties = |deps: Deps, address: &str| {
+        let msg = QueryMsg::Holder {
+            address: address.to_string(),
+        };
+        let holder: HolderResponse = from_binary(&query(deps, mock_env(), msg).unwrap()).unwrap();
+        holder.accrued_penalties
+    };
+    assert_eq!(
+        accrued_penalties(deps.as_ref(), "addr0000"),
+        Uint128::new(33u128)
+    );
+    assert_eq!(
+        accrued_penalties(deps.as_ref(), "addr0001"),
+        Uint128::zero()
+    );
+    assert_eq!(
+        accrued_penalties(deps.as_ref(), "addr0002"),
+        Uint128::new(16u128)
+    );
+
+    let claim_penalties = |deps: DepsMut, sender: &str| {
+        let msg = ExecuteMsg::ClaimPenalties { recipient: None };
+        execute(deps, mock_env(), mock_info(sender, &[]), msg)
+    };
+    assert_eq!(
+        claim_penalties(deps.as_mut(), "addr0001").unwrap_err(),
+        StdError::generic_err("No penalties have accrued yet")
+    );
+    let res = claim_penalties(deps.as_mut(), "addr0000").unwrap();
+    assert_eq!(res.messages, unlock_transfer("addr0000", 33));
+    assert!(res.attributes.contains(&attr("penalties", "33")));
+
+    // the penalties owed to a holder outlive its balance
+    let info = mock_info(MOCK_TOKEN_CONTRACT_ADDR, &[]);
+    let msg = ExecuteMsg::DecreaseBalance {
+        address: "addr0002".to_string(),
+        amount: Uint128::new(100u128),
+    };
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // the last reward weight has nobody to pay a penalty to
+    let info = mock_info("addr0000", &[]);
+    let res = execute(deps.as_mut(), env, info, ExecuteMsg::Unlock { lock_id: 1 }).unwrap();
+    assert_eq!(res.messages, unlock_transfer("addr0000", 100));
+    assert!(res.attributes.contains(&attr("penalty", "0")));
+
+    let res = claim_penalties(deps.as_mut(), "addr0002").unwrap();
+    assert_eq!(res.messages, unlock_transfer("addr0002", 16));
+
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::State {}).unwrap();
+    let state_response: StateResponse = from_binary(&res).unwrap();
+    assert_eq!(state_response.total_locked, Uint128::zero());
+    assert_eq!(state_response.total_effective_balance, Uint128::zero());
+}
+
+#[test]
+fn early_unlock_penalty_skips_the_unlocker() {
+    let mut deps = mock_dependencies(&[]);
+    let info = mock_info("addr0000", &[]);
+    instantiate(deps.as_mut(), mock_env(), info, default_init()).unwrap();
+
+    let msg = ExecuteMsg::PostInitialize {
+        token_contract: MOCK_TOKEN_CONTRACT_ADDR.to_string(),
+    };
+    let info = mock_info(MOCK_OWNER_ADDR, &[]);
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    enable_locking(deps.as_mut());
+
+    // the unlocker holds liquid bETH next to its lock
+    let info = mock_info(MOCK_TOKEN_CONTRACT_ADDR, &[]);
+    let msg = ExecuteMsg::IncreaseBalance {
+        address: "addr0001".to_string(),
+        amount: Uint128::new(100u128),
+    };
+    execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        info.clone(),
+        lock_msg("addr0000", 100, 1000),
+    )
+    .unwrap();
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        lock_msg("addr0001", 100, 500),
+    )
+    .unwrap();
+
+    let mut env = mock_env();
+    env.block.time = env.block.time.plus_seconds(100);
+    let info = mock_info("addr0001", &[]);
+    let res = execute(deps.as_mut(), env, info, ExecuteMsg::Unlock { lock_id: 2 }).unwrap();
+    assert!(res.attributes.contains(&attr("penalty", "50")));
+
+    // all of the penalty goes to the weight of addr0000, 50 / 200
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::State {}).unwrap();
+    let state_response: StateResponse = from_binary(&res).unwrap();
+    assert_eq!(state_response.lock_penalty_index, Decimal256::percent(25));
+
+    let holder = |deps: Deps, address: &str| -> HolderResponse {
+        let msg = QueryMsg::Holder {
+            address: address.to_string(),
+        };
+        from_binary(&query(deps, mock_env(), msg).unwrap()).unwrap()
+    };
+    assert_eq!(
+        holder(deps.as_ref(), "addr0000").accrued_penalties,
+        Uint128::new(50u128)
+    );
+    let unlocker = holder(deps.as_ref(), "addr0001");
+    assert_eq!(unlocker.accrued_penalties, Uint128::zero());
+    assert_eq!(unlocker.penalty_index, Decimal256::percent(25));
+    assert_eq!(unlocker.balance, Uint128::new(100u128));
+}
+
+#[test]
+fn lock_position_cap() {
+    let mut deps = mock_dependencies(&[]);
+    let info = mock_info("addr0000", &[]);
+    instantiate(deps.as_mut(), mock_env(), info, default_init()).unwrap();
+
+    let msg = ExecuteMsg::PostInitialize {
+        token_contract: MOCK_TOKEN_CONTRACT_ADDR.to_string(),
+    };
+    let info = mock_info(MOCK_OWNER_ADDR, &[]);
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    enable_locking(deps.as_mut());
+
+    let info = mock_info(MOCK_TOKEN_CONTRACT_ADDR, &[]);
+    for _ in 0..30 {
+        let msg = lock_msg("addr0000", 10, 1000);
+        execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+    }
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        info.clone(),
+        lock_msg("addr0000", 10, 1000),
+    );
+    assert_eq!(
+        res.unwrap_err(),
+        StdError::generic_err("Cannot hold more than 30 lock positions")
+    );
+
+    // other holders are not limited by it
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        lock_msg("addr0001", 10, 1000),
+    )
+    .unwrap();
+}
+
+#[test]
+fn update_config() {
+    let mut deps = mock_dependencies(&[]);
+    let info = mock_info("addr0000", &[]);
+    instantiate(deps.as_mut(), mock_env(), info, default_init()).unwrap();
+
+    let msg = ExecuteMsg::UpdateConfig {
+        owner: Some("owner0001".to_string()),
+        protocol_fee_rate: None,
+        treasury: None,
+    };
+    let info = mock_info("addr0000", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg);
+    assert_eq!(res.unwrap_err(), StdError::generic_err("unauthorized"));
+
+    let msg = ExecuteMsg::UpdateConfig {
+        owner: None,
+        protocol_fee_rate: Some(Decimal::percent(101)),
+        treasury: None,
+    };
+    let info = mock_info(MOCK_OWNER_ADDR, &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg);
+    assert_eq!(
+        res.unwrap_err(),
+        StdError::generic_err("Protocol fee rate cannot be greater than 1")
+    );
+
+    let msg = ExecuteMsg::UpdateConfig {
+        owner: Some("owner0001".to_string()),
+        protocol_fee_rate: Some(Decimal::percent(10)),
+        treasury: Some("treasury0000".to_string()),
+    };
+    let info = mock_info(MOCK_OWNER_ADDR, &[]);
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap();
+    let config_response: ConfigResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        config_response,
+        ConfigResponse {
+            owner: "owner0001".to_string(),
+            token_contract: None,
+            reward_denom: DEFAULT_REWARD_DENOM.to_string(),
+            protocol_fee_rate: Decimal::percent(10),
+            treasury: Some("treasury0000".to_string()),
+        }
+    );
+}
+
+#[test]
+fn withdraw_protocol_fee() {
+    let mut deps = mock_dependencies(&[]);
+    let info = mock_info("addr0000", &[]);
+    instantiate(deps.as_mut(), mock_env(), info, default_init()).unwrap();
+
+    let msg = ExecuteMsg::PostInitialize {
+        token_contract: MOCK_TOKEN_CONTRACT_ADDR.to_string(),
+    };
+    let info = mock_info(MOCK_OWNER_ADDR, &[]);
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let msg = ExecuteMsg::IncreaseBalance {
+        address: "addr0000".to_string(),
+        amount: Uint128::new(100u128),
+    };
+    let info = mock_info(MOCK_TOKEN_CONTRACT_ADDR, &[]);
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // no treasury is set yet
+    let info = mock_info("treasury0000", &[]);
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        ExecuteMsg::WithdrawProtocolFee {},
+    );
+    assert_eq!(res.unwrap_err(), StdError::generic_err("unauthorized"));
+
+    // the rewards received before the update are distributed without fee
+    deps.querier
+        .update_balance(MOCK_CONTRACT_ADDR, &[Coin::new(1000u128, "uusd")]);
+    let msg = ExecuteMsg::UpdateConfig {
+        owner: None,
+        protocol_fee_rate: Some(Decimal::percent(10)),
+        treasury: Some("treasury0000".to_string()),
+    };
+    let info = mock_info(MOCK_OWNER_ADDR, &[]);
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    deps.querier
+        .update_balance(MOCK_CONTRACT_ADDR, &[Coin::new(2000u128, "uusd")]);
+    let res = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::AccruedRewards {
+            address: "addr0000".to_string(),
+        },
+    )
+    .unwrap();
+    let accrued: AccruedRewardsResponse = from_binary(&res).unwrap();
+    assert_eq!(accrued.rewards, Uint128::new(1900u128));
+
+    let info = mock_info("addr0000", &[]);
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        ExecuteMsg::WithdrawProtocolFee {},
+    );
+    assert_eq!(res.unwrap_err(), StdError::generic_err("unauthorized"));
+
+    let info = mock_info("treasury0000", &[]);
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        ExecuteMsg::WithdrawProtocolFee {},
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+            to_address: "treasury0000".to_string(),
+            amount: vec![Coin {
+                denom: "uusd".to_string(),
+                // 1% tax on Terra
+                amount: Uint128::from(if cfg!(feature = "terra") {
+                    99u128
+                } else {
+                    100u128
+                }),
+            }]
+        }))]
+    );
+
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::State {}).unwrap();
+    let state_response: StateResponse = from_binary(&res).unwrap();
+    assert_eq!(state_response.protocol_fee, Uint128::zero());
+    assert_eq!(state_response.prev_reward_balance, Uint128::new(1900u128));
+
+    deps.querier
+        .update_balance(MOCK_CONTRACT_ADDR, &[Coin::new(1900u128, "uusd")]);
+    let info = mock_info("treasury0000", &[]);
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        ExecuteMsg::WithdrawProtocolFee {},
+    );
+    assert_eq!(
+        res.unwrap_err(),
+        StdError::generic_err("No protocol fee to withdraw")
+    );
+}
+
+#[test]
+fn prune_holders() {
+    let mut deps = mock_dependencies(&[]);
+    let info = mock_info("addr0000", &[]);
+    instantiate(deps.as_mut(), mock_env(), info, default_init()).unwrap();
+
+    let msg = ExecuteMsg::PostInitialize {
+        token_contract: MOCK_TOKEN_CONTRACT_ADDR.to_string(),
+    };
+    let info = mock_info(MOCK_OWNER_ADDR, &[]);
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // holders stored before the count, one of them with only dust left
+    let legacy = [
+        ("addr0000", 100u128, Decimal256::zero()),
+        ("addr0001", 0u128, Decimal256::percent(50)),
+        ("addr0002", 0u128, Decimal256::from_str("2").unwrap()),
+    ];
+    let mut state = read_state(&deps.storage).unwrap();
+    for (address, balance, pending_rewards) in legacy {
+        let address_raw = deps.api.addr_canonicalize(address).unwrap();
+        bucket(&mut deps.storage, PREFIX_HOLDERS)
+            .save(
+                address_raw.as_slice(),
+                &Holder {
+                    balance: Uint128::new(balance),
+                    index: Decimal256::zero(),
+                    pending_rewards,
+                    locked_amount: Uint128::zero(),
+                    locked_weight: Uint128::zero(),
+                    counted: false,
+                    penalty_index: Decimal256::zero(),
+                    pending_penalties: Decimal256::zero(),
+                },
+            )
+            .unwrap();
+        state.total_balance += Uint128::new(balance);
+        state.total_liabilities += pending_rewards;
+    }
+    state.holders_indexed = false;
+    store_state(&mut deps.storage, &state).unwrap();
+
+    let msg = ExecuteMsg::PruneHolders {
+        start_after: None,
+        limit: None,
+    };
+    let info = mock_info("addr0000", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg);
+    assert_eq!(res.unwrap_err(), StdError::generic_err("unauthorized"));
+
+    // a page that does not continue the pass from the first holder leaves the count incomplete
+    let msg = ExecuteMsg::PruneHolders {
+        start_after: Some("addr0001".to_string()),
+        limit: None,
+    };
+    let info = mock_info(MOCK_OWNER_ADDR, &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert!(res.attributes.contains(&attr("holders_indexed", "false")));
+    let res = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::TopHolders { limit: None },
+    );
+    assert_eq!(
+        res.unwrap_err(),
+        StdError::generic_err(
+            "The balance index is incomplete until PruneHolders goes over all the holders"
+        )
+    );
+
+    // go over the holders two by two
+    let mut start_after = None;
+    let mut pruned = 0u64;
+    loop {
+        let msg = ExecuteMsg::PruneHolders {
+            start_after: start_after.clone(),
+            limit: Some(2),
+        };
+        let info = mock_info(MOCK_OWNER_ADDR, &[]);
+        let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+        let value = |key: &str| {
+            res.attributes
+                .iter()
+                .find(|attr| attr.key == key)
+                .map(|attr| attr.value.clone())
+        };
+        pruned += value("pruned").unwrap().parse::<u64>().unwrap();
+        start_after = value("last_holder");
+        if start_after.is_none() {
+            break;
+        }
+    }
+    assert_eq!(pruned, 1);
+
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::State {}).unwrap();
+    let state_response: StateResponse = from_binary(&res).unwrap();
+    assert_eq!(state_response.holder_count, 2);
+    assert!(state_response.holders_indexed);
+    query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::TopHolders { limit: None },
+    )
+    .unwrap();
+    // the dust of the pruned holder is no longer owed
+    let state = read_state(&deps.storage).unwrap();
+    assert_eq!(state.total_liabilities, Decimal256::from_str("2").unwrap());
+    assert_eq!(state.reward_dust, Decimal256::percent(50));
+
+    let res = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::Holders {
+            start_after: None,
+            start_before: None,
+            limit: None,
+            order: None,
+        },
+    )
+    .unwrap();
+    let holders: HoldersResponse = from_binary(&res).unwrap();
+    let mut addresses: Vec<String> = holders.holders.into_iter().map(|h| h.address).collect();
+    addresses.sort();
+    assert_eq!(addresses, vec!["addr0000", "addr0002"]);
+
+    // a holder leaving with nothing to claim is removed right away
+    let msg = ExecuteMsg::DecreaseBalance {
+        address: "addr0000".to_string(),
+        amount: Uint128::new(100u128),
+    };
+    let info = mock_info(MOCK_TOKEN_CONTRACT_ADDR, &[]);
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let address_raw = deps.api.addr_canonicalize("addr0000").unwrap();
+    assert!(!is_holder(&deps.storage, &address_raw).unwrap());
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::State {}).unwrap();
+    let state_response: StateResponse = from_binary(&res).unwrap();
+    assert_eq!(state_response.holder_count, 1);
+}
+
+#[test]
+fn holders_order_and_lookups() {
+    let mut deps = mock_dependencies(&[]);
+    let info = mock_info("addr0000", &[]);
+    instantiate(deps.as_mut(), mock_env(), info, default_init()).unwrap();
+
+    let msg = ExecuteMsg::PostInitialize {
+        token_contract: MOCK_TOKEN_CONTRACT_ADDR.to_string(),
+    };
+    let info = mock_info(MOCK_OWNER_ADDR, &[]);
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    for (address, amount) in [("addr0000", 100u128), ("addr0001", 300), ("addr0002", 200)] {
+        let msg = ExecuteMsg::IncreaseBalance {
+            address: address.to_string(),
+            amount: Uint128::new(amount),
+        };
+        let info = mock_info(MOCK_TOKEN_CONTRACT_ADDR, &[]);
+        execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    }
+
+    let holders = |deps: Deps, msg: QueryMsg| -> Vec<(String, u128)> {
+        let res: HoldersResponse = from_binary(&query(deps, mock_env(), msg).unwrap()).unwrap();
+        res.holders
+            .into_iter()
+            .map(|holder| (holder.address, holder.balance.u128()))
+            .collect()
+    };
+
+    let ascending = holders(
+        deps.as_ref(),
+        QueryMsg::Holders {
+            start_after: None,
+            start_before: None,
+            limit: None,
+            order: None,
+        },
+    );
+    let mut descending = holders(
+        deps.as_ref(),
+        QueryMsg::Holders {
+            start_after: None,
+            start_before: None,
+            limit: None,
+            order: Some(OrderBy::Desc),
+        },
+    );
+    descending.reverse();
+    assert_eq!(ascending, descending);
+
+    // a descending page continues before its last holder
+    let first_page = holders(
+        deps.as_ref(),
+        QueryMsg::Holders {
+            start_after: None,
+            start_before: None,
+            limit: Some(1),
+            order: Some(OrderBy::Desc),
+        },
+    );
+    assert_eq!(first_page, vec![ascending[2].clone()]);
+    let second_page = holders(
+        deps.as_ref(),
+        QueryMsg::Holders {
+            start_after: None,
+            start_before: Some(first_page[0].0.clone()),
+            limit: None,
+            order: Some(OrderBy::Desc),
+        },
+    );
+    assert_eq!(
+        second_page,
+        vec![ascending[1].clone(), ascending[0].clone()]
+    );
+
+    let by_addresses = holders(
+        deps.as_ref(),
+        QueryMsg::HoldersByAddresses {
+            addresses: vec!["addr0002".to_string(), "addr0009".to_string()],
+        },
+    );
+    assert_eq!(
+        by_addresses,
+        vec![("addr0002".to_string(), 200), ("addr0009".to_string(), 0)]
+    );
+    let res = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::HoldersByAddresses {
+            addresses: vec!["addr0000".to_string(); 31],
+        },
+    );
+    assert_eq!(
+        res.unwrap_err(),
+        StdError::generic_err("Cannot query more than 30 holders at once")
+    );
+
+    let top_holders = holders(deps.as_ref(), QueryMsg::TopHolders { limit: Some(2) });
+    assert_eq!(
+        top_holders,
+        vec![("addr0001".to_string(), 300), ("addr0002".to_string(), 200)]
+    );
+
+    // the balance index follows the balance changes
+    let msg = ExecuteMsg::DecreaseBalance {
+        address: "addr0001".to_string(),
+        amount: Uint128::new(250u128),
+    };
+    let info = mock_info(MOCK_TOKEN_CONTRACT_ADDR, &[]);
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    let top_holders = holders(deps.as_ref(), QueryMsg::TopHolders { limit: None });
+    assert_eq!(
+        top_holders,
+        vec![
+            ("addr0002".to_string(), 200),
+            ("addr0000".to_string(), 100),
+            ("addr0001".to_string(), 50)
+        ]
+    );
+
+    // the accrued rewards include the rewards not distributed yet
+    deps.querier
+        .update_balance(MOCK_CONTRACT_ADDR, &[Coin::new(350u128, "uusd")]);
+    let res = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::TopHolders { limit: None },
+    )
+    .unwrap();
+    let top_holders: HoldersResponse = from_binary(&res).unwrap();
+    let accrued: Vec<u128> = top_holders
+        .holders
+        .iter()
+        .map(|holder| holder.accrued_rewards.u128())
+        .collect();
+    assert_eq!(accrued, vec![200, 100, 50]);
+}
+
+/// 100 uusd over 100 bETH 300 seconds after instantiation, then 200 uusd over 200 bETH
+/// 600 seconds later, before the balance drops to 150
+fn distribute_twice(deps: &mut OwnedDeps<MockStorage, MockApi, WasmMockQuerier>) {
+    let info = mock_info("addr0000", &[]);
+    instantiate(deps.as_mut(), mock_env(), info, default_init()).unwrap();
+
+    let msg = ExecuteMsg::PostInitialize {
+        token_contract: MOCK_TOKEN_CONTRACT_ADDR.to_string(),
+    };
+    let info = mock_info(MOCK_OWNER_ADDR, &[]);
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let msg = ExecuteMsg::IncreaseBalance {
+        address: "addr0000".to_string(),
+        amount: Uint128::new(100u128),
+    };
+    let info = mock_info(MOCK_TOKEN_CONTRACT_ADDR, &[]);
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // 100 uusd over 100 bETH
+    deps.querier
+        .update_balance(MOCK_CONTRACT_ADDR, &[Coin::new(100u128, "uusd")]);
+    let mut env = mock_env();
+    env.block.height = 12_400;
+    env.block.time = env.block.time.plus_seconds(300);
+    let msg = ExecuteMsg::IncreaseBalance {
+        address: "addr0001".to_string(),
+        amount: Uint128::new(100u128),
+    };
+    let info = mock_info(MOCK_TOKEN_CONTRACT_ADDR, &[]);
+    execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+    // 200 uusd over 200 bETH
+    deps.querier
+        .update_balance(MOCK_CONTRACT_ADDR, &[Coin::new(300u128, "uusd")]);
+    env.block.height = 12_500;
+    env.block.time = env.block.time.plus_seconds(600);
+    let msg = ExecuteMsg::DecreaseBalance {
+        address: "addr0001".to_string(),
+        amount: Uint128::new(50u128),
+    };
+    let info = mock_info(MOCK_TOKEN_CONTRACT_ADDR, &[]);
+    execute(deps.as_mut(), env, info, msg).unwrap();
+}
+
+#[test]
+fn reward_history() {
+    let mut deps = mock_dependencies(&[]);
+    distribute_twice(&mut deps);
+
+    let res = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::RewardHistory {
+            start_after: None,
+            limit: None,
+        },
+    )
+    .unwrap();
+    let history: RewardHistoryResponse = from_binary(&res).unwrap();
+    let start = mock_env().block.time.seconds();
+    assert_eq!(
+        history.checkpoints,
+        vec![
+            RewardCheckpointResponse {
+                id: 1,
+                height: 12_345,
+                time: start,
+                amount: Uint128::zero(),
+                protocol_fee: Uint128::zero(),
+                total_balance: Uint128::zero(),
+                global_index: Decimal256::zero(),
+            },
+            RewardCheckpointResponse {
+                id: 2,
+                height: 12_400,
+                time: start + 300,
+                amount: Uint128::new(100u128),
+                protocol_fee: Uint128::zero(),
+                total_balance: Uint128::new(100u128),
+                global_index: Decimal256::one(),
+            },
+            RewardCheckpointResponse {
+                id: 3,
+                height: 12_500,
+                time: start + 900,
+                amount: Uint128::new(200u128),
+                protocol_fee: Uint128::zero(),
+                total_balance: Uint128::new(200u128),
+                global_index: Decimal256::from_str("2").unwrap(),
+            },
+        ]
+    );
+
+    let res = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::RewardHistory {
+            start_after: Some(1),
+            limit: Some(1),
+        },
+    )
+    .unwrap();
+    let page: RewardHistoryResponse = from_binary(&res).unwrap();
+    assert_eq!(page.checkpoints, history.checkpoints[1..2].to_vec());
+
+    let global_index_at = |height: u64| {
+        query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::GlobalIndexAt { height },
+        )
+        .and_then(|res| from_binary::<GlobalIndexAtResponse>(&res))
+        .map(|res| res.global_index)
+    };
+    assert_eq!(
+        global_index_at(12_344).unwrap_err(),
+        StdError::generic_err("No global index recorded at or before height 12344")
+    );
+    assert_eq!(global_index_at(12_345).unwrap(), Decimal256::zero());
+    assert_eq!(global_index_at(12_450).unwrap(), Decimal256::one());
+    assert_eq!(
+        global_index_at(u64::MAX).unwrap(),
+        Decimal256::from_str("2").unwrap()
+    );
+}
+
+#[test]
+fn reward_rate() {
+    let mut deps = mock_dependencies(&[]);
+    distribute_twice(&mut deps);
+
+    let start = mock_env().block.time;
+    let reward_rate = |seconds: u64, window_seconds: u64| {
+        let mut env = mock_env();
+        env.block.time = start.plus_seconds(seconds);
+        query(deps.as_ref(), env, QueryMsg::RewardRate { window_seconds })
+            .and_then(|res| from_binary::<RewardRateResponse>(&res))
+    };
+
+    assert_eq!(
+        reward_rate(900, 0).unwrap_err(),
+        StdError::generic_err("Window must be greater than 0")
+    );
+
+    // 100 bETH for 300 seconds then 200 bETH for 600 seconds, 300 uusd over 150000 bETH seconds
+    assert_eq!(
+        reward_rate(900, 900).unwrap(),
+        RewardRateResponse {
+            start_time: start.seconds(),
+            end_time: start.seconds() + 900,
+            rewards: Uint128::new(300u128),
+            average_total_balance: Uint128::new(166u128),
+            annualized_rate: Decimal256::from_str("63072").unwrap(),
+            complete: true,
+        }
+    );
+    // the history starts at instantiation
+    assert_eq!(
+        reward_rate(900, 2000).unwrap(),
+        RewardRateResponse {
+            complete: false,
+            ..reward_rate(900, 900).unwrap()
+        }
+    );
+
+    assert_eq!(
+        reward_rate(900, 600).unwrap(),
+        RewardRateResponse {
+            start_time: start.seconds() + 300,
+            end_time: start.seconds() + 900,
+            rewards: Uint128::new(200u128),
+            average_total_balance: Uint128::new(200u128),
+            annualized_rate: Decimal256::from_str("52560").unwrap(),
+            complete: true,
+        }
+    );
+
+    // no rewards since the balance dropped to 150
+    assert_eq!(
+        reward_rate(1200, 300).unwrap(),
+        RewardRateResponse {
+            start_time: start.seconds() + 900,
+            end_time: start.seconds() + 1200,
+            rewards: Uint128::zero(),
+            average_total_balance: Uint128::new(150u128),
+            annualized_rate: Decimal256::zero(),
+            complete: true,
+        }
+    );
+}
+
+#[test]
+fn reward_rate_checkpoint_cap() {
+    let mut deps = mock_dependencies(&[]);
+    let info = mock_info("addr0000", &[]);
+    instantiate(deps.as_mut(), mock_env(), info, default_init()).unwrap();
+
+    let msg = ExecuteMsg::PostInitialize {
+        token_contract: MOCK_TOKEN_CONTRACT_ADDR.to_string(),
+    };
+    let info = mock_info(MOCK_OWNER_ADDR, &[]);
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let msg = ExecuteMsg::IncreaseBalance {
+        address: "addr0000".to_string(),
+        amount: Uint128::new(100u128),
+    };
+    let info = mock_info(MOCK_TOKEN_CONTRACT_ADDR, &[]);
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // 1 uusd every 10 seconds, one checkpoint each
+    let mut env = mock_env();
+    for i in 1..=150u128 {
+        deps.querier
+            .update_balance(MOCK_CONTRACT_ADDR, &[Coin::new(i, "uusd")]);
+        env.block.height += 1;
+        env.block.time = env.block.time.plus_seconds(10);
+        let msg = ExecuteMsg::IncreaseBalance {
+            address: "addr0001".to_string(),
+            amount: Uint128::zero(),
+        };
+        let info = mock_info(MOCK_TOKEN_CONTRACT_ADDR, &[]);
+        execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+    }
+
+    // only the last 100 checkpoints are read
+    let res = query(
+        deps.as_ref(),
+        env.clone(),
+        QueryMsg::RewardRate {
+            window_seconds: u64::MAX,
+        },
+    )
+    .unwrap();
+    let rate: RewardRateResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        rate,
+        RewardRateResponse {
+            start_time: mock_env().block.time.seconds() + 510,
+            end_time: env.block.time.seconds(),
+            rewards: Uint128::new(99u128),
+            average_total_balance: Uint128::new(100u128),
+            annualized_rate: Decimal256::from_str("31536").unwrap(),
+            complete: false,
+        }
+    );
+
+    // windows within the cap are complete
+    let res = query(
+        deps.as_ref(),
+        env.clone(),
+        QueryMsg::RewardRate {
+            window_seconds: 500,
+        },
+    )
+    .unwrap();
+    let rate: RewardRateResponse = from_binary(&res).unwrap();
+    assert_eq!(rate.rewards, Uint128::new(50u128));
+    assert!(rate.complete);
+
+    // as is a window of exactly as many checkpoints as the cap
+    let res = query(
+        deps.as_ref(),
+        env,
+        QueryMsg::RewardRate {
+            window_seconds: 1000,
+        },
+    )
+    .unwrap();
+    let rate: RewardRateResponse = from_binary(&res).unwrap();
+    assert_eq!(rate.rewards, Uint128::new(100u128));
+    assert!(rate.complete);
+}
+
+#[test]
+fn stats() {
+    let mut deps = mock_dependencies(&[]);
+    distribute_twice(&mut deps);
+
+    let mut env = mock_env();
+    env.block.time = env.block.time.plus_seconds(900);
+    let claim = |deps: DepsMut, sender: &str| {
+        execute(
+            deps,
+            env.clone(),
+            mock_info(sender, &[]),
+            ExecuteMsg::ClaimRewards { recipient: None },
+        )
+    };
+
+    // 1% tax on Terra
+    claim(deps.as_mut(), "addr0000").unwrap();
+    deps.querier
+        .update_balance(MOCK_CONTRACT_ADDR, &[Coin::new(100u128, "uusd")]);
+    claim(deps.as_mut(), "addr0001").unwrap();
+    deps.querier.update_balance(MOCK_CONTRACT_ADDR, &[]);
+    assert_eq!(
+        claim(deps.as_mut(), "addr0000").unwrap_err(),
+        StdError::generic_err("No rewards have accrued yet")
+    );
+
+    // 150 uusd over 150 bETH, claimed again by addr0000
+    deps.querier
+        .update_balance(MOCK_CONTRACT_ADDR, &[Coin::new(150u128, "uusd")]);
+    claim(deps.as_mut(), "addr0000").unwrap();
+
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::Stats {}).unwrap();
+    let stats: StatsResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        stats,
+        StatsResponse {
+            total_rewards: Uint128::new(450u128),
+            total_claimed: Uint128::new(400u128),
+            total_claimed_net: Uint128::new(if cfg!(feature = "terra") {
+                396u128
+            } else {
+                400u128
+            }),
+            total_tax: Uint128::new(if cfg!(feature = "terra") {
+                4u128
+            } else {
+                0u128
+            }),
+            claims: 3,
+            claimants: 2,
+        }
+    );
+
+    let res = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::Holder {
+            address: "addr0000".to_string(),
+        },
+    )
+    .unwrap();
+    let holder: HolderResponse = from_binary(&res).unwrap();
+    assert_eq!(holder.total_claimed, Uint128::new(300u128));
+    assert_eq!(holder.last_claim_time, Some(env.block.time.seconds()));
+}
+
+#[test]
+fn claim_stats_outlive_pruning() {
+    let mut deps = mock_dependencies(&[]);
+    distribute_twice(&mut deps);
+
+    // addr0001 claims its 100 uusd and sends all its bETH away
+    let mut env = mock_env();
+    env.block.time = env.block.time.plus_seconds(900);
+    let info = mock_info("addr0001", &[]);
+    let msg = ExecuteMsg::ClaimRewards { recipient: None };
+    execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+    deps.querier
+        .update_balance(MOCK_CONTRACT_ADDR, &[Coin::new(200u128, "uusd")]);
+    let info = mock_info(MOCK_TOKEN_CONTRACT_ADDR, &[]);
+    let msg = ExecuteMsg::DecreaseBalance {
+        address: "addr0001".to_string(),
+        amount: Uint128::new(50u128),
+    };
+    execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+    let msg = ExecuteMsg::PruneHolders {
+        start_after: None,
+        limit: None,
+    };
+    let info = mock_info(MOCK_OWNER_ADDR, &[]);
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    let addr0001 = deps.api.addr_canonicalize("addr0001").unwrap();
+    assert!(!is_holder(&deps.storage, &addr0001).unwrap());
+
+    let res = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::Holder {
+            address: "addr0001".to_string(),
+        },
+    )
+    .unwrap();
+    let holder: HolderResponse = from_binary(&res).unwrap();
+    assert_eq!(holder.balance, Uint128::zero());
+    assert_eq!(holder.total_claimed, Uint128::new(100u128));
+    assert_eq!(holder.last_claim_time, Some(env.block.time.seconds()));
+}
+
+#[test]
+fn solvency() {
+    let mut deps = mock_dependencies(&[]);
+    distribute_twice(&mut deps);
+
+    let solvency = |deps: Deps| {
+        let res = query(deps, mock_env(), QueryMsg::Solvency {}).unwrap();
+        from_binary::<SolvencyResponse>(&res).unwrap()
+    };
+    assert_eq!(
+        solvency(deps.as_ref()),
+        SolvencyResponse {
+            reward_balance: Uint128::new(300u128),
+            liabilities: Uint128::new(300u128),
+            protocol_fee: Uint128::zero(),
+            surplus: Uint128::zero(),
+            deficit: Uint128::zero(),
+            reward_dust: Decimal256::zero(),
+        }
+    );
+
+    // 100 uusd over 150 bETH are not recognized yet, the index keeps 18 decimals of 2/3
+    deps.querier
+        .update_balance(MOCK_CONTRACT_ADDR, &[Coin::new(400u128, "uusd")]);
+    assert_eq!(
+        solvency(deps.as_ref()),
+        SolvencyResponse {
+            reward_balance: Uint128::new(400u128),
+            liabilities: Uint128::new(399u128),
+            protocol_fee: Uint128::zero(),
+            surplus: Uint128::new(1u128),
+            deficit: Uint128::zero(),
+            reward_dust: Decimal256::from_str("0.0000000000000001").unwrap(),
+        }
+    );
+
+    // addr0000 claims 266 uusd and keeps 2/3 pending
+    let info = mock_info("addr0000", &[]);
+    let msg = ExecuteMsg::ClaimRewards { recipient: None };
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert!(res.attributes.contains(&attr("rewards", "266")));
+    deps.querier
+        .update_balance(MOCK_CONTRACT_ADDR, &[Coin::new(134u128, "uusd")]);
+    let res = solvency(deps.as_ref());
+    assert_eq!(res.liabilities, Uint128::new(133u128));
+    assert_eq!(res.surplus, Uint128::new(1u128));
+
+    // the pending fraction of a pruned holder is dust
+    let msg = ExecuteMsg::DecreaseBalance {
+        address: "addr0000".to_string(),
+        amount: Uint128::new(100u128),
+    };
+    let info = mock_info(MOCK_TOKEN_CONTRACT_ADDR, &[]);
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    let res = solvency(deps.as_ref());
+    assert_eq!(res.liabilities, Uint128::new(133u128));
+    assert_eq!(
+        res.reward_dust,
+        Decimal256::from_str("0.6666666666666667").unwrap()
+    );
+
+    // the balance fell below the rewards owed
+    deps.querier
+        .update_balance(MOCK_CONTRACT_ADDR, &[Coin::new(100u128, "uusd")]);
+    assert_eq!(
+        solvency(deps.as_ref()),
+        SolvencyResponse {
+            reward_balance: Uint128::new(100u128),
+            liabilities: Uint128::new(133u128),
+            protocol_fee: Uint128::zero(),
+            surplus: Uint128::zero(),
+            deficit: Uint128::new(33u128),
+            reward_dust: Decimal256::from_str("0.6666666666666667").unwrap(),
+        }
+    );
+}
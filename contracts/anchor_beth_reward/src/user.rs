@@ -1,27 +1,35 @@
+use crate::history::record_global_index;
+use crate::lock::{accrued_penalties, settle_holder_penalties};
 use crate::state::{
-    read_config, read_contract_addr, read_holder, read_holders, read_state, store_holder,
-    store_state, Config, Holder, State,
+    read_claimant, read_config, read_contract_addr, read_holder, read_holder_entries, read_state,
+    read_stats, read_top_holders, store_claimant, store_holder, store_state, store_stats, Claimant,
+    Config, Holder, State,
+};
+use beth::reward::{
+    AccruedRewardsResponse, HolderResponse, HoldersResponse, OrderBy, SolvencyResponse,
 };
-use beth::reward::{AccruedRewardsResponse, HolderResponse, HoldersResponse};
 
 use cosmwasm_std::{
-    attr, Addr, BankMsg, CanonicalAddr, Coin, CosmosMsg, Decimal, Deps, DepsMut, Env, MessageInfo,
-    Response, StdError, StdResult, Uint128,
+    attr, Addr, BankMsg, BlockInfo, CanonicalAddr, Coin, CosmosMsg, Decimal, Deps, DepsMut, Env,
+    MessageInfo, Order, Response, StdError, StdResult, Storage, Uint128,
 };
 
 use crate::math::{
-    decimal_multiplication_in_256, decimal_subtraction_in_256, decimal_summation_in_256,
+    decimal_from_ratio_in_256, decimal_from_uint128, decimal_multiplication_in_256,
+    decimal_split_in_256, decimal_subtraction_in_256, decimal_summation_in_256,
 };
-use beth::deduct_tax;
-use std::str::FromStr;
-use terra_cosmwasm::TerraMsgWrapper;
+use beth::{deduct_tax, CustomMsg};
+use cosmwasm_bignumber::Decimal256;
+
+// addresses of a `HoldersByAddresses` query
+const MAX_ADDRESSES: usize = 30;
 
 pub fn execute_claim_rewards(
     deps: DepsMut,
     env: Env,
     info: MessageInfo,
     recipient: Option<Addr>,
-) -> StdResult<Response<TerraMsgWrapper>> {
+) -> StdResult<Response<CustomMsg>> {
     let contract_addr = env.contract.address;
     let holder_addr = info.sender.clone();
     let holder_addr_raw = deps.api.addr_canonicalize(holder_addr.as_str())?;
@@ -41,35 +49,180 @@ pub fn execute_claim_rewards(
         .unwrap();
 
     // Update state's global index before calculating user rewards
-    update_global_index(&mut state, reward_balance.amount)?;
+    update_global_index(
+        deps.storage,
+        &env.block,
+        &mut state,
+        reward_balance.amount,
+        config.protocol_fee_rate,
+    )?;
 
     let reward_with_decimals =
-        calculate_decimal_rewards(state.global_index, holder.index, holder.balance)?;
+        calculate_decimal_rewards(state.global_index, holder.index, holder.effective_balance())?;
 
     let all_reward_with_decimals =
-        decimal_summation_in_256(reward_with_decimals, holder.pending_rewards);
-    let decimals = get_decimals(all_reward_with_decimals).unwrap();
-
-    let rewards = all_reward_with_decimals * Uint128::new(1);
+        decimal_summation_in_256(reward_with_decimals, holder.pending_rewards)?;
+    let (rewards, decimals) = decimal_split_in_256(all_reward_with_decimals)?;
 
     if rewards.is_zero() {
         return Err(StdError::generic_err("No rewards have accrued yet"));
     }
 
     state.prev_reward_balance = state.prev_reward_balance.checked_sub(rewards)?;
-    store_state(deps.storage, &state)?;
+    state.total_liabilities =
+        decimal_subtraction_in_256(state.total_liabilities, decimal_from_uint128(rewards))?;
 
     holder.pending_rewards = decimals;
     holder.index = state.global_index;
-    store_holder(deps.storage, &holder_addr_raw, &holder)?;
+    store_holder(deps.storage, &mut state, &holder_addr_raw, &holder)?;
+    store_state(deps.storage, &state)?;
 
-    let bank_msg: CosmosMsg<TerraMsgWrapper> = BankMsg::Send {
+    let net_rewards = deduct_tax(
+        deps.as_ref(),
+        Coin {
+            denom: config.reward_denom,
+            amount: rewards,
+        },
+    )?;
+
+    let mut stats = read_stats(deps.storage)?;
+    stats.total_claimed = stats.total_claimed.checked_add(rewards)?;
+    stats.total_claimed_net = stats.total_claimed_net.checked_add(net_rewards.amount)?;
+    stats.total_tax = stats
+        .total_tax
+        .checked_add(rewards.checked_sub(net_rewards.amount)?)?;
+    stats.claims += 1;
+    let total_claimed = match read_claimant(deps.storage, &holder_addr_raw)? {
+        Some(claimant) => claimant.total_claimed,
+        None => {
+            stats.claimants += 1;
+            Uint128::zero()
+        }
+    };
+    store_stats(deps.storage, &stats)?;
+    store_claimant(
+        deps.storage,
+        &holder_addr_raw,
+        &Claimant {
+            total_claimed: total_claimed.checked_add(rewards)?,
+            last_claim_time: env.block.time.seconds(),
+        },
+    )?;
+
+    let bank_msg: CosmosMsg<CustomMsg> = BankMsg::Send {
         to_address: recipient.to_string(),
+        amount: vec![net_rewards],
+    }
+    .into();
+
+    Ok(Response::new()
+        .add_attributes(vec![
+            attr("action", "claim_reward"),
+            attr("holder_address", holder_addr),
+            attr("rewards", rewards),
+        ])
+        .add_message(bank_msg))
+}
+
+pub fn execute_deposit_rewards(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+) -> StdResult<Response<CustomMsg>> {
+    let config: Config = read_config(deps.storage)?;
+    let mut state: State = read_state(deps.storage)?;
+
+    if info
+        .funds
+        .iter()
+        .any(|coin| coin.denom != config.reward_denom)
+    {
+        return Err(StdError::generic_err(format!(
+            "Only {} can be deposited",
+            config.reward_denom
+        )));
+    }
+    let amount = info
+        .funds
+        .iter()
+        .find(|coin| coin.denom == config.reward_denom)
+        .map(|coin| coin.amount)
+        .unwrap_or_default();
+    if amount.is_zero() {
+        return Err(StdError::generic_err(format!(
+            "No {} assets are provided to deposit",
+            config.reward_denom
+        )));
+    }
+
+    // The contract balance already includes the deposit
+    let reward_balance = deps
+        .querier
+        .query_balance(env.contract.address, config.reward_denom.as_str())?;
+
+    // Update state's global index, without holders the deposit waits for the first one
+    update_global_index(
+        deps.storage,
+        &env.block,
+        &mut state,
+        reward_balance.amount,
+        config.protocol_fee_rate,
+    )?;
+    store_state(deps.storage, &state)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "deposit_rewards"),
+        attr("depositor", info.sender),
+        attr("amount", amount),
+    ]))
+}
+
+pub fn execute_withdraw_protocol_fee(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+) -> StdResult<Response<CustomMsg>> {
+    let config: Config = read_config(deps.storage)?;
+    let mut state: State = read_state(deps.storage)?;
+
+    let treasury = match config.treasury {
+        Some(treasury) => deps.api.addr_humanize(&treasury)?,
+        None => return Err(StdError::generic_err("unauthorized")),
+    };
+    if info.sender != treasury {
+        return Err(StdError::generic_err("unauthorized"));
+    }
+
+    // Load the reward contract balance
+    let reward_balance = deps
+        .querier
+        .query_balance(env.contract.address, config.reward_denom.as_str())?;
+
+    // Update state's global index to collect the fee of the latest rewards
+    update_global_index(
+        deps.storage,
+        &env.block,
+        &mut state,
+        reward_balance.amount,
+        config.protocol_fee_rate,
+    )?;
+
+    let protocol_fee = state.protocol_fee;
+    if protocol_fee.is_zero() {
+        return Err(StdError::generic_err("No protocol fee to withdraw"));
+    }
+
+    state.prev_reward_balance = state.prev_reward_balance.checked_sub(protocol_fee)?;
+    state.protocol_fee = Uint128::zero();
+    store_state(deps.storage, &state)?;
+
+    let bank_msg: CosmosMsg<CustomMsg> = BankMsg::Send {
+        to_address: treasury.to_string(),
         amount: vec![deduct_tax(
             deps.as_ref(),
             Coin {
                 denom: config.reward_denom,
-                amount: rewards,
+                amount: protocol_fee,
             },
         )?],
     }
@@ -77,9 +230,9 @@ pub fn execute_claim_rewards(
 
     Ok(Response::new()
         .add_attributes(vec![
-            attr("action", "claim_reward"),
-            attr("holder_address", holder_addr),
-            attr("rewards", rewards),
+            attr("action", "withdraw_protocol_fee"),
+            attr("treasury", treasury),
+            attr("amount", protocol_fee),
         ])
         .add_message(bank_msg))
 }
@@ -90,7 +243,7 @@ pub fn execute_increase_balance(
     info: MessageInfo,
     address: Addr,
     amount: Uint128,
-) -> StdResult<Response<TerraMsgWrapper>> {
+) -> StdResult<Response<CustomMsg>> {
     let config = read_config(deps.storage)?;
     let token_address = assert_token_contract(config.token_contract)?;
     let address_raw = deps.api.addr_canonicalize(address.as_str())?;
@@ -101,6 +254,15 @@ pub fn execute_increase_balance(
         return Err(StdError::generic_err("unauthorized"));
     }
 
+    // the bETH held by the contract is accounted in the lock positions
+    if address == env.contract.address {
+        return Ok(Response::new().add_attributes(vec![
+            attr("action", "increase_balance"),
+            attr("holder_address", address),
+            attr("amount", amount),
+        ]));
+    }
+
     let mut state: State = read_state(deps.storage)?;
     let mut holder: Holder = read_holder(deps.storage, &address_raw)?;
 
@@ -111,17 +273,25 @@ pub fn execute_increase_balance(
         .unwrap();
 
     // Update state's global index
-    update_global_index(&mut state, reward_balance.amount)?;
+    update_global_index(
+        deps.storage,
+        &env.block,
+        &mut state,
+        reward_balance.amount,
+        config.protocol_fee_rate,
+    )?;
 
     // Get decimals
-    let rewards = calculate_decimal_rewards(state.global_index, holder.index, holder.balance)?;
+    let rewards =
+        calculate_decimal_rewards(state.global_index, holder.index, holder.effective_balance())?;
 
     holder.index = state.global_index;
-    holder.pending_rewards = decimal_summation_in_256(rewards, holder.pending_rewards);
+    holder.pending_rewards = decimal_summation_in_256(rewards, holder.pending_rewards)?;
+    settle_holder_penalties(&state, &mut holder)?;
     holder.balance += amount;
     state.total_balance += amount;
 
-    store_holder(deps.storage, &address_raw, &holder)?;
+    store_holder(deps.storage, &mut state, &address_raw, &holder)?;
     store_state(deps.storage, &state)?;
 
     Ok(Response::new().add_attributes(vec![
@@ -137,7 +307,7 @@ pub fn execute_decrease_balance(
     info: MessageInfo,
     address: Addr,
     amount: Uint128,
-) -> StdResult<Response<TerraMsgWrapper>> {
+) -> StdResult<Response<CustomMsg>> {
     let config = read_config(deps.storage)?;
     let token_address = assert_token_contract(config.token_contract)?;
     let address_raw = deps.api.addr_canonicalize(address.as_str())?;
@@ -148,6 +318,15 @@ pub fn execute_decrease_balance(
         return Err(StdError::generic_err("unauthorized"));
     }
 
+    // the bETH held by the contract is accounted in the lock positions
+    if address == env.contract.address {
+        return Ok(Response::new().add_attributes(vec![
+            attr("action", "decrease_balance"),
+            attr("holder_address", address),
+            attr("amount", amount),
+        ]));
+    }
+
     let mut state: State = read_state(deps.storage)?;
     let mut holder: Holder = read_holder(deps.storage, &address_raw)?;
     if holder.balance < amount {
@@ -164,16 +343,24 @@ pub fn execute_decrease_balance(
         .unwrap();
 
     // Update state's global index
-    update_global_index(&mut state, reward_balance.amount)?;
+    update_global_index(
+        deps.storage,
+        &env.block,
+        &mut state,
+        reward_balance.amount,
+        config.protocol_fee_rate,
+    )?;
 
-    let rewards = calculate_decimal_rewards(state.global_index, holder.index, holder.balance)?;
+    let rewards =
+        calculate_decimal_rewards(state.global_index, holder.index, holder.effective_balance())?;
 
     holder.index = state.global_index;
-    holder.pending_rewards = decimal_summation_in_256(rewards, holder.pending_rewards);
+    holder.pending_rewards = decimal_summation_in_256(rewards, holder.pending_rewards)?;
+    settle_holder_penalties(&state, &mut holder)?;
     holder.balance = holder.balance.checked_sub(amount).unwrap();
     state.total_balance = state.total_balance.checked_sub(amount).unwrap();
 
-    store_holder(deps.storage, &address_raw, &holder)?;
+    store_holder(deps.storage, &mut state, &address_raw, &holder)?;
     store_state(deps.storage, &state)?;
 
     Ok(Response::new().add_attributes(vec![
@@ -183,35 +370,169 @@ pub fn execute_decrease_balance(
     ]))
 }
 
-/// Increase global_index according to claimed rewards amount
-fn update_global_index(state: &mut State, reward_balance: Uint128) -> StdResult<()> {
+pub fn execute_batch_transfer_balance(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    from: Addr,
+    transfers: Vec<(Addr, Uint128)>,
+) -> StdResult<Response<CustomMsg>> {
+    let config = read_config(deps.storage)?;
+    let token_address = assert_token_contract(config.token_contract)?;
+    let from_raw = deps.api.addr_canonicalize(from.as_str())?;
+    let sender = deps.api.addr_canonicalize(info.sender.as_str())?;
+
+    // Check sender is token contract
+    if sender != token_address {
+        return Err(StdError::generic_err("unauthorized"));
+    }
+
+    let mut total_amount = Uint128::zero();
+    for (_, amount) in transfers.iter() {
+        total_amount = total_amount.checked_add(*amount)?;
+    }
+
+    let mut state: State = read_state(deps.storage)?;
+    let mut holder: Holder = read_holder(deps.storage, &from_raw)?;
+    if holder.balance < total_amount {
+        return Err(StdError::generic_err(format!(
+            "Decrease amount cannot exceed user balance: {}",
+            holder.balance
+        )));
+    }
+
+    // Load the reward contract balance
+    let reward_balance = deps
+        .querier
+        .query_balance(env.contract.address.clone(), config.reward_denom.as_str())
+        .unwrap();
+
+    // Update state's global index once for the whole batch
+    update_global_index(
+        deps.storage,
+        &env.block,
+        &mut state,
+        reward_balance.amount,
+        config.protocol_fee_rate,
+    )?;
+
+    let rewards =
+        calculate_decimal_rewards(state.global_index, holder.index, holder.effective_balance())?;
+
+    holder.index = state.global_index;
+    holder.pending_rewards = decimal_summation_in_256(rewards, holder.pending_rewards)?;
+    settle_holder_penalties(&state, &mut holder)?;
+    holder.balance = holder.balance.checked_sub(total_amount)?;
+    store_holder(deps.storage, &mut state, &from_raw, &holder)?;
+
+    // recipients are loaded one by one, so a recipient can appear more than once
+    for (address, amount) in transfers.iter() {
+        // the bETH held by the contract is accounted in the lock positions
+        if *address == env.contract.address {
+            state.total_balance = state.total_balance.checked_sub(*amount)?;
+            continue;
+        }
+
+        let address_raw = deps.api.addr_canonicalize(address.as_str())?;
+        let mut holder: Holder = read_holder(deps.storage, &address_raw)?;
+
+        let rewards = calculate_decimal_rewards(
+            state.global_index,
+            holder.index,
+            holder.effective_balance(),
+        )?;
+
+        holder.index = state.global_index;
+        holder.pending_rewards = decimal_summation_in_256(rewards, holder.pending_rewards)?;
+        settle_holder_penalties(&state, &mut holder)?;
+        holder.balance += *amount;
+        store_holder(deps.storage, &mut state, &address_raw, &holder)?;
+    }
+
+    // the total balance only changes by what is sent to the contract itself
+    store_state(deps.storage, &state)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "batch_transfer_balance"),
+        attr("from", from),
+        attr("amount", total_amount),
+        attr("transfers", transfers.len().to_string()),
+    ]))
+}
+
+/// Increase global_index according to claimed rewards amount, and record the update in
+/// the reward history
+pub fn update_global_index(
+    storage: &mut dyn Storage,
+    block: &BlockInfo,
+    state: &mut State,
+    reward_balance: Uint128,
+    protocol_fee_rate: Decimal,
+) -> StdResult<()> {
+    let (claimed_rewards, protocol_fee) =
+        accrue_global_index(state, reward_balance, protocol_fee_rate)?;
+    if claimed_rewards.is_zero() {
+        return Ok(());
+    }
+
+    let mut stats = read_stats(storage)?;
+    stats.total_rewards = stats.total_rewards.checked_add(claimed_rewards)?;
+    store_stats(storage, &stats)?;
+
+    record_global_index(storage, block, state, claimed_rewards, protocol_fee)
+}
+
+/// Increase global_index according to claimed rewards amount, returns the claimed rewards
+/// and the protocol fee carved out of them
+fn accrue_global_index(
+    state: &mut State,
+    reward_balance: Uint128,
+    protocol_fee_rate: Decimal,
+) -> StdResult<(Uint128, Uint128)> {
+    let total_effective_balance = state.total_effective_balance();
+
     // Zero staking balance check
-    if state.total_balance.is_zero() {
+    if total_effective_balance.is_zero() {
         // nothing balance, skip update
-        return Ok(());
+        return Ok((Uint128::zero(), Uint128::zero()));
     }
 
     // No change check
     if state.prev_reward_balance == reward_balance {
         // balance didnt change, skip update
-        return Ok(());
+        return Ok((Uint128::zero(), Uint128::zero()));
     }
 
     // claimed_rewards = current_balance - prev_balance;
     let claimed_rewards = reward_balance.checked_sub(state.prev_reward_balance)?;
 
+    // the protocol fee is carved out before the distribution, at the current rate
+    let protocol_fee = protocol_fee_rate * claimed_rewards;
+    state.protocol_fee += protocol_fee;
+
     // update state
     state.prev_reward_balance = reward_balance;
-    // global_index += claimed_rewards / total_balance;
-    state.global_index = decimal_summation_in_256(
-        state.global_index,
-        Decimal::from_ratio(claimed_rewards, state.total_balance),
-    );
-
-    Ok(())
+    // global_index += (claimed_rewards - protocol_fee) / total_effective_balance;
+    let distributed = claimed_rewards - protocol_fee;
+    let index_increase = decimal_from_ratio_in_256(distributed, total_effective_balance)?;
+    state.global_index = decimal_summation_in_256(state.global_index, index_increase)?;
+
+    // the holders are owed the distribution at the precision of the index, the rest is dust
+    let owed = decimal_multiplication_in_256(
+        index_increase,
+        decimal_from_uint128(total_effective_balance),
+    )?;
+    state.total_liabilities = decimal_summation_in_256(state.total_liabilities, owed)?;
+    state.reward_dust = decimal_summation_in_256(
+        state.reward_dust,
+        decimal_subtraction_in_256(decimal_from_uint128(distributed), owed)?,
+    )?;
+
+    Ok((claimed_rewards, protocol_fee))
 }
 
-pub fn query_accrued_rewards(deps: Deps, address: Addr) -> StdResult<AccruedRewardsResponse> {
+/// State with the global index updated to the current reward balance
+fn live_state(deps: Deps) -> StdResult<State> {
     let mut state = read_state(deps.storage)?;
     let config = read_config(deps.storage)?;
 
@@ -225,70 +546,172 @@ pub fn query_accrued_rewards(deps: Deps, address: Addr) -> StdResult<AccruedRewa
         .unwrap();
 
     // Update state's global index
-    update_global_index(&mut state, reward_balance.amount)?;
+    accrue_global_index(&mut state, reward_balance.amount, config.protocol_fee_rate)?;
 
-    let holder: Holder = read_holder(deps.storage, &deps.api.addr_canonicalize(address.as_str())?)?;
+    Ok(state)
+}
+
+fn accrued_rewards(state: &State, holder: &Holder) -> StdResult<Uint128> {
     let reward_with_decimals =
-        calculate_decimal_rewards(state.global_index, holder.index, holder.balance)?;
+        calculate_decimal_rewards(state.global_index, holder.index, holder.effective_balance())?;
     let all_reward_with_decimals =
-        decimal_summation_in_256(reward_with_decimals, holder.pending_rewards);
+        decimal_summation_in_256(reward_with_decimals, holder.pending_rewards)?;
+    let (rewards, _) = decimal_split_in_256(all_reward_with_decimals)?;
 
-    let rewards = all_reward_with_decimals * Uint128::new(1);
-
-    Ok(AccruedRewardsResponse { rewards })
+    Ok(rewards)
 }
 
-pub fn query_holder(deps: Deps, address: Addr) -> StdResult<HolderResponse> {
-    let holder: Holder = read_holder(deps.storage, &deps.api.addr_canonicalize(address.as_str())?)?;
+fn holder_response(
+    deps: Deps,
+    state: &State,
+    address: &CanonicalAddr,
+    holder: &Holder,
+) -> StdResult<HolderResponse> {
+    let claimant = read_claimant(deps.storage, address)?;
     Ok(HolderResponse {
-        address: address.to_string(),
+        address: deps.api.addr_humanize(address)?.to_string(),
         balance: holder.balance,
         index: holder.index,
         pending_rewards: holder.pending_rewards,
+        accrued_rewards: accrued_rewards(state, holder)?,
+        total_claimed: claimant
+            .as_ref()
+            .map(|claimant| claimant.total_claimed)
+            .unwrap_or_default(),
+        last_claim_time: claimant.map(|claimant| claimant.last_claim_time),
+        locked_amount: holder.locked_amount,
+        locked_weight: holder.locked_weight,
+        penalty_index: holder.penalty_index,
+        pending_penalties: holder.pending_penalties,
+        accrued_penalties: accrued_penalties(state, holder)?,
     })
 }
 
+pub fn query_accrued_rewards(deps: Deps, address: Addr) -> StdResult<AccruedRewardsResponse> {
+    let state = live_state(deps)?;
+    let holder: Holder = read_holder(deps.storage, &deps.api.addr_canonicalize(address.as_str())?)?;
+
+    Ok(AccruedRewardsResponse {
+        rewards: accrued_rewards(&state, &holder)?,
+    })
+}
+
+/// Liabilities of the contract against its reward balance, with the rewards not recognized
+/// yet distributed. A balance below the recognized rewards is a deficit.
+pub fn query_solvency(deps: Deps) -> StdResult<SolvencyResponse> {
+    let mut state = read_state(deps.storage)?;
+    let config = read_config(deps.storage)?;
+
+    let contract_addr = deps.api.addr_humanize(&read_contract_addr(deps.storage)?)?;
+    let reward_balance = deps
+        .querier
+        .query_balance(contract_addr, config.reward_denom.as_str())?
+        .amount;
+    if reward_balance > state.prev_reward_balance {
+        accrue_global_index(&mut state, reward_balance, config.protocol_fee_rate)?;
+    }
+
+    // the fractions pending to the holders cannot be claimed
+    let (liabilities, _) = decimal_split_in_256(state.total_liabilities)?;
+    let owed = liabilities.checked_add(state.protocol_fee)?;
+
+    Ok(SolvencyResponse {
+        reward_balance,
+        liabilities,
+        protocol_fee: state.protocol_fee,
+        surplus: reward_balance.saturating_sub(owed),
+        deficit: owed.saturating_sub(reward_balance),
+        reward_dust: state.reward_dust,
+    })
+}
+
+pub fn query_holder(deps: Deps, address: Addr) -> StdResult<HolderResponse> {
+    let state = live_state(deps)?;
+    let address_raw = deps.api.addr_canonicalize(address.as_str())?;
+    let holder: Holder = read_holder(deps.storage, &address_raw)?;
+    holder_response(deps, &state, &address_raw, &holder)
+}
+
 pub fn query_holders(
     deps: Deps,
     start_after: Option<Addr>,
+    start_before: Option<Addr>,
     limit: Option<u32>,
+    order: Option<OrderBy>,
 ) -> StdResult<HoldersResponse> {
-    let start_after = if let Some(start_after) = start_after {
-        Some(deps.api.addr_canonicalize(start_after.as_str())?)
-    } else {
-        None
+    let start_after = match start_after {
+        Some(start_after) => Some(deps.api.addr_canonicalize(start_after.as_str())?),
+        None => None,
+    };
+    let start_before = match start_before {
+        Some(start_before) => Some(deps.api.addr_canonicalize(start_before.as_str())?),
+        None => None,
+    };
+    let order = match order {
+        Some(OrderBy::Desc) => Order::Descending,
+        Some(OrderBy::Asc) | None => Order::Ascending,
     };
 
-    let holders: Vec<HolderResponse> = read_holders(deps, start_after, limit)?;
+    let state = live_state(deps)?;
+    let holders = read_holder_entries(deps.storage, start_after, start_before, limit, order)?
+        .into_iter()
+        .map(|(address, holder)| holder_response(deps, &state, &address, &holder))
+        .collect::<StdResult<Vec<HolderResponse>>>()?;
 
     Ok(HoldersResponse { holders })
 }
 
-// calculate the reward based on the sender's index and the global index.
-fn calculate_decimal_rewards(
-    global_index: Decimal,
-    user_index: Decimal,
-    user_balance: Uint128,
-) -> StdResult<Decimal> {
-    let decimal_balance = Decimal::from_ratio(user_balance, Uint128::new(1));
-    Ok(decimal_multiplication_in_256(
-        decimal_subtraction_in_256(global_index, user_index),
-        decimal_balance,
-    ))
+pub fn query_holders_by_addresses(deps: Deps, addresses: Vec<Addr>) -> StdResult<HoldersResponse> {
+    if addresses.len() > MAX_ADDRESSES {
+        return Err(StdError::generic_err(format!(
+            "Cannot query more than {} holders at once",
+            MAX_ADDRESSES
+        )));
+    }
+
+    let state = live_state(deps)?;
+    let holders = addresses
+        .into_iter()
+        .map(|address| {
+            let address_raw = deps.api.addr_canonicalize(address.as_str())?;
+            let holder = read_holder(deps.storage, &address_raw)?;
+            holder_response(deps, &state, &address_raw, &holder)
+        })
+        .collect::<StdResult<Vec<HolderResponse>>>()?;
+
+    Ok(HoldersResponse { holders })
 }
 
-// calculate the reward with decimal
-fn get_decimals(value: Decimal) -> StdResult<Decimal> {
-    let stringed: &str = &*value.to_string();
-    let parts: &[&str] = &*stringed.split('.').collect::<Vec<&str>>();
-    match parts.len() {
-        1 => Ok(Decimal::zero()),
-        2 => {
-            let decimals = Decimal::from_str(&*("0.".to_owned() + parts[1]))?;
-            Ok(decimals)
-        }
-        _ => Err(StdError::generic_err("Unexpected number of dots")),
+pub fn query_top_holders(deps: Deps, limit: Option<u32>) -> StdResult<HoldersResponse> {
+    let state = live_state(deps)?;
+    // a partial balance index would rank the holders wrongly
+    if !state.holders_indexed {
+        return Err(StdError::generic_err(
+            "The balance index is incomplete until PruneHolders goes over all the holders",
+        ));
     }
+    let holders = read_top_holders(deps.storage, limit)?
+        .into_iter()
+        .map(|address| {
+            let holder = read_holder(deps.storage, &address)?;
+            holder_response(deps, &state, &address, &holder)
+        })
+        .collect::<StdResult<Vec<HolderResponse>>>()?;
+
+    Ok(HoldersResponse { holders })
+}
+
+// calculate the reward based on the sender's index and the global index.
+pub fn calculate_decimal_rewards(
+    global_index: Decimal256,
+    user_index: Decimal256,
+    user_balance: Uint128,
+) -> StdResult<Decimal256> {
+    let decimal_balance = decimal_from_uint128(user_balance);
+    decimal_multiplication_in_256(
+        decimal_subtraction_in_256(global_index, user_index)?,
+        decimal_balance,
+    )
 }
 
 fn assert_token_contract(token_contract: Option<CanonicalAddr>) -> StdResult<CanonicalAddr> {
@@ -304,22 +727,24 @@ mod tests {
 
     #[test]
     pub fn proper_calculate_rewards() {
-        let global_index = Decimal::from_ratio(Uint128::new(9), Uint128::new(100));
-        let user_index = Decimal::zero();
+        let global_index = decimal_from_ratio_in_256(Uint128::new(9), Uint128::new(100)).unwrap();
+        let user_index = Decimal256::zero();
         let user_balance = Uint128::new(1000);
         let reward = calculate_decimal_rewards(global_index, user_index, user_balance).unwrap();
         assert_eq!(reward.to_string(), "90");
     }
 
     #[test]
-    pub fn proper_get_decimals() {
-        let global_index = Decimal::from_ratio(Uint128::new(9999999), Uint128::new(100000000));
-        let user_index = Decimal::zero();
+    pub fn proper_split_rewards() {
+        let global_index =
+            decimal_from_ratio_in_256(Uint128::new(9999999), Uint128::new(100000000)).unwrap();
+        let user_index = Decimal256::zero();
         let user_balance = Uint128::new(10);
-        let reward = get_decimals(
+        let (rewards, decimals) = decimal_split_in_256(
             calculate_decimal_rewards(global_index, user_index, user_balance).unwrap(),
         )
         .unwrap();
-        assert_eq!(reward.to_string(), "0.9999999");
+        assert_eq!(rewards, Uint128::zero());
+        assert_eq!(decimals.to_string(), "0.9999999");
     }
 }
@@ -0,0 +1,321 @@
+#[cfg(not(feature = "library"))]
+use cosmwasm_std::entry_point;
+
+use crate::state::{
+    read_config, read_swap_pair, read_swap_pairs, remove_swap_pair, store_config, store_swap_pair,
+    Config,
+};
+use crate::swap::{cw20_swap_msg, native_swap_msgs};
+
+use beth::migration::migrate_contract_version;
+use beth::reward::ExecuteMsg as RewardExecuteMsg;
+use beth::reward_dispatcher::{
+    ConfigResponse, Cw20HookMsg, ExecuteMsg, InstantiateMsg, MigrateMsg, QueryMsg,
+    SwapPairsResponse,
+};
+use beth::{deduct_tax, CustomMsg};
+use cosmwasm_std::{
+    attr, from_binary, to_binary, BankMsg, Binary, Coin, CosmosMsg, Decimal, Deps, DepsMut, Env,
+    MessageInfo, Response, StdError, StdResult, WasmMsg,
+};
+use cw2::set_contract_version;
+use cw20::Cw20ReceiveMsg;
+
+// version info for migration info
+const CONTRACT_NAME: &str = "crates.io:anchor_beth_reward_dispatcher";
+const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn instantiate(
+    deps: DepsMut,
+    _env: Env,
+    _info: MessageInfo,
+    msg: InstantiateMsg,
+) -> StdResult<Response> {
+    assert_protocol_fee(msg.protocol_fee)?;
+
+    let conf = Config {
+        owner: deps.api.addr_canonicalize(&msg.owner)?,
+        reward_contract: deps.api.addr_canonicalize(&msg.reward_contract)?,
+        treasury: deps.api.addr_canonicalize(&msg.treasury)?,
+        reward_denom: msg.reward_denom,
+        protocol_fee: msg.protocol_fee,
+        max_spread: msg.max_spread,
+    };
+
+    store_config(deps.storage, &conf)?;
+    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+
+    Ok(Response::default())
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn execute(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msg: ExecuteMsg,
+) -> StdResult<Response<CustomMsg>> {
+    match msg {
+        ExecuteMsg::Receive(msg) => receive_cw20(deps, env, info, msg),
+        ExecuteMsg::DispatchRewards {} => execute_dispatch_rewards(deps, env),
+        ExecuteMsg::UpdateConfig {
+            owner,
+            reward_contract,
+            treasury,
+            protocol_fee,
+            max_spread,
+            clear_max_spread,
+        } => {
+            let max_spread = match (max_spread, clear_max_spread) {
+                (Some(_), true) => {
+                    return Err(StdError::generic_err(
+                        "Cannot set and clear the max spread at once",
+                    ))
+                }
+                (None, true) => Some(None),
+                (max_spread, false) => max_spread.map(Some),
+            };
+            execute_update_config(
+                deps,
+                info,
+                owner,
+                reward_contract,
+                treasury,
+                protocol_fee,
+                max_spread,
+            )
+        }
+        ExecuteMsg::UpdateSwapPair { asset_token, pair } => {
+            execute_update_swap_pair(deps, info, asset_token, pair)
+        }
+        ExecuteMsg::ForwardRewards {} => execute_forward_rewards(deps, env, info),
+    }
+}
+
+pub fn receive_cw20(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    cw20_msg: Cw20ReceiveMsg,
+) -> StdResult<Response<CustomMsg>> {
+    let asset_token = info.sender;
+
+    match from_binary(&cw20_msg.msg) {
+        Ok(Cw20HookMsg::DispatchRewards {}) => {
+            let config = read_config(deps.storage)?;
+            let pair = match read_swap_pair(
+                deps.storage,
+                &deps.api.addr_canonicalize(asset_token.as_str())?,
+            )? {
+                Some(pair) => deps.api.addr_humanize(&pair)?,
+                None => {
+                    return Err(StdError::generic_err(format!(
+                        "No swap pair for {}",
+                        asset_token
+                    )))
+                }
+            };
+
+            Ok(Response::new()
+                .add_message(cw20_swap_msg(
+                    &asset_token,
+                    &pair,
+                    cw20_msg.amount,
+                    config.max_spread,
+                )?)
+                .add_message(forward_rewards_msg(&env)?)
+                .add_attributes(vec![
+                    attr("action", "dispatch_rewards"),
+                    attr("asset_token", asset_token),
+                    attr("amount", cw20_msg.amount),
+                ]))
+        }
+        Err(err) => Err(err),
+    }
+}
+
+pub fn execute_dispatch_rewards(deps: DepsMut, env: Env) -> StdResult<Response<CustomMsg>> {
+    let config = read_config(deps.storage)?;
+
+    Ok(Response::new()
+        .add_messages(native_swap_msgs(deps.as_ref(), &env, &config.reward_denom)?)
+        .add_message(forward_rewards_msg(&env)?)
+        .add_attributes(vec![attr("action", "dispatch_rewards")]))
+}
+
+pub fn execute_forward_rewards(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+) -> StdResult<Response<CustomMsg>> {
+    if info.sender != env.contract.address {
+        return Err(StdError::generic_err("unauthorized"));
+    }
+
+    let config = read_config(deps.storage)?;
+    let balance = deps
+        .querier
+        .query_balance(&env.contract.address, config.reward_denom.as_str())?;
+
+    let fee = balance.amount * config.protocol_fee;
+    let rewards = balance.amount.checked_sub(fee)?;
+
+    // the tax is paid on top of both transfers
+    let mut messages: Vec<CosmosMsg<CustomMsg>> = vec![];
+    let fee_coin = deduct_tax(deps.as_ref(), Coin::new(fee.u128(), &config.reward_denom))?;
+    if !fee_coin.amount.is_zero() {
+        messages.push(
+            BankMsg::Send {
+                to_address: deps.api.addr_humanize(&config.treasury)?.to_string(),
+                amount: vec![fee_coin],
+            }
+            .into(),
+        );
+    }
+    let reward_coin = deduct_tax(
+        deps.as_ref(),
+        Coin::new(rewards.u128(), &config.reward_denom),
+    )?;
+    if !reward_coin.amount.is_zero() {
+        messages.push(
+            WasmMsg::Execute {
+                contract_addr: deps.api.addr_humanize(&config.reward_contract)?.to_string(),
+                msg: to_binary(&RewardExecuteMsg::DepositRewards {})?,
+                funds: vec![reward_coin],
+            }
+            .into(),
+        );
+    }
+
+    Ok(Response::new().add_messages(messages).add_attributes(vec![
+        attr("action", "forward_rewards"),
+        attr("protocol_fee", fee),
+        attr("rewards", rewards),
+    ]))
+}
+
+pub fn execute_update_config(
+    deps: DepsMut,
+    info: MessageInfo,
+    owner: Option<String>,
+    reward_contract: Option<String>,
+    treasury: Option<String>,
+    protocol_fee: Option<Decimal>,
+    max_spread: Option<Option<Decimal>>,
+) -> StdResult<Response<CustomMsg>> {
+    let mut config = read_config(deps.storage)?;
+    if config.owner != deps.api.addr_canonicalize(info.sender.as_str())? {
+        return Err(StdError::generic_err("unauthorized"));
+    }
+
+    if let Some(owner) = owner {
+        config.owner = deps.api.addr_canonicalize(&owner)?;
+    }
+    if let Some(reward_contract) = reward_contract {
+        config.reward_contract = deps.api.addr_canonicalize(&reward_contract)?;
+    }
+    if let Some(treasury) = treasury {
+        config.treasury = deps.api.addr_canonicalize(&treasury)?;
+    }
+    if let Some(protocol_fee) = protocol_fee {
+        assert_protocol_fee(protocol_fee)?;
+        config.protocol_fee = protocol_fee;
+    }
+    if let Some(max_spread) = max_spread {
+        config.max_spread = max_spread;
+    }
+
+    store_config(deps.storage, &config)?;
+
+    Ok(Response::new().add_attributes(vec![attr("action", "update_config")]))
+}
+
+pub fn execute_update_swap_pair(
+    deps: DepsMut,
+    info: MessageInfo,
+    asset_token: String,
+    pair: Option<String>,
+) -> StdResult<Response<CustomMsg>> {
+    let config = read_config(deps.storage)?;
+    if config.owner != deps.api.addr_canonicalize(info.sender.as_str())? {
+        return Err(StdError::generic_err("unauthorized"));
+    }
+
+    let asset_token_raw = deps.api.addr_canonicalize(&asset_token)?;
+    match &pair {
+        Some(pair) => store_swap_pair(
+            deps.storage,
+            &asset_token_raw,
+            &deps.api.addr_canonicalize(pair)?,
+        )?,
+        None => remove_swap_pair(deps.storage, &asset_token_raw),
+    }
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "update_swap_pair"),
+        attr("asset_token", asset_token),
+        attr("pair", pair.unwrap_or_default()),
+    ]))
+}
+
+fn forward_rewards_msg(env: &Env) -> StdResult<CosmosMsg<CustomMsg>> {
+    Ok(WasmMsg::Execute {
+        contract_addr: env.contract.address.to_string(),
+        msg: to_binary(&ExecuteMsg::ForwardRewards {})?,
+        funds: vec![],
+    }
+    .into())
+}
+
+fn assert_protocol_fee(protocol_fee: Decimal) -> StdResult<()> {
+    if protocol_fee > Decimal::one() {
+        return Err(StdError::generic_err(
+            "Protocol fee cannot be greater than 1",
+        ));
+    }
+    Ok(())
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
+    match msg {
+        QueryMsg::Config {} => to_binary(&query_config(deps)?),
+        QueryMsg::SwapPairs { start_after, limit } => {
+            to_binary(&query_swap_pairs(deps, start_after, limit)?)
+        }
+    }
+}
+
+fn query_config(deps: Deps) -> StdResult<ConfigResponse> {
+    let config = read_config(deps.storage)?;
+    Ok(ConfigResponse {
+        owner: deps.api.addr_humanize(&config.owner)?.to_string(),
+        reward_contract: deps.api.addr_humanize(&config.reward_contract)?.to_string(),
+        treasury: deps.api.addr_humanize(&config.treasury)?.to_string(),
+        reward_denom: config.reward_denom,
+        protocol_fee: config.protocol_fee,
+        max_spread: config.max_spread,
+    })
+}
+
+fn query_swap_pairs(
+    deps: Deps,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<SwapPairsResponse> {
+    let start_after = match start_after {
+        Some(asset_token) => Some(deps.api.addr_canonicalize(&asset_token)?),
+        None => None,
+    };
+    Ok(SwapPairsResponse {
+        swap_pairs: read_swap_pairs(deps, start_after, limit)?,
+    })
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn migrate(deps: DepsMut, _env: Env, _msg: MigrateMsg) -> StdResult<Response> {
+    migrate_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION, &[])?;
+
+    Ok(Response::default())
+}
@@ -0,0 +1,6 @@
+pub mod contract;
+pub mod state;
+pub mod swap;
+
+#[cfg(test)]
+mod testing;
@@ -0,0 +1,83 @@
+use cosmwasm_std::{CanonicalAddr, Decimal, Deps, Order, StdResult, Storage};
+use cosmwasm_storage::{bucket, bucket_read, singleton, singleton_read, ReadonlyBucket};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use beth::reward_dispatcher::SwapPairResponse;
+
+pub static KEY_CONFIG: &[u8] = b"config";
+
+pub static PREFIX_SWAP_PAIRS: &[u8] = b"swap_pairs";
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Config {
+    pub owner: CanonicalAddr,
+    pub reward_contract: CanonicalAddr,
+    pub treasury: CanonicalAddr,
+    pub reward_denom: String,
+    pub protocol_fee: Decimal,
+    pub max_spread: Option<Decimal>,
+}
+
+pub fn store_config(storage: &mut dyn Storage, config: &Config) -> StdResult<()> {
+    singleton(storage, KEY_CONFIG).save(config)
+}
+
+pub fn read_config(storage: &dyn Storage) -> StdResult<Config> {
+    singleton_read(storage, KEY_CONFIG).load()
+}
+
+// This is similar to HashMap<CW20 token address, pair address>
+pub fn store_swap_pair(
+    storage: &mut dyn Storage,
+    asset_token: &CanonicalAddr,
+    pair: &CanonicalAddr,
+) -> StdResult<()> {
+    bucket(storage, PREFIX_SWAP_PAIRS).save(asset_token.as_slice(), pair)
+}
+
+pub fn remove_swap_pair(storage: &mut dyn Storage, asset_token: &CanonicalAddr) {
+    bucket::<CanonicalAddr>(storage, PREFIX_SWAP_PAIRS).remove(asset_token.as_slice())
+}
+
+pub fn read_swap_pair(
+    storage: &dyn Storage,
+    asset_token: &CanonicalAddr,
+) -> StdResult<Option<CanonicalAddr>> {
+    bucket_read(storage, PREFIX_SWAP_PAIRS).may_load(asset_token.as_slice())
+}
+
+// settings for pagination
+const MAX_LIMIT: u32 = 30;
+const DEFAULT_LIMIT: u32 = 10;
+pub fn read_swap_pairs(
+    deps: Deps,
+    start_after: Option<CanonicalAddr>,
+    limit: Option<u32>,
+) -> StdResult<Vec<SwapPairResponse>> {
+    let pair_bucket: ReadonlyBucket<CanonicalAddr> = bucket_read(deps.storage, PREFIX_SWAP_PAIRS);
+
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = calc_range_start(start_after);
+
+    pair_bucket
+        .range(start.as_deref(), None, Order::Ascending)
+        .take(limit)
+        .map(|elem| {
+            let (k, v) = elem?;
+            Ok(SwapPairResponse {
+                asset_token: deps.api.addr_humanize(&CanonicalAddr::from(k))?.to_string(),
+                pair: deps.api.addr_humanize(&v)?.to_string(),
+            })
+        })
+        .collect()
+}
+
+// this will set the first key after the provided key, by appending a 1 byte
+fn calc_range_start(start_after: Option<CanonicalAddr>) -> Option<Vec<u8>> {
+    start_after.map(|addr| {
+        let mut v = addr.as_slice().to_vec();
+        v.push(1);
+        v
+    })
+}
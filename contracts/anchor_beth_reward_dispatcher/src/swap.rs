@@ -0,0 +1,66 @@
+use cosmwasm_std::{to_binary, Addr, CosmosMsg, Decimal, Deps, Env, StdResult, Uint128, WasmMsg};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use beth::CustomMsg;
+use cw20::Cw20ExecuteMsg;
+
+/// Hook of the Terraswap / Astroport pairs swapping the sent token
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum PairCw20HookMsg {
+    Swap {
+        belief_price: Option<Decimal>,
+        max_spread: Option<Decimal>,
+        to: Option<String>,
+    },
+}
+
+/// Swap every native balance but `reward_denom` through the Terra market module
+#[cfg(feature = "terra")]
+pub fn native_swap_msgs(
+    deps: Deps,
+    env: &Env,
+    reward_denom: &str,
+) -> StdResult<Vec<CosmosMsg<CustomMsg>>> {
+    let balances = deps.querier.query_all_balances(&env.contract.address)?;
+
+    Ok(balances
+        .into_iter()
+        .filter(|coin| coin.denom != reward_denom && !coin.amount.is_zero())
+        .map(|coin| terra_cosmwasm::create_swap_msg(coin, reward_denom.to_string()))
+        .collect())
+}
+
+/// Other chains have no market module, only `reward_denom` is dispatched
+#[cfg(not(feature = "terra"))]
+pub fn native_swap_msgs(
+    _deps: Deps,
+    _env: &Env,
+    _reward_denom: &str,
+) -> StdResult<Vec<CosmosMsg<CustomMsg>>> {
+    Ok(vec![])
+}
+
+/// Send `amount` of `asset_token` to its pair, the pair returns the reward denom
+pub fn cw20_swap_msg(
+    asset_token: &Addr,
+    pair: &Addr,
+    amount: Uint128,
+    max_spread: Option<Decimal>,
+) -> StdResult<CosmosMsg<CustomMsg>> {
+    Ok(WasmMsg::Execute {
+        contract_addr: asset_token.to_string(),
+        msg: to_binary(&Cw20ExecuteMsg::Send {
+            contract: pair.to_string(),
+            amount,
+            msg: to_binary(&PairCw20HookMsg::Swap {
+                belief_price: None,
+                max_spread,
+                to: None,
+            })?,
+        })?,
+        funds: vec![],
+    }
+    .into())
+}
@@ -0,0 +1,356 @@
+use cosmwasm_std::testing::{mock_env, mock_info, MockApi, MockStorage, MOCK_CONTRACT_ADDR};
+use cosmwasm_std::{
+    from_binary, to_binary, BankMsg, Coin, CosmosMsg, Decimal, OwnedDeps, StdError, SubMsg,
+    Uint128, WasmMsg,
+};
+
+use crate::contract::{execute, instantiate, query};
+use crate::swap::PairCw20HookMsg;
+use beth::mock_querier::{mock_dependencies_with_querier, WasmMockQuerier};
+use beth::reward::ExecuteMsg as RewardExecuteMsg;
+use beth::reward_dispatcher::{
+    ConfigResponse, Cw20HookMsg, ExecuteMsg, InstantiateMsg, QueryMsg, SwapPairResponse,
+    SwapPairsResponse,
+};
+use cw20::{Cw20ExecuteMsg, Cw20ReceiveMsg};
+
+const MOCK_OWNER_ADDR: &str = "owner0000";
+const MOCK_REWARD_ADDR: &str = "reward0000";
+const MOCK_TREASURY_ADDR: &str = "treasury0000";
+const MOCK_ASSET_TOKEN_ADDR: &str = "asset_token0000";
+const MOCK_PAIR_ADDR: &str = "pair0000";
+
+/// Mock dependencies holding `contract_balance` with a 1% tax capped at 1 UST
+fn mock_dependencies(
+    contract_balance: &[Coin],
+) -> OwnedDeps<MockStorage, MockApi, WasmMockQuerier> {
+    mock_dependencies_with_querier(
+        WasmMockQuerier::builder()
+            .with_balance(MOCK_CONTRACT_ADDR, contract_balance)
+            .with_tax(Decimal::percent(1), &[("uusd", 1000000u128)])
+            .build(),
+    )
+}
+
+fn default_init() -> InstantiateMsg {
+    InstantiateMsg {
+        owner: MOCK_OWNER_ADDR.to_string(),
+        reward_contract: MOCK_REWARD_ADDR.to_string(),
+        treasury: MOCK_TREASURY_ADDR.to_string(),
+        reward_denom: "uusd".to_string(),
+        protocol_fee: Decimal::percent(10),
+        max_spread: None,
+    }
+}
+
+fn forward_rewards_msg() -> SubMsg<beth::CustomMsg> {
+    SubMsg::new(WasmMsg::Execute {
+        contract_addr: MOCK_CONTRACT_ADDR.to_string(),
+        msg: to_binary(&ExecuteMsg::ForwardRewards {}).unwrap(),
+        funds: vec![],
+    })
+}
+
+#[test]
+fn proper_init() {
+    let mut deps = mock_dependencies(&[]);
+
+    let mut init_msg = default_init();
+    init_msg.protocol_fee = Decimal::percent(101);
+    let info = mock_info("addr0000", &[]);
+    let res = instantiate(deps.as_mut(), mock_env(), info, init_msg);
+    assert_eq!(
+        res.unwrap_err(),
+        StdError::generic_err("Protocol fee cannot be greater than 1")
+    );
+
+    let info = mock_info("addr0000", &[]);
+    let res = instantiate(deps.as_mut(), mock_env(), info, default_init()).unwrap();
+    assert_eq!(0, res.messages.len());
+
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap();
+    let config: ConfigResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        config,
+        ConfigResponse {
+            owner: MOCK_OWNER_ADDR.to_string(),
+            reward_contract: MOCK_REWARD_ADDR.to_string(),
+            treasury: MOCK_TREASURY_ADDR.to_string(),
+            reward_denom: "uusd".to_string(),
+            protocol_fee: Decimal::percent(10),
+            max_spread: None,
+        }
+    );
+}
+
+#[test]
+fn dispatch_native_rewards() {
+    let mut deps = mock_dependencies(&[Coin::new(1000u128, "uusd"), Coin::new(500u128, "ukrw")]);
+    let info = mock_info("addr0000", &[]);
+    instantiate(deps.as_mut(), mock_env(), info, default_init()).unwrap();
+
+    // anyone can dispatch
+    let info = mock_info("addr0000", &[]);
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        ExecuteMsg::DispatchRewards {},
+    )
+    .unwrap();
+
+    #[cfg(feature = "terra")]
+    let expected = vec![
+        SubMsg::new(terra_cosmwasm::create_swap_msg(
+            Coin::new(500u128, "ukrw"),
+            "uusd".to_string(),
+        )),
+        forward_rewards_msg(),
+    ];
+    // no market module to swap the other denoms
+    #[cfg(not(feature = "terra"))]
+    let expected = vec![forward_rewards_msg()];
+    assert_eq!(res.messages, expected);
+}
+
+#[test]
+fn forward_rewards() {
+    let mut deps = mock_dependencies(&[Coin::new(1000u128, "uusd")]);
+    let info = mock_info("addr0000", &[]);
+    instantiate(deps.as_mut(), mock_env(), info, default_init()).unwrap();
+
+    let info = mock_info("addr0000", &[]);
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        ExecuteMsg::ForwardRewards {},
+    );
+    assert_eq!(res.unwrap_err(), StdError::generic_err("unauthorized"));
+
+    // 10% to the treasury, the rest to the reward contract
+    let info = mock_info(MOCK_CONTRACT_ADDR, &[]);
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        ExecuteMsg::ForwardRewards {},
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages,
+        vec![
+            SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+                to_address: MOCK_TREASURY_ADDR.to_string(),
+                // 1% tax on Terra
+                amount: vec![Coin::new(
+                    if cfg!(feature = "terra") {
+                        99u128
+                    } else {
+                        100u128
+                    },
+                    "uusd"
+                )],
+            })),
+            SubMsg::new(WasmMsg::Execute {
+                contract_addr: MOCK_REWARD_ADDR.to_string(),
+                msg: to_binary(&RewardExecuteMsg::DepositRewards {}).unwrap(),
+                funds: vec![Coin::new(
+                    if cfg!(feature = "terra") {
+                        891u128
+                    } else {
+                        900u128
+                    },
+                    "uusd"
+                )],
+            }),
+        ]
+    );
+
+    // nothing to send without a fee or rewards
+    let msg = ExecuteMsg::UpdateConfig {
+        owner: None,
+        reward_contract: None,
+        treasury: None,
+        protocol_fee: Some(Decimal::zero()),
+        max_spread: None,
+        clear_max_spread: false,
+    };
+    let info = mock_info(MOCK_OWNER_ADDR, &[]);
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    deps.querier.update_balance(MOCK_CONTRACT_ADDR, &[]);
+
+    let info = mock_info(MOCK_CONTRACT_ADDR, &[]);
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        ExecuteMsg::ForwardRewards {},
+    )
+    .unwrap();
+    assert_eq!(res.messages.len(), 0);
+}
+
+#[test]
+fn dispatch_cw20_rewards() {
+    let mut deps = mock_dependencies(&[]);
+    let info = mock_info("addr0000", &[]);
+    instantiate(deps.as_mut(), mock_env(), info, default_init()).unwrap();
+
+    let receive_msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: "addr0000".to_string(),
+        amount: Uint128::new(1000),
+        msg: to_binary(&Cw20HookMsg::DispatchRewards {}).unwrap(),
+    });
+    let info = mock_info(MOCK_ASSET_TOKEN_ADDR, &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, receive_msg.clone());
+    assert_eq!(
+        res.unwrap_err(),
+        StdError::generic_err("No swap pair for asset_token0000")
+    );
+
+    let msg = ExecuteMsg::UpdateSwapPair {
+        asset_token: MOCK_ASSET_TOKEN_ADDR.to_string(),
+        pair: Some(MOCK_PAIR_ADDR.to_string()),
+    };
+    let info = mock_info("addr0000", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg.clone());
+    assert_eq!(res.unwrap_err(), StdError::generic_err("unauthorized"));
+
+    let info = mock_info(MOCK_OWNER_ADDR, &[]);
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let msg = QueryMsg::SwapPairs {
+        start_after: None,
+        limit: None,
+    };
+    let res: SwapPairsResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+    assert_eq!(
+        res.swap_pairs,
+        vec![SwapPairResponse {
+            asset_token: MOCK_ASSET_TOKEN_ADDR.to_string(),
+            pair: MOCK_PAIR_ADDR.to_string(),
+        }]
+    );
+
+    let info = mock_info(MOCK_ASSET_TOKEN_ADDR, &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, receive_msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![
+            SubMsg::new(WasmMsg::Execute {
+                contract_addr: MOCK_ASSET_TOKEN_ADDR.to_string(),
+                msg: to_binary(&Cw20ExecuteMsg::Send {
+                    contract: MOCK_PAIR_ADDR.to_string(),
+                    amount: Uint128::new(1000),
+                    msg: to_binary(&PairCw20HookMsg::Swap {
+                        belief_price: None,
+                        max_spread: None,
+                        to: None,
+                    })
+                    .unwrap(),
+                })
+                .unwrap(),
+                funds: vec![],
+            }),
+            forward_rewards_msg(),
+        ]
+    );
+
+    let msg = ExecuteMsg::UpdateSwapPair {
+        asset_token: MOCK_ASSET_TOKEN_ADDR.to_string(),
+        pair: None,
+    };
+    let info = mock_info(MOCK_OWNER_ADDR, &[]);
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let msg = QueryMsg::SwapPairs {
+        start_after: None,
+        limit: None,
+    };
+    let res: SwapPairsResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+    assert_eq!(res.swap_pairs, vec![]);
+}
+
+#[test]
+fn update_config() {
+    let mut deps = mock_dependencies(&[]);
+    let info = mock_info("addr0000", &[]);
+    instantiate(deps.as_mut(), mock_env(), info, default_init()).unwrap();
+
+    let msg = ExecuteMsg::UpdateConfig {
+        owner: Some("owner0001".to_string()),
+        reward_contract: Some("reward0001".to_string()),
+        treasury: Some("treasury0001".to_string()),
+        protocol_fee: Some(Decimal::percent(5)),
+        max_spread: Some(Decimal::percent(1)),
+        clear_max_spread: false,
+    };
+    let info = mock_info("addr0000", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg.clone());
+    assert_eq!(res.unwrap_err(), StdError::generic_err("unauthorized"));
+
+    let bad_fee = ExecuteMsg::UpdateConfig {
+        owner: None,
+        reward_contract: None,
+        treasury: None,
+        protocol_fee: Some(Decimal::percent(101)),
+        max_spread: None,
+        clear_max_spread: false,
+    };
+    let info = mock_info(MOCK_OWNER_ADDR, &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, bad_fee);
+    assert_eq!(
+        res.unwrap_err(),
+        StdError::generic_err("Protocol fee cannot be greater than 1")
+    );
+
+    let info = mock_info(MOCK_OWNER_ADDR, &[]);
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap();
+    let config: ConfigResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        config,
+        ConfigResponse {
+            owner: "owner0001".to_string(),
+            reward_contract: "reward0001".to_string(),
+            treasury: "treasury0001".to_string(),
+            reward_denom: "uusd".to_string(),
+            protocol_fee: Decimal::percent(5),
+            max_spread: Some(Decimal::percent(1)),
+        }
+    );
+
+    // the max spread can be cleared, but not set at the same time
+    let info = mock_info("owner0001", &[]);
+    let msg = ExecuteMsg::UpdateConfig {
+        owner: None,
+        reward_contract: None,
+        treasury: None,
+        protocol_fee: None,
+        max_spread: Some(Decimal::percent(2)),
+        clear_max_spread: true,
+    };
+    let res = execute(deps.as_mut(), mock_env(), info.clone(), msg);
+    assert_eq!(
+        res.unwrap_err(),
+        StdError::generic_err("Cannot set and clear the max spread at once")
+    );
+
+    let msg = ExecuteMsg::UpdateConfig {
+        owner: None,
+        reward_contract: None,
+        treasury: None,
+        protocol_fee: None,
+        max_spread: None,
+        clear_max_spread: true,
+    };
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap();
+    let config: ConfigResponse = from_binary(&res).unwrap();
+    assert_eq!(config.max_spread, None);
+    assert_eq!(config.protocol_fee, Decimal::percent(5));
+}
@@ -3,12 +3,14 @@ use std::fs::create_dir_all;
 
 use cosmwasm_schema::{export_schema, remove_schemas, schema_for};
 
-use anchor_beth_token::msg::TokenInstantiateMsg;
+use anchor_beth_token::msg::{
+    ExecuteMsg, ObserversResponse, QueryMsg, TokenConfigResponse, TokenInstantiateMsg,
+};
+use beth::observer::ObserverExecuteMsg;
 use cw20::{
     AllAccountsResponse, AllAllowancesResponse, AllowanceResponse, BalanceResponse,
-    TokenInfoResponse,
+    DownloadLogoResponse, MarketingInfoResponse, TokenInfoResponse,
 };
-use cw20_legacy::msg::{ExecuteMsg, QueryMsg};
 
 fn main() {
     let mut out_dir = current_dir().unwrap();
@@ -24,4 +26,9 @@ fn main() {
     export_schema(&schema_for!(TokenInfoResponse), &out_dir);
     export_schema(&schema_for!(AllAllowancesResponse), &out_dir);
     export_schema(&schema_for!(AllAccountsResponse), &out_dir);
+    export_schema(&schema_for!(MarketingInfoResponse), &out_dir);
+    export_schema(&schema_for!(DownloadLogoResponse), &out_dir);
+    export_schema(&schema_for!(TokenConfigResponse), &out_dir);
+    export_schema(&schema_for!(ObserversResponse), &out_dir);
+    export_schema(&schema_for!(ObserverExecuteMsg), &out_dir);
 }
@@ -0,0 +1,160 @@
+use cosmwasm_std::{attr, Addr, Deps, DepsMut, MessageInfo, Response, StdError, Uint128};
+
+use crate::state::{
+    read_admin, read_observer, read_observers, read_pending_admin, remove_observer,
+    remove_pending_admin, store_admin, store_observer, store_pending_admin, store_reward_contract,
+    MAX_OBSERVERS,
+};
+use cw20_base::state::{MinterData, TOKEN_INFO};
+use cw20_base::ContractError;
+
+pub fn execute_update_reward_contract(
+    deps: DepsMut,
+    info: MessageInfo,
+    reward_contract: String,
+) -> Result<Response, ContractError> {
+    assert_admin(deps.as_ref(), &info.sender)?;
+
+    let reward_raw = deps.api.addr_canonicalize(&reward_contract)?;
+    store_reward_contract(deps.storage, &reward_raw)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "update_reward_contract"),
+        attr("reward_contract", reward_contract),
+    ]))
+}
+
+pub fn execute_update_minter(
+    deps: DepsMut,
+    info: MessageInfo,
+    new_minter: String,
+    cap: Option<Uint128>,
+) -> Result<Response, ContractError> {
+    assert_admin(deps.as_ref(), &info.sender)?;
+
+    let mut token_info = TOKEN_INFO.load(deps.storage)?;
+    if let Some(limit) = cap {
+        if token_info.total_supply > limit {
+            return Err(ContractError::CannotExceedCap {});
+        }
+    }
+
+    token_info.mint = Some(MinterData {
+        minter: deps.api.addr_validate(&new_minter)?,
+        cap,
+    });
+    TOKEN_INFO.save(deps.storage, &token_info)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "update_minter"),
+        attr("minter", new_minter),
+    ]))
+}
+
+pub fn execute_propose_new_admin(
+    deps: DepsMut,
+    info: MessageInfo,
+    new_admin: String,
+) -> Result<Response, ContractError> {
+    assert_admin(deps.as_ref(), &info.sender)?;
+
+    let new_admin_raw = deps
+        .api
+        .addr_canonicalize(deps.api.addr_validate(&new_admin)?.as_str())?;
+    store_pending_admin(deps.storage, &new_admin_raw)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "propose_new_admin"),
+        attr("new_admin", new_admin),
+    ]))
+}
+
+pub fn execute_accept_admin(deps: DepsMut, info: MessageInfo) -> Result<Response, ContractError> {
+    let sender_raw = deps.api.addr_canonicalize(info.sender.as_str())?;
+    match read_pending_admin(deps.storage)? {
+        Some(pending_admin) if pending_admin == sender_raw => {}
+        _ => return Err(ContractError::Unauthorized {}),
+    }
+
+    store_admin(deps.storage, &sender_raw)?;
+    remove_pending_admin(deps.storage);
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "accept_admin"),
+        attr("admin", info.sender),
+    ]))
+}
+
+pub fn execute_add_observer(
+    deps: DepsMut,
+    info: MessageInfo,
+    address: String,
+) -> Result<Response, ContractError> {
+    assert_admin(deps.as_ref(), &info.sender)?;
+
+    let observer_raw = deps.api.addr_canonicalize(&address)?;
+    if read_observer(deps.storage, &observer_raw)?.is_some() {
+        return Err(StdError::generic_err("observer is already registered").into());
+    }
+    if read_observers(deps.storage)?.len() >= MAX_OBSERVERS {
+        return Err(StdError::generic_err(format!(
+            "cannot register more than {} observers",
+            MAX_OBSERVERS
+        ))
+        .into());
+    }
+    store_observer(deps.storage, &observer_raw, true)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "add_observer"),
+        attr("observer", address),
+    ]))
+}
+
+pub fn execute_remove_observer(
+    deps: DepsMut,
+    info: MessageInfo,
+    address: String,
+) -> Result<Response, ContractError> {
+    assert_admin(deps.as_ref(), &info.sender)?;
+
+    let observer_raw = deps.api.addr_canonicalize(&address)?;
+    if read_observer(deps.storage, &observer_raw)?.is_none() {
+        return Err(StdError::generic_err("observer is not registered").into());
+    }
+    remove_observer(deps.storage, &observer_raw);
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "remove_observer"),
+        attr("observer", address),
+    ]))
+}
+
+pub fn execute_update_observer(
+    deps: DepsMut,
+    info: MessageInfo,
+    address: String,
+    enabled: bool,
+) -> Result<Response, ContractError> {
+    assert_admin(deps.as_ref(), &info.sender)?;
+
+    let observer_raw = deps.api.addr_canonicalize(&address)?;
+    if read_observer(deps.storage, &observer_raw)?.is_none() {
+        return Err(StdError::generic_err("observer is not registered").into());
+    }
+    store_observer(deps.storage, &observer_raw, enabled)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "update_observer"),
+        attr("observer", address),
+        attr("enabled", enabled.to_string()),
+    ]))
+}
+
+fn assert_admin(deps: Deps, sender: &Addr) -> Result<(), ContractError> {
+    let sender_raw = deps.api.addr_canonicalize(sender.as_str())?;
+    if read_admin(deps.storage)? != Some(sender_raw) {
+        return Err(ContractError::Unauthorized {});
+    }
+    Ok(())
+}
@@ -1,31 +1,70 @@
 #[cfg(not(feature = "library"))]
 use cosmwasm_std::entry_point;
 
-use cosmwasm_std::{Binary, Deps, DepsMut, Env, MessageInfo, Response, StdResult};
+use cosmwasm_std::{
+    attr, to_binary, Attribute, Binary, Deps, DepsMut, Env, MessageInfo, Reply, Response, StdError,
+    StdResult,
+};
 
-use cw20_legacy::allowances::{execute_decrease_allowance, execute_increase_allowance};
-use cw20_legacy::contract::instantiate as cw20_instantiate;
-use cw20_legacy::contract::query as cw20_query;
-use cw20_legacy::msg::{ExecuteMsg, InstantiateMsg, QueryMsg};
+use cw20_base::allowances::{
+    execute_decrease_allowance, execute_increase_allowance, query_allowance,
+};
+use cw20_base::contract::instantiate as cw20_instantiate;
+use cw20_base::contract::{
+    execute_update_marketing, execute_upload_logo, query_balance, query_download_logo,
+    query_marketing_info, query_minter, query_token_info,
+};
+use cw20_base::enumerable::{query_all_accounts, query_all_allowances};
+use cw20_base::msg::InstantiateMsg;
 
+use crate::admin::{
+    execute_accept_admin, execute_add_observer, execute_propose_new_admin, execute_remove_observer,
+    execute_update_minter, execute_update_observer, execute_update_reward_contract,
+};
 use crate::handler::*;
-use crate::msg::{MigrateMsg, TokenInstantiateMsg};
-use crate::state::store_reward_contract;
-use cw20_legacy::state::{MinterData, TOKEN_INFO};
-use cw20_legacy::ContractError;
+use crate::migration::{
+    assert_legacy_migrated, migrate_legacy_accounts, migrate_legacy_token_info,
+    LegacyMigrationProgress,
+};
+use crate::msg::{
+    ExecuteMsg, MigrateMsg, ObserverResponse, ObserversResponse, QueryMsg, TokenConfigResponse,
+    TokenInstantiateMsg,
+};
+use crate::state::{
+    read_admin, read_observers, read_pending_admin, read_reward_contract, store_admin,
+    store_reward_contract,
+};
+use beth::migration::migrate_contract_version;
+use cw2::set_contract_version;
+use cw20::MarketingInfoResponse;
+use cw20_base::state::{MinterData, MARKETING_INFO, TOKEN_INFO};
+use cw20_base::ContractError;
+
+// version info for migration info
+const CONTRACT_NAME: &str = "crates.io:anchor_beth_token";
+const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
+// recorded by the cw20_legacy and cw20-base instantiate of earlier deployments
+const CW20_BASE_CONTRACT_NAME: &str = "crates.io:cw20-base";
+
+pub const OBSERVER_HOOK_REPLY_ID: u64 = 1;
 
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn instantiate(
-    deps: DepsMut,
+    mut deps: DepsMut,
     env: Env,
     info: MessageInfo,
     msg: TokenInstantiateMsg,
-) -> StdResult<Response> {
+) -> Result<Response, ContractError> {
     let reward_raw = deps.api.addr_canonicalize(&msg.reward_contract)?;
     store_reward_contract(deps.storage, &reward_raw)?;
 
+    if let Some(admin) = msg.admin {
+        let admin = deps.api.addr_validate(&admin)?;
+        store_admin(deps.storage, &deps.api.addr_canonicalize(admin.as_str())?)?;
+    }
+
     cw20_instantiate(
-        deps,
+        deps.branch(),
         env,
         info,
         InstantiateMsg {
@@ -34,8 +73,11 @@ pub fn instantiate(
             decimals: msg.decimals,
             initial_balances: msg.initial_balances,
             mint: msg.mint,
+            marketing: msg.marketing,
         },
     )?;
+    // overwrites the version recorded by cw20-base
+    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
 
     Ok(Response::default())
 }
@@ -47,10 +89,18 @@ pub fn execute(
     info: MessageInfo,
     msg: ExecuteMsg,
 ) -> Result<Response, ContractError> {
+    // balances are not reliable until all of them are moved to the new layout
+    if !matches!(msg, ExecuteMsg::MigrateLegacyState { .. }) {
+        assert_legacy_migrated(deps.storage)?;
+    }
+
     match msg {
         ExecuteMsg::Transfer { recipient, amount } => {
             execute_transfer(deps, env, info, recipient, amount)
         }
+        ExecuteMsg::BatchTransfer { transfers } => {
+            execute_batch_transfer(deps, env, info, transfers)
+        }
         ExecuteMsg::Burn { amount } => execute_burn(deps, env, info, amount),
         ExecuteMsg::Send {
             contract,
@@ -73,6 +123,9 @@ pub fn execute(
             recipient,
             amount,
         } => execute_transfer_from(deps, env, info, owner, recipient, amount),
+        ExecuteMsg::BatchTransferFrom { owner, transfers } => {
+            execute_batch_transfer_from(deps, env, info, owner, transfers)
+        }
         ExecuteMsg::BurnFrom { owner, amount } => execute_burn_from(deps, env, info, owner, amount),
         ExecuteMsg::SendFrom {
             owner,
@@ -80,32 +133,191 @@ pub fn execute(
             amount,
             msg,
         } => execute_send_from(deps, env, info, owner, contract, amount, msg),
+        ExecuteMsg::UpdateMarketing {
+            project,
+            description,
+            marketing,
+        } => execute_update_marketing(deps, env, info, project, description, marketing),
+        ExecuteMsg::UploadLogo(logo) => execute_upload_logo(deps, env, info, logo),
+        ExecuteMsg::UpdateRewardContract { reward_contract } => {
+            execute_update_reward_contract(deps, info, reward_contract)
+        }
+        ExecuteMsg::UpdateMinter { new_minter, cap } => {
+            execute_update_minter(deps, info, new_minter, cap)
+        }
+        ExecuteMsg::ProposeNewAdmin { new_admin } => {
+            execute_propose_new_admin(deps, info, new_admin)
+        }
+        ExecuteMsg::AcceptAdmin {} => execute_accept_admin(deps, info),
+        ExecuteMsg::AddObserver { address } => execute_add_observer(deps, info, address),
+        ExecuteMsg::RemoveObserver { address } => execute_remove_observer(deps, info, address),
+        ExecuteMsg::UpdateObserver { address, enabled } => {
+            execute_update_observer(deps, info, address, enabled)
+        }
+        ExecuteMsg::MigrateLegacyState { limit } => execute_migrate_legacy_state(deps, limit),
     }
 }
 
+pub fn execute_migrate_legacy_state(
+    mut deps: DepsMut,
+    limit: Option<u32>,
+) -> Result<Response, ContractError> {
+    let progress = migrate_legacy_accounts(&mut deps, limit)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "migrate_legacy_state")
+        .add_attributes(legacy_migration_attributes(&progress)))
+}
+
+fn legacy_migration_attributes(progress: &LegacyMigrationProgress) -> Vec<Attribute> {
+    vec![
+        attr("staged_balances", progress.staged_balances.to_string()),
+        attr("staged_allowances", progress.staged_allowances.to_string()),
+        attr("moved_balances", progress.moved_balances.to_string()),
+        attr("moved_allowances", progress.moved_allowances.to_string()),
+        attr("completed", progress.completed.to_string()),
+    ]
+}
+
+/// Observer hooks only reply on error, a failing observer must not block the bETH
+/// transfers, so the error is recorded and the balance change is kept
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
-    cw20_query(deps, _env, msg)
+pub fn reply(_deps: DepsMut, _env: Env, msg: Reply) -> StdResult<Response> {
+    match msg.id {
+        OBSERVER_HOOK_REPLY_ID => {
+            let error = msg.result.into_result().err().unwrap_or_default();
+            Ok(Response::new().add_attributes(vec![
+                attr("action", "observer_hook_failed"),
+                attr("error", error),
+            ]))
+        }
+        _ => Err(StdError::generic_err("invalid reply id")),
+    }
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn migrate(deps: DepsMut, _env: Env, msg: MigrateMsg) -> StdResult<Response> {
-    let mut token_info = TOKEN_INFO.load(deps.storage)?;
+pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
+    // balances and allowances are partial until all of them are moved to the new layout
+    if matches!(
+        msg,
+        QueryMsg::Balance { .. }
+            | QueryMsg::AllAccounts { .. }
+            | QueryMsg::Allowance { .. }
+            | QueryMsg::AllAllowances { .. }
+    ) {
+        assert_legacy_migrated(deps.storage)?;
+    }
 
-    let minter = MinterData {
-        minter: deps.api.addr_canonicalize(&msg.minter)?,
-        cap: None,
+    match msg {
+        QueryMsg::Balance { address } => to_binary(&query_balance(deps, address)?),
+        QueryMsg::TokenInfo {} => to_binary(&query_token_info(deps)?),
+        QueryMsg::Minter {} => to_binary(&query_minter(deps)?),
+        QueryMsg::Allowance { owner, spender } => {
+            to_binary(&query_allowance(deps, owner, spender)?)
+        }
+        QueryMsg::AllAllowances {
+            owner,
+            start_after,
+            limit,
+        } => to_binary(&query_all_allowances(deps, owner, start_after, limit)?),
+        QueryMsg::AllAccounts { start_after, limit } => {
+            to_binary(&query_all_accounts(deps, start_after, limit)?)
+        }
+        QueryMsg::MarketingInfo {} => to_binary(&query_marketing_info(deps)?),
+        QueryMsg::DownloadLogo {} => to_binary(&query_download_logo(deps)?),
+        QueryMsg::TokenConfig {} => to_binary(&query_token_config(deps)?),
+        QueryMsg::Observers {} => to_binary(&query_observers(deps)?),
+    }
+}
+
+pub fn query_token_config(deps: Deps) -> StdResult<TokenConfigResponse> {
+    let reward_contract = deps
+        .api
+        .addr_humanize(&read_reward_contract(deps.storage)?)?;
+    let admin = match read_admin(deps.storage)? {
+        Some(admin) => Some(deps.api.addr_humanize(&admin)?.to_string()),
+        None => None,
     };
-    token_info.mint = Some(minter);
-    TOKEN_INFO.save(deps.storage, &token_info)?;
-    Ok(Response::default())
+    let pending_admin = match read_pending_admin(deps.storage)? {
+        Some(pending_admin) => Some(deps.api.addr_humanize(&pending_admin)?.to_string()),
+        None => None,
+    };
+
+    Ok(TokenConfigResponse {
+        reward_contract: reward_contract.to_string(),
+        admin,
+        pending_admin,
+    })
+}
+
+pub fn query_observers(deps: Deps) -> StdResult<ObserversResponse> {
+    let observers = read_observers(deps.storage)?
+        .into_iter()
+        .map(|(observer, enabled)| {
+            Ok(ObserverResponse {
+                address: deps.api.addr_humanize(&observer)?.to_string(),
+                enabled,
+            })
+        })
+        .collect::<StdResult<Vec<ObserverResponse>>>()?;
+
+    Ok(ObserversResponse { observers })
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn migrate(mut deps: DepsMut, _env: Env, msg: MigrateMsg) -> StdResult<Response> {
+    migrate_contract_version(
+        deps.storage,
+        CONTRACT_NAME,
+        CONTRACT_VERSION,
+        &[CW20_BASE_CONTRACT_NAME],
+    )?;
+
+    // deployments that predate cw20-base keep their state in the cw20_legacy layout
+    let mut res = Response::new();
+    if migrate_legacy_token_info(&mut deps)? {
+        let progress = migrate_legacy_accounts(&mut deps, msg.limit)?;
+        res = res.add_attributes(legacy_migration_attributes(&progress));
+    }
+
+    if let Some(minter) = msg.minter {
+        let mut token_info = TOKEN_INFO.load(deps.storage)?;
+        token_info.mint = Some(MinterData {
+            minter: deps.api.addr_validate(&minter)?,
+            cap: None,
+        });
+        TOKEN_INFO.save(deps.storage, &token_info)?;
+    }
+
+    // deployments that predate the admin role can set it during migration
+    if let Some(admin) = msg.admin {
+        let admin = deps.api.addr_validate(&admin)?;
+        store_admin(deps.storage, &deps.api.addr_canonicalize(admin.as_str())?)?;
+    }
+
+    // the marketing address can only be changed by itself once it is set
+    if let Some(marketing) = msg.marketing {
+        if MARKETING_INFO.may_load(deps.storage)?.is_some() {
+            return Err(StdError::generic_err("marketing info is already set"));
+        }
+        MARKETING_INFO.save(
+            deps.storage,
+            &MarketingInfoResponse {
+                marketing: Some(deps.api.addr_validate(&marketing)?),
+                ..MarketingInfoResponse::default()
+            },
+        )?;
+    }
+
+    Ok(res)
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
     use cosmwasm_std::testing::{mock_dependencies, mock_env, mock_info};
-    use cosmwasm_std::{Addr, Api};
+    use cosmwasm_std::Addr;
+    use cw2::get_contract_version;
     use cw20::MinterResponse;
 
     #[test]
@@ -124,6 +336,8 @@ mod test {
                 cap: None,
             }),
             reward_contract: "reward_contract".to_string(),
+            admin: None,
+            marketing: None,
         };
 
         let info = mock_info("sender", &[]);
@@ -132,17 +346,79 @@ mod test {
 
         //migrate
         let migrate_msg = MigrateMsg {
-            minter: new_minter.to_string(),
+            minter: Some(new_minter.to_string()),
+            admin: None,
+            limit: None,
+            marketing: None,
         };
         let res = migrate(deps.as_mut(), mock_env(), migrate_msg).unwrap();
         assert_eq!(res, Response::default());
 
         let token_info = TOKEN_INFO.load(deps.as_ref().storage).unwrap();
+        assert_eq!(Addr::unchecked(new_minter), token_info.mint.unwrap().minter);
+
+        // the admin is validated like the minter
+        let migrate_msg = MigrateMsg {
+            minter: None,
+            admin: Some("a".to_string()),
+            limit: None,
+            marketing: None,
+        };
+        let res = migrate(deps.as_mut(), mock_env(), migrate_msg);
+        assert!(res.is_err());
+        assert_eq!(read_admin(deps.as_ref().storage).unwrap(), None);
+    }
+
+    #[test]
+    fn migrate_contract_version() {
+        let mut deps = mock_dependencies(&[]);
+        let init_msg = TokenInstantiateMsg {
+            name: "bonded ETH".to_string(),
+            symbol: "BETH".to_string(),
+            decimals: 6,
+            initial_balances: vec![],
+            mint: None,
+            reward_contract: "reward_contract".to_string(),
+            admin: None,
+            marketing: None,
+        };
+        let info = mock_info("sender", &[]);
+        instantiate(deps.as_mut(), mock_env(), info, init_msg).unwrap();
+
+        // the version recorded by cw20-base is overwritten
+        let version = get_contract_version(&deps.storage).unwrap();
+        assert_eq!(version.contract, CONTRACT_NAME);
+        assert_eq!(version.version, CONTRACT_VERSION);
+
+        let migrate_msg = MigrateMsg {
+            minter: None,
+            admin: None,
+            limit: None,
+            marketing: None,
+        };
+
+        // deployments instantiated with cw20_legacy or cw20-base
+        set_contract_version(&mut deps.storage, CW20_BASE_CONTRACT_NAME, "0.8.0").unwrap();
+        migrate(deps.as_mut(), mock_env(), migrate_msg.clone()).unwrap();
+        assert_eq!(get_contract_version(&deps.storage).unwrap(), version);
+
+        set_contract_version(&mut deps.storage, CONTRACT_NAME, "99.0.0").unwrap();
+        let res = migrate(deps.as_mut(), mock_env(), migrate_msg.clone());
+        assert_eq!(
+            res.unwrap_err(),
+            StdError::generic_err(format!(
+                "Cannot downgrade from 99.0.0 to {}",
+                CONTRACT_VERSION
+            ))
+        );
+
+        set_contract_version(&mut deps.storage, "crates.io:other", "0.1.0").unwrap();
+        let res = migrate(deps.as_mut(), mock_env(), migrate_msg);
         assert_eq!(
-            Addr::unchecked(new_minter),
-            deps.api
-                .addr_humanize(&token_info.mint.unwrap().minter)
-                .unwrap()
+            res.unwrap_err(),
+            StdError::generic_err(
+                "Cannot migrate from crates.io:other to crates.io:anchor_beth_token"
+            )
         );
     }
 }
@@ -1,21 +1,25 @@
-use beth::reward::ExecuteMsg::{DecreaseBalance, IncreaseBalance};
+use beth::observer::{BalanceDelta, ObserverExecuteMsg};
+use beth::reward::ExecuteMsg::{BatchTransferBalance, DecreaseBalance, IncreaseBalance};
 use cosmwasm_std::{
-    to_binary, Binary, CosmosMsg, DepsMut, Env, MessageInfo, Response, SubMsg, Uint128, WasmMsg,
+    attr, to_binary, Addr, Binary, CosmosMsg, Deps, DepsMut, Env, MessageInfo, Response, StdError,
+    StdResult, SubMsg, Uint128, WasmMsg,
 };
 
-use crate::state::read_reward_contract;
-use cw20_legacy::allowances::{
-    execute_burn_from as cw20_burn_from, execute_send_from as cw20_send_from,
+use crate::contract::OBSERVER_HOOK_REPLY_ID;
+use crate::state::{read_observers, read_reward_contract, OBSERVER_HOOK_GAS_LIMIT};
+use cw20_base::allowances::{
+    deduct_allowance, execute_burn_from as cw20_burn_from, execute_send_from as cw20_send_from,
     execute_transfer_from as cw20_transfer_from,
 };
-use cw20_legacy::contract::{
+use cw20_base::contract::{
     execute_burn as cw20_burn, execute_mint as cw20_mint, execute_send as cw20_send,
     execute_transfer as cw20_transfer,
 };
-use cw20_legacy::ContractError;
+use cw20_base::state::BALANCES;
+use cw20_base::ContractError;
 
 pub fn execute_transfer(
-    deps: DepsMut,
+    mut deps: DepsMut,
     env: Env,
     info: MessageInfo,
     recipient: String,
@@ -28,7 +32,14 @@ pub fn execute_transfer(
 
     let rcpt_addr = deps.api.addr_validate(&recipient)?;
 
-    let res: Response = cw20_transfer(deps, env, info, recipient, amount)?;
+    let res: Response = cw20_transfer(deps.branch(), env, info, recipient, amount)?;
+    let hooks = balance_change_hooks(
+        deps.as_ref(),
+        vec![
+            (sender.clone(), BalanceDelta::Decrease(amount)),
+            (rcpt_addr.clone(), BalanceDelta::Increase(amount)),
+        ],
+    )?;
     let messages = vec![
         CosmosMsg::Wasm(WasmMsg::Execute {
             contract_addr: reward_contract.to_string(),
@@ -51,11 +62,42 @@ pub fn execute_transfer(
     ];
     Ok(Response::new()
         .add_messages(messages)
+        .add_submessages(hooks)
         .add_attributes(res.attributes))
 }
 
+pub fn execute_batch_transfer(
+    mut deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    transfers: Vec<(String, Uint128)>,
+) -> Result<Response, ContractError> {
+    let reward_contract = deps
+        .api
+        .addr_humanize(&read_reward_contract(deps.storage)?)?;
+
+    let transfers = validate_transfers(&deps, transfers)?;
+    let total_amount = sum_transfers(&transfers)?;
+    move_balances(&mut deps, &info.sender, total_amount, &transfers)?;
+    let hooks = batch_balance_change_hooks(deps.as_ref(), &info.sender, total_amount, &transfers)?;
+
+    Ok(Response::new()
+        .add_message(batch_transfer_balance_msg(
+            &reward_contract,
+            &info.sender,
+            transfers.clone(),
+        )?)
+        .add_submessages(hooks)
+        .add_attributes(vec![
+            attr("action", "batch_transfer"),
+            attr("from", info.sender),
+            attr("amount", total_amount),
+            attr("transfers", transfers.len().to_string()),
+        ]))
+}
+
 pub fn execute_burn(
-    deps: DepsMut,
+    mut deps: DepsMut,
     env: Env,
     info: MessageInfo,
     amount: Uint128,
@@ -65,7 +107,11 @@ pub fn execute_burn(
         .api
         .addr_humanize(&read_reward_contract(deps.storage)?)?;
 
-    let res: Response = cw20_burn(deps, env, info, amount)?;
+    let res: Response = cw20_burn(deps.branch(), env, info, amount)?;
+    let hooks = balance_change_hooks(
+        deps.as_ref(),
+        vec![(sender.clone(), BalanceDelta::Decrease(amount))],
+    )?;
     let messages = vec![CosmosMsg::Wasm(WasmMsg::Execute {
         contract_addr: reward_contract.to_string(),
         msg: to_binary(&DecreaseBalance {
@@ -77,11 +123,12 @@ pub fn execute_burn(
     })];
     Ok(Response::new()
         .add_messages(messages)
+        .add_submessages(hooks)
         .add_attributes(res.attributes))
 }
 
 pub fn execute_mint(
-    deps: DepsMut,
+    mut deps: DepsMut,
     env: Env,
     info: MessageInfo,
     recipient: String,
@@ -91,7 +138,14 @@ pub fn execute_mint(
         .api
         .addr_humanize(&read_reward_contract(deps.storage)?)?;
 
-    let res: Response = cw20_mint(deps, env, info, recipient.clone(), amount)?;
+    let res: Response = cw20_mint(deps.branch(), env, info, recipient.clone(), amount)?;
+    let hooks = balance_change_hooks(
+        deps.as_ref(),
+        vec![(
+            deps.api.addr_validate(&recipient)?,
+            BalanceDelta::Increase(amount),
+        )],
+    )?;
     Ok(Response::new()
         .add_messages(vec![CosmosMsg::Wasm(WasmMsg::Execute {
             contract_addr: reward_contract.to_string(),
@@ -102,11 +156,12 @@ pub fn execute_mint(
             .unwrap(),
             funds: vec![],
         })])
+        .add_submessages(hooks)
         .add_attributes(res.attributes))
 }
 
 pub fn execute_send(
-    deps: DepsMut,
+    mut deps: DepsMut,
     env: Env,
     info: MessageInfo,
     contract: String,
@@ -118,8 +173,18 @@ pub fn execute_send(
         .api
         .addr_humanize(&read_reward_contract(deps.storage)?)?;
 
-    let res: Response = cw20_send(deps, env, info, contract.clone(), amount, msg)?;
-    let messages = vec![
+    let res: Response = cw20_send(deps.branch(), env, info, contract.clone(), amount, msg)?;
+    let hooks = balance_change_hooks(
+        deps.as_ref(),
+        vec![
+            (sender.clone(), BalanceDelta::Decrease(amount)),
+            (
+                deps.api.addr_validate(&contract)?,
+                BalanceDelta::Increase(amount),
+            ),
+        ],
+    )?;
+    let messages = [
         vec![
             SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
                 contract_addr: reward_contract.to_string(),
@@ -140,6 +205,7 @@ pub fn execute_send(
                 funds: vec![],
             })),
         ],
+        hooks,
         res.messages,
     ]
     .concat();
@@ -150,7 +216,7 @@ pub fn execute_send(
 }
 
 pub fn execute_transfer_from(
-    deps: DepsMut,
+    mut deps: DepsMut,
     env: Env,
     info: MessageInfo,
     owner: String,
@@ -163,7 +229,18 @@ pub fn execute_transfer_from(
 
     let valid_owner = deps.api.addr_validate(owner.as_str())?;
 
-    let res: Response = cw20_transfer_from(deps, env, info, owner, recipient.clone(), amount)?;
+    let res: Response =
+        cw20_transfer_from(deps.branch(), env, info, owner, recipient.clone(), amount)?;
+    let hooks = balance_change_hooks(
+        deps.as_ref(),
+        vec![
+            (valid_owner.clone(), BalanceDelta::Decrease(amount)),
+            (
+                deps.api.addr_validate(&recipient)?,
+                BalanceDelta::Increase(amount),
+            ),
+        ],
+    )?;
     let messages = vec![
         CosmosMsg::Wasm(WasmMsg::Execute {
             contract_addr: reward_contract.to_string(),
@@ -186,11 +263,54 @@ pub fn execute_transfer_from(
     ];
     Ok(Response::new()
         .add_messages(messages)
+        .add_submessages(hooks)
         .add_attributes(res.attributes))
 }
 
+pub fn execute_batch_transfer_from(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    owner: String,
+    transfers: Vec<(String, Uint128)>,
+) -> Result<Response, ContractError> {
+    let reward_contract = deps
+        .api
+        .addr_humanize(&read_reward_contract(deps.storage)?)?;
+
+    let valid_owner = deps.api.addr_validate(owner.as_str())?;
+    let transfers = validate_transfers(&deps, transfers)?;
+
+    // the allowance is deducted once for the whole batch
+    let total_amount = sum_transfers(&transfers)?;
+    deduct_allowance(
+        deps.storage,
+        &valid_owner,
+        &info.sender,
+        &env.block,
+        total_amount,
+    )?;
+    move_balances(&mut deps, &valid_owner, total_amount, &transfers)?;
+    let hooks = batch_balance_change_hooks(deps.as_ref(), &valid_owner, total_amount, &transfers)?;
+
+    Ok(Response::new()
+        .add_message(batch_transfer_balance_msg(
+            &reward_contract,
+            &valid_owner,
+            transfers.clone(),
+        )?)
+        .add_submessages(hooks)
+        .add_attributes(vec![
+            attr("action", "batch_transfer_from"),
+            attr("from", valid_owner),
+            attr("by", info.sender),
+            attr("amount", total_amount),
+            attr("transfers", transfers.len().to_string()),
+        ]))
+}
+
 pub fn execute_burn_from(
-    deps: DepsMut,
+    mut deps: DepsMut,
     env: Env,
     info: MessageInfo,
     owner: String,
@@ -202,7 +322,11 @@ pub fn execute_burn_from(
 
     let valid_owner = deps.api.addr_validate(owner.as_str())?;
 
-    let res: Response = cw20_burn_from(deps, env, info, owner, amount)?;
+    let res: Response = cw20_burn_from(deps.branch(), env, info, owner, amount)?;
+    let hooks = balance_change_hooks(
+        deps.as_ref(),
+        vec![(valid_owner.clone(), BalanceDelta::Decrease(amount))],
+    )?;
     let messages = vec![CosmosMsg::Wasm(WasmMsg::Execute {
         contract_addr: reward_contract.to_string(),
         msg: to_binary(&DecreaseBalance {
@@ -214,11 +338,12 @@ pub fn execute_burn_from(
     })];
     Ok(Response::new()
         .add_messages(messages)
+        .add_submessages(hooks)
         .add_attributes(res.attributes))
 }
 
 pub fn execute_send_from(
-    deps: DepsMut,
+    mut deps: DepsMut,
     env: Env,
     info: MessageInfo,
     owner: String,
@@ -232,8 +357,26 @@ pub fn execute_send_from(
 
     let valid_owner = deps.api.addr_validate(owner.as_str())?;
 
-    let res: Response = cw20_send_from(deps, env, info, owner, contract.clone(), amount, msg)?;
-    let messages = vec![
+    let res: Response = cw20_send_from(
+        deps.branch(),
+        env,
+        info,
+        owner,
+        contract.clone(),
+        amount,
+        msg,
+    )?;
+    let hooks = balance_change_hooks(
+        deps.as_ref(),
+        vec![
+            (valid_owner.clone(), BalanceDelta::Decrease(amount)),
+            (
+                deps.api.addr_validate(&contract)?,
+                BalanceDelta::Increase(amount),
+            ),
+        ],
+    )?;
+    let messages = [
         vec![
             SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
                 contract_addr: reward_contract.to_string(),
@@ -254,6 +397,7 @@ pub fn execute_send_from(
                 funds: vec![],
             })),
         ],
+        hooks,
         res.messages,
     ]
     .concat();
@@ -262,3 +406,161 @@ pub fn execute_send_from(
         .add_submessages(messages)
         .add_attributes(res.attributes))
 }
+
+fn validate_transfers(
+    deps: &DepsMut,
+    transfers: Vec<(String, Uint128)>,
+) -> Result<Vec<(Addr, Uint128)>, ContractError> {
+    if transfers.is_empty() {
+        return Err(ContractError::Std(StdError::generic_err(
+            "transfers cannot be empty",
+        )));
+    }
+
+    transfers
+        .into_iter()
+        .map(|(recipient, amount)| {
+            if amount.is_zero() {
+                return Err(ContractError::InvalidZeroAmount {});
+            }
+            Ok((deps.api.addr_validate(&recipient)?, amount))
+        })
+        .collect()
+}
+
+fn sum_transfers(transfers: &[(Addr, Uint128)]) -> StdResult<Uint128> {
+    let mut total_amount = Uint128::zero();
+    for (_, amount) in transfers.iter() {
+        total_amount = total_amount.checked_add(*amount)?;
+    }
+    Ok(total_amount)
+}
+
+/// Debits `from` once with the sum of the transfers and credits every recipient
+fn move_balances(
+    deps: &mut DepsMut,
+    from: &Addr,
+    total_amount: Uint128,
+    transfers: &[(Addr, Uint128)],
+) -> StdResult<()> {
+    BALANCES.update(
+        deps.storage,
+        from,
+        |balance: Option<Uint128>| -> StdResult<_> {
+            Ok(balance.unwrap_or_default().checked_sub(total_amount)?)
+        },
+    )?;
+    for (recipient, amount) in transfers.iter() {
+        BALANCES.update(
+            deps.storage,
+            recipient,
+            |balance: Option<Uint128>| -> StdResult<_> { Ok(balance.unwrap_or_default() + amount) },
+        )?;
+    }
+
+    Ok(())
+}
+
+fn batch_transfer_balance_msg(
+    reward_contract: &Addr,
+    from: &Addr,
+    transfers: Vec<(Addr, Uint128)>,
+) -> StdResult<CosmosMsg> {
+    Ok(CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr: reward_contract.to_string(),
+        msg: to_binary(&BatchTransferBalance {
+            from: from.to_string(),
+            transfers: transfers
+                .into_iter()
+                .map(|(recipient, amount)| (recipient.to_string(), amount))
+                .collect(),
+        })?,
+        funds: vec![],
+    }))
+}
+
+/// Builds a `BalanceChangeHook` for every enabled observer and every account whose balance
+/// changed, `new_balance` is read after all the changes are stored. A failing hook is
+/// reported in `reply` and does not revert the balance change.
+fn balance_change_hooks(deps: Deps, changes: Vec<(Addr, BalanceDelta)>) -> StdResult<Vec<SubMsg>> {
+    let observers = read_observers(deps.storage)?
+        .into_iter()
+        .filter(|(_, enabled)| *enabled)
+        .map(|(observer, _)| deps.api.addr_humanize(&observer))
+        .collect::<StdResult<Vec<Addr>>>()?;
+    if observers.is_empty() {
+        return Ok(vec![]);
+    }
+
+    let mut hooks = vec![];
+    for (address, delta) in net_balance_changes(changes) {
+        let new_balance = BALANCES
+            .may_load(deps.storage, &address)?
+            .unwrap_or_default();
+        let msg = to_binary(&ObserverExecuteMsg::BalanceChangeHook {
+            address: address.to_string(),
+            delta,
+            new_balance,
+        })?;
+        for observer in observers.iter() {
+            hooks.push(
+                SubMsg::reply_on_error(
+                    CosmosMsg::Wasm(WasmMsg::Execute {
+                        contract_addr: observer.to_string(),
+                        msg: msg.clone(),
+                        funds: vec![],
+                    }),
+                    OBSERVER_HOOK_REPLY_ID,
+                )
+                .with_gas_limit(OBSERVER_HOOK_GAS_LIMIT),
+            );
+        }
+    }
+    Ok(hooks)
+}
+
+/// Sums the changes of every account, in the order the accounts first appear,
+/// and drops the accounts whose balance is unchanged (e.g. self transfers)
+fn net_balance_changes(changes: Vec<(Addr, BalanceDelta)>) -> Vec<(Addr, BalanceDelta)> {
+    let mut totals: Vec<(Addr, Uint128, Uint128)> = vec![];
+    for (address, delta) in changes {
+        let index = match totals.iter().position(|(a, _, _)| *a == address) {
+            Some(index) => index,
+            None => {
+                totals.push((address, Uint128::zero(), Uint128::zero()));
+                totals.len() - 1
+            }
+        };
+        match delta {
+            BalanceDelta::Increase(amount) => totals[index].1 += amount,
+            BalanceDelta::Decrease(amount) => totals[index].2 += amount,
+        }
+    }
+
+    totals
+        .into_iter()
+        .filter(|(_, increase, decrease)| increase != decrease)
+        .map(|(address, increase, decrease)| {
+            if increase > decrease {
+                (address, BalanceDelta::Increase(increase - decrease))
+            } else {
+                (address, BalanceDelta::Decrease(decrease - increase))
+            }
+        })
+        .collect()
+}
+
+fn batch_balance_change_hooks(
+    deps: Deps,
+    from: &Addr,
+    total_amount: Uint128,
+    transfers: &[(Addr, Uint128)],
+) -> StdResult<Vec<SubMsg>> {
+    let mut changes = vec![(from.clone(), BalanceDelta::Decrease(total_amount))];
+    changes.extend(
+        transfers
+            .iter()
+            .map(|(recipient, amount)| (recipient.clone(), BalanceDelta::Increase(*amount))),
+    );
+    balance_change_hooks(deps, changes)
+}
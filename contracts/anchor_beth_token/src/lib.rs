@@ -2,7 +2,9 @@ pub mod contract;
 pub mod msg;
 pub mod state;
 
+mod admin;
 mod handler;
+mod migration;
 
 #[cfg(test)]
 mod testing;
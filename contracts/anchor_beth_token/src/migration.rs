@@ -0,0 +1,217 @@
+use cosmwasm_std::{CanonicalAddr, DepsMut, Order, StdError, StdResult, Storage};
+use cw_storage_plus::Bound;
+
+use crate::state::{
+    read_legacy_migration, remove_legacy_migration, store_legacy_migration, LegacyMigration,
+    LegacyMigrationStep, STAGED_ALLOWANCES, STAGED_BALANCES,
+};
+use cw20_base::state::{
+    MinterData, TokenInfo, ALLOWANCES, BALANCES, TOKEN_INFO as BASE_TOKEN_INFO,
+};
+use cw20_legacy::state::{
+    ALLOWANCES as LEGACY_ALLOWANCES, BALANCES as LEGACY_BALANCES, TOKEN_INFO as LEGACY_TOKEN_INFO,
+};
+
+/// Moves the token info from the cw20_legacy layout and schedules the migration
+/// of balances and allowances. Returns false if there is no legacy state.
+pub fn migrate_legacy_token_info(deps: &mut DepsMut) -> StdResult<bool> {
+    let legacy_info = match LEGACY_TOKEN_INFO.may_load(deps.storage)? {
+        Some(info) => info,
+        None => return Ok(false),
+    };
+
+    let mint = match legacy_info.mint {
+        Some(m) => Some(MinterData {
+            minter: deps.api.addr_humanize(&m.minter)?,
+            cap: m.cap,
+        }),
+        None => None,
+    };
+    BASE_TOKEN_INFO.save(
+        deps.storage,
+        &TokenInfo {
+            name: legacy_info.name,
+            symbol: legacy_info.symbol,
+            decimals: legacy_info.decimals,
+            total_supply: legacy_info.total_supply,
+            mint,
+        },
+    )?;
+    LEGACY_TOKEN_INFO.remove(deps.storage);
+
+    store_legacy_migration(
+        deps.storage,
+        &LegacyMigration {
+            step: LegacyMigrationStep::StageBalances,
+            cursor: None,
+        },
+    )?;
+    Ok(true)
+}
+
+/// Number of legacy entries handled by each step of a migration batch
+#[derive(Debug, Default, PartialEq)]
+pub struct LegacyMigrationProgress {
+    pub staged_balances: usize,
+    pub staged_allowances: usize,
+    pub moved_balances: usize,
+    pub moved_allowances: usize,
+    pub completed: bool,
+}
+
+/// Moves up to `limit` legacy balances and then legacy allowances to the cw20-base layout.
+/// Everything is moved if `limit` is not set. Returns the number of entries staged and
+/// moved by each step, and whether the migration is complete.
+///
+/// cw20_legacy and cw20-base share the same namespaces, so the legacy entries are first
+/// staged in namespaces of their own and only then moved to the cw20-base layout. This
+/// way every batch iterates only over entries that are still to be migrated.
+pub fn migrate_legacy_accounts(
+    deps: &mut DepsMut,
+    limit: Option<u32>,
+) -> StdResult<LegacyMigrationProgress> {
+    let mut progress = LegacyMigrationProgress::default();
+    let mut migration = match read_legacy_migration(deps.storage)? {
+        Some(migration) => migration,
+        None => {
+            progress.completed = true;
+            return Ok(progress);
+        }
+    };
+    let mut remaining = limit.map_or(usize::MAX, |limit| limit as usize);
+
+    while remaining > 0 && migration.step != LegacyMigrationStep::Done {
+        let start = migration.cursor.take().map(Bound::exclusive);
+        let (count, last) = match migration.step {
+            LegacyMigrationStep::StageBalances => stage_balances(deps.storage, start, remaining)?,
+            LegacyMigrationStep::StageAllowances => {
+                stage_allowances(deps.storage, start, remaining)?
+            }
+            LegacyMigrationStep::MoveBalances => move_balances(deps, start, remaining)?,
+            LegacyMigrationStep::MoveAllowances => move_allowances(deps, start, remaining)?,
+            LegacyMigrationStep::Done => unreachable!(),
+        };
+        match migration.step {
+            LegacyMigrationStep::StageBalances => progress.staged_balances += count,
+            LegacyMigrationStep::StageAllowances => progress.staged_allowances += count,
+            LegacyMigrationStep::MoveBalances => progress.moved_balances += count,
+            LegacyMigrationStep::MoveAllowances => progress.moved_allowances += count,
+            LegacyMigrationStep::Done => unreachable!(),
+        }
+
+        remaining -= count;
+        if remaining > 0 {
+            migration.step = migration.step.next();
+        } else {
+            migration.cursor = last;
+        }
+    }
+
+    progress.completed = migration.step == LegacyMigrationStep::Done;
+    if progress.completed {
+        remove_legacy_migration(deps.storage);
+    } else {
+        store_legacy_migration(deps.storage, &migration)?;
+    }
+
+    Ok(progress)
+}
+
+fn stage_balances(
+    storage: &mut dyn Storage,
+    start: Option<Bound>,
+    limit: usize,
+) -> StdResult<(usize, Option<Vec<u8>>)> {
+    let batch = LEGACY_BALANCES
+        .range(storage, start, None, Order::Ascending)
+        .take(limit)
+        .collect::<StdResult<Vec<_>>>()?;
+
+    for (key, balance) in batch.iter() {
+        LEGACY_BALANCES.remove(storage, key);
+        STAGED_BALANCES.save(storage, key, balance)?;
+    }
+
+    Ok((batch.len(), batch.last().map(|(key, _)| key.clone())))
+}
+
+fn stage_allowances(
+    storage: &mut dyn Storage,
+    start: Option<Bound>,
+    limit: usize,
+) -> StdResult<(usize, Option<Vec<u8>>)> {
+    let batch = LEGACY_ALLOWANCES
+        .range(storage, start, None, Order::Ascending)
+        .take(limit)
+        .collect::<StdResult<Vec<_>>>()?;
+
+    for (key, allowance) in batch.iter() {
+        LEGACY_ALLOWANCES.remove(storage, split_pair_key(key)?);
+        STAGED_ALLOWANCES.save(storage, key, allowance)?;
+    }
+
+    Ok((batch.len(), batch.last().map(|(key, _)| key.clone())))
+}
+
+fn move_balances(
+    deps: &mut DepsMut,
+    start: Option<Bound>,
+    limit: usize,
+) -> StdResult<(usize, Option<Vec<u8>>)> {
+    let batch = STAGED_BALANCES
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .collect::<StdResult<Vec<_>>>()?;
+
+    for (key, balance) in batch.iter() {
+        let address = deps
+            .api
+            .addr_humanize(&CanonicalAddr::from(key.as_slice()))?;
+        STAGED_BALANCES.remove(deps.storage, key);
+        BALANCES.save(deps.storage, &address, balance)?;
+    }
+
+    Ok((batch.len(), batch.last().map(|(key, _)| key.clone())))
+}
+
+fn move_allowances(
+    deps: &mut DepsMut,
+    start: Option<Bound>,
+    limit: usize,
+) -> StdResult<(usize, Option<Vec<u8>>)> {
+    let batch = STAGED_ALLOWANCES
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .collect::<StdResult<Vec<_>>>()?;
+
+    for (key, allowance) in batch.iter() {
+        let (owner_raw, spender_raw) = split_pair_key(key)?;
+        let owner = deps.api.addr_humanize(&CanonicalAddr::from(owner_raw))?;
+        let spender = deps.api.addr_humanize(&CanonicalAddr::from(spender_raw))?;
+        STAGED_ALLOWANCES.remove(deps.storage, key);
+        ALLOWANCES.save(deps.storage, (&owner, &spender), allowance)?;
+    }
+
+    Ok((batch.len(), batch.last().map(|(key, _)| key.clone())))
+}
+
+pub fn assert_legacy_migrated(storage: &dyn Storage) -> StdResult<()> {
+    if read_legacy_migration(storage)?.is_some() {
+        return Err(StdError::generic_err(
+            "legacy state migration is in progress",
+        ));
+    }
+    Ok(())
+}
+
+// composite keys are stored as the length-prefixed first key followed by the second key
+fn split_pair_key(key: &[u8]) -> StdResult<(&[u8], &[u8])> {
+    if key.len() < 2 {
+        return Err(StdError::generic_err("invalid allowance key"));
+    }
+    let owner_len = u16::from_be_bytes([key[0], key[1]]) as usize;
+    if key.len() < 2 + owner_len {
+        return Err(StdError::generic_err("invalid allowance key"));
+    }
+    Ok((&key[2..2 + owner_len], &key[2 + owner_len..]))
+}
@@ -1,4 +1,6 @@
-use cw20::{Cw20Coin, MinterResponse};
+use cosmwasm_std::{Binary, Uint128};
+use cw20::{Cw20Coin, Expiration, Logo, MinterResponse};
+use cw20_base::msg::InstantiateMarketingInfo;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 #[derive(Serialize, Deserialize, JsonSchema)]
@@ -9,9 +11,187 @@ pub struct TokenInstantiateMsg {
     pub initial_balances: Vec<Cw20Coin>,
     pub mint: Option<MinterResponse>,
     pub reward_contract: String,
+    /// account allowed to rotate the reward contract and the minter,
+    /// the token configuration is immutable if it is not set
+    pub admin: Option<String>,
+    pub marketing: Option<InstantiateMarketingInfo>,
 }
 
-#[derive(Serialize, Deserialize, JsonSchema)]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ExecuteMsg {
+    /// Transfer is a base message to move tokens to another account without triggering actions
+    Transfer { recipient: String, amount: Uint128 },
+    /// Move tokens to several accounts, the sender is debited once with the sum of the amounts
+    BatchTransfer { transfers: Vec<(String, Uint128)> },
+    /// Burn is a base message to destroy tokens forever
+    Burn { amount: Uint128 },
+    /// Send is a base message to transfer tokens to a contract and trigger an action
+    /// on the receiving contract.
+    Send {
+        contract: String,
+        amount: Uint128,
+        msg: Binary,
+    },
+    /// Only with the "mintable" extension. If authorized, creates amount new tokens
+    /// and adds to the recipient balance.
+    Mint { recipient: String, amount: Uint128 },
+    /// Only with "approval" extension. Allows spender to access an additional amount tokens
+    /// from the owner's (env.sender) account. If expires is Some(), overwrites current allowance
+    /// expiration with this one.
+    IncreaseAllowance {
+        spender: String,
+        amount: Uint128,
+        expires: Option<Expiration>,
+    },
+    /// Only with "approval" extension. Lowers the spender's access of tokens
+    /// from the owner's (env.sender) account by amount. If expires is Some(), overwrites current
+    /// allowance expiration with this one.
+    DecreaseAllowance {
+        spender: String,
+        amount: Uint128,
+        expires: Option<Expiration>,
+    },
+    /// Only with "approval" extension. Transfers amount tokens from owner -> recipient
+    /// if `env.sender` has sufficient pre-approval.
+    TransferFrom {
+        owner: String,
+        recipient: String,
+        amount: Uint128,
+    },
+    /// Only with "approval" extension. Transfers tokens from owner to several recipients
+    /// if `env.sender` has sufficient pre-approval for the sum of the amounts.
+    BatchTransferFrom {
+        owner: String,
+        transfers: Vec<(String, Uint128)>,
+    },
+    /// Only with "approval" extension. Sends amount tokens from owner -> contract
+    /// if `env.sender` has sufficient pre-approval.
+    SendFrom {
+        owner: String,
+        contract: String,
+        amount: Uint128,
+        msg: Binary,
+    },
+    /// Only with "approval" extension. Destroys tokens forever
+    BurnFrom { owner: String, amount: Uint128 },
+    /// Only with the "marketing" extension. If authorized, updates marketing metadata.
+    /// Setting None/null for any of these will leave it unchanged.
+    /// Setting Some("") will clear this field on the contract storage
+    UpdateMarketing {
+        /// A URL pointing to the project behind this token.
+        project: Option<String>,
+        /// A longer description of the token and it's utility. Designed for tooltips or such
+        description: Option<String>,
+        /// The address (if any) who can update this data structure
+        marketing: Option<String>,
+    },
+    /// If set as the "marketing" role on the contract, upload a new URL, SVG, or PNG for the token
+    UploadLogo(Logo),
+
+    ////////////////////
+    /// Admin's operations
+    ///////////////////
+
+    /// Set the reward contract that is notified about balance changes
+    UpdateRewardContract { reward_contract: String },
+    /// Replace the minter and its cap
+    UpdateMinter {
+        new_minter: String,
+        cap: Option<Uint128>,
+    },
+    /// Propose a new admin, the new admin has to accept the role
+    ProposeNewAdmin { new_admin: String },
+    /// Accept the admin role, can only be executed by the proposed admin
+    AcceptAdmin {},
+    /// Register a contract that receives `BalanceChangeHook` on every balance change
+    AddObserver { address: String },
+    /// Unregister an observer contract
+    RemoveObserver { address: String },
+    /// Pause or resume the hooks of an observer contract
+    UpdateObserver { address: String, enabled: bool },
+
+    /// Move the next batch of balances and allowances from the cw20_legacy layout,
+    /// every other message is rejected until the migration is complete
+    MigrateLegacyState { limit: Option<u32> },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum QueryMsg {
+    /// Returns the current balance of the given address, 0 if unset.
+    /// Return type: BalanceResponse.
+    Balance { address: String },
+    /// Returns metadata on the contract - name, decimals, supply, etc.
+    /// Return type: TokenInfoResponse.
+    TokenInfo {},
+    /// Only with "mintable" extension.
+    /// Returns who can mint and how much.
+    /// Return type: MinterResponse.
+    Minter {},
+    /// Only with "allowance" extension.
+    /// Returns how much spender can use from owner account, 0 if unset.
+    /// Return type: AllowanceResponse.
+    Allowance { owner: String, spender: String },
+    /// Only with "enumerable" extension (and "allowances")
+    /// Returns all allowances this owner has approved. Supports pagination.
+    /// Return type: AllAllowancesResponse.
+    AllAllowances {
+        owner: String,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    /// Only with "enumerable" extension
+    /// Returns all accounts that have balances. Supports pagination.
+    /// Return type: AllAccountsResponse.
+    AllAccounts {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    /// Only with "marketing" extension
+    /// Returns more metadata on the contract to display in the client:
+    /// description, logo, project url, etc.
+    /// Return type: MarketingInfoResponse
+    MarketingInfo {},
+    /// Only with "marketing" extension
+    /// Downloads the embedded logo data (if stored on chain). Errors if no logo data is stored for this
+    /// contract.
+    /// Return type: DownloadLogoResponse.
+    DownloadLogo {},
+    /// Returns the reward contract and the admin.
+    /// Return type: TokenConfigResponse.
+    TokenConfig {},
+    /// Returns the registered observer contracts.
+    /// Return type: ObserversResponse.
+    Observers {},
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct TokenConfigResponse {
+    pub reward_contract: String,
+    pub admin: Option<String>,
+    pub pending_admin: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ObserverResponse {
+    pub address: String,
+    pub enabled: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ObserversResponse {
+    pub observers: Vec<ObserverResponse>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct MigrateMsg {
-    pub minter: String,
+    pub minter: Option<String>,
+    pub admin: Option<String>,
+    /// max number of cw20_legacy balances and allowances moved during the migration,
+    /// the rest can be moved with `MigrateLegacyState`. Everything is moved if unset.
+    pub limit: Option<u32>,
+    /// marketing address for deployments that were created without marketing info,
+    /// it can then set the project, description and logo
+    pub marketing: Option<String>,
 }
@@ -1,7 +1,20 @@
-use cosmwasm_std::{CanonicalAddr, StdResult, Storage};
-use cosmwasm_storage::{singleton, singleton_read};
+use cosmwasm_std::{CanonicalAddr, Order, StdResult, Storage, Uint128};
+use cosmwasm_storage::{bucket, bucket_read, singleton, singleton_read};
+use cw20::AllowanceResponse;
+use cw_storage_plus::Map;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
 
 const REWARD_CONTRACT_KEY: &[u8] = b"reward_contract";
+const ADMIN_KEY: &[u8] = b"admin";
+const PENDING_ADMIN_KEY: &[u8] = b"pending_admin";
+const LEGACY_MIGRATION_KEY: &[u8] = b"legacy_migration";
+const PREFIX_OBSERVERS: &[u8] = b"observers";
+
+/// Max number of observer contracts, every observer receives a message per balance change
+pub const MAX_OBSERVERS: usize = 10;
+/// Gas available to every observer hook, so one observer can not consume the gas of the transfer
+pub const OBSERVER_HOOK_GAS_LIMIT: u64 = 200_000;
 
 pub fn read_reward_contract(storage: &dyn Storage) -> StdResult<CanonicalAddr> {
     singleton_read(storage, REWARD_CONTRACT_KEY).load()
@@ -13,3 +26,102 @@ pub fn store_reward_contract(
 ) -> StdResult<()> {
     singleton(storage, REWARD_CONTRACT_KEY).save(reward_contract)
 }
+
+pub fn read_admin(storage: &dyn Storage) -> StdResult<Option<CanonicalAddr>> {
+    singleton_read(storage, ADMIN_KEY).may_load()
+}
+
+pub fn store_admin(storage: &mut dyn Storage, admin: &CanonicalAddr) -> StdResult<()> {
+    singleton(storage, ADMIN_KEY).save(admin)
+}
+
+pub fn read_pending_admin(storage: &dyn Storage) -> StdResult<Option<CanonicalAddr>> {
+    singleton_read(storage, PENDING_ADMIN_KEY).may_load()
+}
+
+pub fn store_pending_admin(storage: &mut dyn Storage, admin: &CanonicalAddr) -> StdResult<()> {
+    singleton(storage, PENDING_ADMIN_KEY).save(admin)
+}
+
+pub fn remove_pending_admin(storage: &mut dyn Storage) {
+    singleton::<CanonicalAddr>(storage, PENDING_ADMIN_KEY).remove()
+}
+
+/// Legacy balances and allowances moved out of the namespaces shared with cw20-base,
+/// keyed by their cw20_legacy keys until they are moved to the cw20-base layout
+pub const STAGED_BALANCES: Map<&[u8], Uint128> = Map::new("staged_balance");
+pub const STAGED_ALLOWANCES: Map<&[u8], AllowanceResponse> = Map::new("staged_allowance");
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum LegacyMigrationStep {
+    StageBalances,
+    StageAllowances,
+    MoveBalances,
+    MoveAllowances,
+    Done,
+}
+
+impl LegacyMigrationStep {
+    pub fn next(self) -> Self {
+        match self {
+            LegacyMigrationStep::StageBalances => LegacyMigrationStep::StageAllowances,
+            LegacyMigrationStep::StageAllowances => LegacyMigrationStep::MoveBalances,
+            LegacyMigrationStep::MoveBalances => LegacyMigrationStep::MoveAllowances,
+            LegacyMigrationStep::MoveAllowances | LegacyMigrationStep::Done => {
+                LegacyMigrationStep::Done
+            }
+        }
+    }
+}
+
+/// Progress of moving balances and allowances from the cw20_legacy layout,
+/// it is only stored while the migration is not complete
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct LegacyMigration {
+    pub step: LegacyMigrationStep,
+    /// Last key handled by the current step
+    pub cursor: Option<Vec<u8>>,
+}
+
+pub fn read_legacy_migration(storage: &dyn Storage) -> StdResult<Option<LegacyMigration>> {
+    singleton_read(storage, LEGACY_MIGRATION_KEY).may_load()
+}
+
+pub fn store_legacy_migration(
+    storage: &mut dyn Storage,
+    migration: &LegacyMigration,
+) -> StdResult<()> {
+    singleton(storage, LEGACY_MIGRATION_KEY).save(migration)
+}
+
+pub fn remove_legacy_migration(storage: &mut dyn Storage) {
+    singleton::<LegacyMigration>(storage, LEGACY_MIGRATION_KEY).remove()
+}
+
+// This is similar to HashMap<observer's address, enabled>
+pub fn store_observer(
+    storage: &mut dyn Storage,
+    observer: &CanonicalAddr,
+    enabled: bool,
+) -> StdResult<()> {
+    bucket(storage, PREFIX_OBSERVERS).save(observer.as_slice(), &enabled)
+}
+
+pub fn read_observer(storage: &dyn Storage, observer: &CanonicalAddr) -> StdResult<Option<bool>> {
+    bucket_read(storage, PREFIX_OBSERVERS).may_load(observer.as_slice())
+}
+
+pub fn remove_observer(storage: &mut dyn Storage, observer: &CanonicalAddr) {
+    bucket::<bool>(storage, PREFIX_OBSERVERS).remove(observer.as_slice())
+}
+
+pub fn read_observers(storage: &dyn Storage) -> StdResult<Vec<(CanonicalAddr, bool)>> {
+    bucket_read(storage, PREFIX_OBSERVERS)
+        .range(None, None, Order::Ascending)
+        .map(|item| {
+            let (k, enabled) = item?;
+            Ok((CanonicalAddr::from(k), enabled))
+        })
+        .collect()
+}
@@ -1,22 +1,39 @@
-use cosmwasm_std::testing::{mock_dependencies, mock_env, mock_info};
+use beth::mock_querier::mock_dependencies;
+use cosmwasm_std::testing::{mock_env, mock_info};
 use cosmwasm_std::{
-    coins, to_binary, Api, CosmosMsg, DepsMut, OwnedDeps, Querier, Storage, SubMsg, Uint128,
-    WasmMsg,
+    attr, coins, from_binary, to_binary, Addr, Api, ContractResult, CosmosMsg, DepsMut, Order,
+    OwnedDeps, Querier, Reply, StdError, Storage, SubMsg, Uint128, WasmMsg,
 };
 
-use beth::reward::ExecuteMsg::{DecreaseBalance, IncreaseBalance};
-use cw20::{Cw20ReceiveMsg, MinterResponse, TokenInfoResponse};
-use cw20_legacy::contract::{query_minter, query_token_info};
-use cw20_legacy::msg::ExecuteMsg;
+use beth::observer::{BalanceDelta, ObserverExecuteMsg};
+use beth::reward::ExecuteMsg::{BatchTransferBalance, DecreaseBalance, IncreaseBalance};
+use cw20::{
+    Cw20Coin, Cw20ReceiveMsg, DownloadLogoResponse, EmbeddedLogo, Logo, LogoInfo,
+    MarketingInfoResponse, MinterResponse, TokenInfoResponse,
+};
+use cw20_base::allowances::query_allowance;
+use cw20_base::contract::{query_balance, query_minter, query_token_info};
+use cw20_base::msg::InstantiateMarketingInfo;
+use cw20_base::ContractError;
 
-use crate::contract::{execute, instantiate};
-use crate::msg::TokenInstantiateMsg;
-use crate::state::read_reward_contract;
+use crate::contract::{execute, instantiate, migrate, query, reply, OBSERVER_HOOK_REPLY_ID};
+use crate::msg::{
+    ExecuteMsg, MigrateMsg, ObserverResponse, ObserversResponse, QueryMsg, TokenConfigResponse,
+    TokenInstantiateMsg,
+};
+use crate::state::{
+    read_legacy_migration, read_reward_contract, store_reward_contract, MAX_OBSERVERS,
+    OBSERVER_HOOK_GAS_LIMIT, STAGED_ALLOWANCES, STAGED_BALANCES,
+};
 
 use std::borrow::BorrowMut;
 
 const MOCK_REWARD_CONTRACT_ADDR: &str = "bethreward0000";
 const MOCK_MINTER_ADDR: &str = "minter0000";
+const MOCK_ADMIN_ADDR: &str = "admin0000";
+const MOCK_MARKETING_ADDR: &str = "marketing0000";
+const MOCK_OBSERVER_ADDR: &str = "observer0000";
+const PNG_HEADER: [u8; 8] = [0x89, b'P', b'N', b'G', 0x0d, 0x0a, 0x1a, 0x0a];
 
 // this will set up the init for other tests
 fn do_init_with_minter<S: Storage, A: Api, Q: Querier>(
@@ -40,6 +57,8 @@ fn _do_init<S: Storage, A: Api, Q: Querier>(
         initial_balances: vec![],
         mint: mint.clone(),
         reward_contract,
+        admin: Some(MOCK_ADMIN_ADDR.to_string()),
+        marketing: None,
     };
 
     let info = mock_info(MOCK_REWARD_CONTRACT_ADDR, &[]);
@@ -83,6 +102,8 @@ fn proper_initialization() {
         initial_balances: vec![],
         mint: None,
         reward_contract: reward_contract.clone(),
+        admin: None,
+        marketing: None,
     };
     let info = mock_info(&reward_contract, &[]);
     let res = instantiate(deps.as_mut(), mock_env(), info, init_msg).unwrap();
@@ -198,6 +219,159 @@ fn transfer_from() {
     );
 }
 
+#[test]
+fn batch_transfer() {
+    let mut deps = mock_dependencies(&coins(2, "token"));
+    let addr1 = "addr0001".to_string();
+    let addr2 = "addr0002".to_string();
+    let addr3 = "addr0003".to_string();
+    let amount1 = Uint128::from(100u128);
+
+    do_init_with_minter(deps.borrow_mut(), MOCK_MINTER_ADDR.to_string(), None);
+    do_mint(deps.as_mut(), addr1.clone(), amount1);
+
+    let info = mock_info(&addr1, &[]);
+    let msg = ExecuteMsg::BatchTransfer { transfers: vec![] };
+    let res = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap_err();
+    assert_eq!(
+        res,
+        ContractError::Std(StdError::generic_err("transfers cannot be empty"))
+    );
+
+    let msg = ExecuteMsg::BatchTransfer {
+        transfers: vec![
+            (addr2.clone(), Uint128::new(10u128)),
+            (addr3.clone(), Uint128::zero()),
+        ],
+    };
+    let res = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap_err();
+    assert_eq!(res, ContractError::InvalidZeroAmount {});
+
+    // the sum of the transfers is above the balance
+    let msg = ExecuteMsg::BatchTransfer {
+        transfers: vec![
+            (addr2.clone(), Uint128::new(60u128)),
+            (addr3.clone(), Uint128::new(50u128)),
+        ],
+    };
+    let res = execute(deps.as_mut(), mock_env(), info.clone(), msg);
+    assert!(matches!(
+        res,
+        Err(ContractError::Std(StdError::Overflow { .. }))
+    ));
+
+    let msg = ExecuteMsg::BatchTransfer {
+        transfers: vec![
+            (addr2.clone(), Uint128::new(30u128)),
+            (addr3.clone(), Uint128::new(20u128)),
+        ],
+    };
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: MOCK_REWARD_CONTRACT_ADDR.to_string(),
+            msg: to_binary(&BatchTransferBalance {
+                from: addr1.clone(),
+                transfers: vec![
+                    (addr2.clone(), Uint128::new(30u128)),
+                    (addr3.clone(), Uint128::new(20u128)),
+                ],
+            })
+            .unwrap(),
+            funds: vec![],
+        }))]
+    );
+
+    assert_eq!(
+        query_balance(deps.as_ref(), addr1).unwrap().balance,
+        Uint128::new(50u128)
+    );
+    assert_eq!(
+        query_balance(deps.as_ref(), addr2).unwrap().balance,
+        Uint128::new(30u128)
+    );
+    assert_eq!(
+        query_balance(deps.as_ref(), addr3).unwrap().balance,
+        Uint128::new(20u128)
+    );
+}
+
+#[test]
+fn batch_transfer_from() {
+    let mut deps = mock_dependencies(&coins(2, "token"));
+    let addr1 = "addr0001".to_string();
+    let addr2 = "addr0002".to_string();
+    let addr3 = "addr0003".to_string();
+    let addr4 = "addr0004".to_string();
+    let amount1 = Uint128::from(100u128);
+
+    do_init_with_minter(deps.borrow_mut(), MOCK_MINTER_ADDR.to_string(), None);
+    do_mint(deps.as_mut(), addr1.clone(), amount1);
+
+    let info = mock_info(&addr1, &[]);
+    let msg = ExecuteMsg::IncreaseAllowance {
+        spender: addr4.clone(),
+        amount: Uint128::new(40u128),
+        expires: None,
+    };
+    let _ = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // the sum of the transfers is above the allowance
+    let info = mock_info(&addr4, &[]);
+    let msg = ExecuteMsg::BatchTransferFrom {
+        owner: addr1.clone(),
+        transfers: vec![
+            (addr2.clone(), Uint128::new(30u128)),
+            (addr3.clone(), Uint128::new(20u128)),
+        ],
+    };
+    let res = execute(deps.as_mut(), mock_env(), info.clone(), msg);
+    assert!(matches!(
+        res,
+        Err(ContractError::Std(StdError::Overflow { .. }))
+    ));
+
+    let msg = ExecuteMsg::BatchTransferFrom {
+        owner: addr1.clone(),
+        transfers: vec![
+            (addr2.clone(), Uint128::new(25u128)),
+            (addr3.clone(), Uint128::new(15u128)),
+        ],
+    };
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: MOCK_REWARD_CONTRACT_ADDR.to_string(),
+            msg: to_binary(&BatchTransferBalance {
+                from: addr1.clone(),
+                transfers: vec![
+                    (addr2.clone(), Uint128::new(25u128)),
+                    (addr3.clone(), Uint128::new(15u128)),
+                ],
+            })
+            .unwrap(),
+            funds: vec![],
+        }))]
+    );
+
+    assert_eq!(
+        query_balance(deps.as_ref(), addr1.clone()).unwrap().balance,
+        Uint128::new(60u128)
+    );
+    assert_eq!(
+        query_balance(deps.as_ref(), addr2).unwrap().balance,
+        Uint128::new(25u128)
+    );
+    assert_eq!(
+        query_allowance(deps.as_ref(), addr1, addr4)
+            .unwrap()
+            .allowance,
+        Uint128::zero()
+    );
+}
+
 #[test]
 fn mint() {
     let mut deps = mock_dependencies(&coins(2, "token"));
@@ -426,3 +600,772 @@ fn send_from() {
         )
     );
 }
+
+#[test]
+fn update_reward_contract() {
+    let mut deps = mock_dependencies(&coins(2, "token"));
+    let new_reward_contract = "bethreward0001".to_string();
+
+    do_init_with_minter(deps.borrow_mut(), MOCK_MINTER_ADDR.to_string(), None);
+
+    let msg = ExecuteMsg::UpdateRewardContract {
+        reward_contract: new_reward_contract.clone(),
+    };
+
+    // only the admin can rotate the reward contract
+    let info = mock_info(MOCK_MINTER_ADDR, &[]);
+    let err = execute(deps.as_mut(), mock_env(), info, msg.clone()).unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+
+    let info = mock_info(MOCK_ADMIN_ADDR, &[]);
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::TokenConfig {}).unwrap();
+    let config: TokenConfigResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        config,
+        TokenConfigResponse {
+            reward_contract: new_reward_contract.clone(),
+            admin: Some(MOCK_ADMIN_ADDR.to_string()),
+            pending_admin: None,
+        }
+    );
+
+    // balance changes are reported to the new reward contract
+    let addr = "addr0000".to_string();
+    let info = mock_info(MOCK_MINTER_ADDR, &[]);
+    let msg = ExecuteMsg::Mint {
+        recipient: addr.clone(),
+        amount: Uint128::new(1u128),
+    };
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: new_reward_contract,
+            msg: to_binary(&IncreaseBalance {
+                address: addr,
+                amount: Uint128::new(1u128),
+            })
+            .unwrap(),
+            funds: vec![],
+        }))]
+    );
+}
+
+#[test]
+fn update_minter() {
+    let mut deps = mock_dependencies(&coins(2, "token"));
+    let new_minter = "minter0001".to_string();
+
+    do_init_with_minter(deps.borrow_mut(), MOCK_MINTER_ADDR.to_string(), None);
+    do_mint(deps.as_mut(), "addr0000".to_string(), Uint128::new(100u128));
+
+    let msg = ExecuteMsg::UpdateMinter {
+        new_minter: new_minter.clone(),
+        cap: Some(Uint128::new(200u128)),
+    };
+    let info = mock_info(MOCK_MINTER_ADDR, &[]);
+    let err = execute(deps.as_mut(), mock_env(), info, msg.clone()).unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+
+    // the cap cannot be lower than the current supply
+    let info = mock_info(MOCK_ADMIN_ADDR, &[]);
+    let invalid_msg = ExecuteMsg::UpdateMinter {
+        new_minter: new_minter.clone(),
+        cap: Some(Uint128::new(99u128)),
+    };
+    let err = execute(deps.as_mut(), mock_env(), info.clone(), invalid_msg).unwrap_err();
+    assert_eq!(err, ContractError::CannotExceedCap {});
+
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(
+        query_minter(deps.as_ref()).unwrap(),
+        Some(MinterResponse {
+            minter: new_minter.clone(),
+            cap: Some(Uint128::new(200u128)),
+        })
+    );
+
+    // the previous minter lost its permission
+    let info = mock_info(MOCK_MINTER_ADDR, &[]);
+    let msg = ExecuteMsg::Mint {
+        recipient: "addr0000".to_string(),
+        amount: Uint128::new(1u128),
+    };
+    let err = execute(deps.as_mut(), mock_env(), info, msg.clone()).unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+
+    let info = mock_info(&new_minter, &[]);
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+}
+
+#[test]
+fn transfer_admin() {
+    let mut deps = mock_dependencies(&coins(2, "token"));
+    let new_admin = "admin0001".to_string();
+
+    do_init_with_minter(deps.borrow_mut(), MOCK_MINTER_ADDR.to_string(), None);
+
+    let msg = ExecuteMsg::ProposeNewAdmin {
+        new_admin: new_admin.clone(),
+    };
+    let info = mock_info(&new_admin, &[]);
+    let err = execute(deps.as_mut(), mock_env(), info, msg.clone()).unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+
+    let info = mock_info(MOCK_ADMIN_ADDR, &[]);
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::TokenConfig {}).unwrap();
+    let config: TokenConfigResponse = from_binary(&res).unwrap();
+    assert_eq!(config.admin, Some(MOCK_ADMIN_ADDR.to_string()));
+    assert_eq!(config.pending_admin, Some(new_admin.clone()));
+
+    // only the proposed admin can accept
+    let info = mock_info("addr0000", &[]);
+    let err = execute(deps.as_mut(), mock_env(), info, ExecuteMsg::AcceptAdmin {}).unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+
+    let info = mock_info(&new_admin, &[]);
+    execute(deps.as_mut(), mock_env(), info, ExecuteMsg::AcceptAdmin {}).unwrap();
+
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::TokenConfig {}).unwrap();
+    let config: TokenConfigResponse = from_binary(&res).unwrap();
+    assert_eq!(config.admin, Some(new_admin));
+    assert_eq!(config.pending_admin, None);
+
+    // the previous admin has no permission anymore
+    let info = mock_info(MOCK_ADMIN_ADDR, &[]);
+    let msg = ExecuteMsg::UpdateRewardContract {
+        reward_contract: "bethreward0001".to_string(),
+    };
+    let err = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+}
+
+fn balance_change_hook(address: &str, delta: BalanceDelta, new_balance: u128) -> SubMsg {
+    SubMsg::reply_on_error(
+        CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: MOCK_OBSERVER_ADDR.to_string(),
+            msg: to_binary(&ObserverExecuteMsg::BalanceChangeHook {
+                address: address.to_string(),
+                delta,
+                new_balance: Uint128::new(new_balance),
+            })
+            .unwrap(),
+            funds: vec![],
+        }),
+        OBSERVER_HOOK_REPLY_ID,
+    )
+    .with_gas_limit(OBSERVER_HOOK_GAS_LIMIT)
+}
+
+#[test]
+fn manage_observers() {
+    let mut deps = mock_dependencies(&coins(2, "token"));
+    do_init_with_minter(deps.borrow_mut(), MOCK_MINTER_ADDR.to_string(), None);
+
+    let msg = ExecuteMsg::AddObserver {
+        address: MOCK_OBSERVER_ADDR.to_string(),
+    };
+
+    // only the admin can register observers
+    let info = mock_info(MOCK_MINTER_ADDR, &[]);
+    let err = execute(deps.as_mut(), mock_env(), info, msg.clone()).unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+
+    let info = mock_info(MOCK_ADMIN_ADDR, &[]);
+    execute(deps.as_mut(), mock_env(), info.clone(), msg.clone()).unwrap();
+
+    let err = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::Std(StdError::generic_err("observer is already registered"))
+    );
+
+    let msg = ExecuteMsg::UpdateObserver {
+        address: MOCK_OBSERVER_ADDR.to_string(),
+        enabled: false,
+    };
+    execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::Observers {}).unwrap();
+    let observers: ObserversResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        observers,
+        ObserversResponse {
+            observers: vec![ObserverResponse {
+                address: MOCK_OBSERVER_ADDR.to_string(),
+                enabled: false,
+            }],
+        }
+    );
+
+    // the number of observers is bounded
+    for i in 1..MAX_OBSERVERS {
+        let msg = ExecuteMsg::AddObserver {
+            address: format!("observer{:04}", i),
+        };
+        execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+    }
+    let msg = ExecuteMsg::AddObserver {
+        address: "observer9999".to_string(),
+    };
+    let err = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::Std(StdError::generic_err(format!(
+            "cannot register more than {} observers",
+            MAX_OBSERVERS
+        )))
+    );
+
+    let msg = ExecuteMsg::RemoveObserver {
+        address: MOCK_OBSERVER_ADDR.to_string(),
+    };
+    execute(deps.as_mut(), mock_env(), info.clone(), msg.clone()).unwrap();
+    let err = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::Std(StdError::generic_err("observer is not registered"))
+    );
+
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::Observers {}).unwrap();
+    let observers: ObserversResponse = from_binary(&res).unwrap();
+    assert_eq!(observers.observers.len(), MAX_OBSERVERS - 1);
+}
+
+#[test]
+fn balance_change_hooks() {
+    let mut deps = mock_dependencies(&coins(2, "token"));
+    let addr1 = "addr0001".to_string();
+    let addr2 = "addr0002".to_string();
+    let addr3 = "addr0003".to_string();
+
+    do_init_with_minter(deps.borrow_mut(), MOCK_MINTER_ADDR.to_string(), None);
+
+    let info = mock_info(MOCK_ADMIN_ADDR, &[]);
+    let msg = ExecuteMsg::AddObserver {
+        address: MOCK_OBSERVER_ADDR.to_string(),
+    };
+    execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+
+    // disabled observers are skipped
+    let msg = ExecuteMsg::AddObserver {
+        address: "observer0001".to_string(),
+    };
+    execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+    let msg = ExecuteMsg::UpdateObserver {
+        address: "observer0001".to_string(),
+        enabled: false,
+    };
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let info = mock_info(MOCK_MINTER_ADDR, &[]);
+    let msg = ExecuteMsg::Mint {
+        recipient: addr1.clone(),
+        amount: Uint128::new(100u128),
+    };
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(res.messages.len(), 2);
+    assert_eq!(
+        res.messages[1],
+        balance_change_hook(&addr1, BalanceDelta::Increase(Uint128::new(100u128)), 100)
+    );
+
+    let info = mock_info(&addr1, &[]);
+    let msg = ExecuteMsg::Transfer {
+        recipient: addr2.clone(),
+        amount: Uint128::new(40u128),
+    };
+    let res = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+    assert_eq!(res.messages.len(), 4);
+    assert_eq!(
+        res.messages[2..],
+        [
+            balance_change_hook(&addr1, BalanceDelta::Decrease(Uint128::new(40u128)), 60),
+            balance_change_hook(&addr2, BalanceDelta::Increase(Uint128::new(40u128)), 40),
+        ]
+    );
+
+    let msg = ExecuteMsg::Burn {
+        amount: Uint128::new(10u128),
+    };
+    let res = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+    assert_eq!(res.messages.len(), 2);
+    assert_eq!(
+        res.messages[1],
+        balance_change_hook(&addr1, BalanceDelta::Decrease(Uint128::new(10u128)), 50)
+    );
+
+    let msg = ExecuteMsg::BatchTransfer {
+        transfers: vec![
+            (addr2.clone(), Uint128::new(20u128)),
+            (addr3.clone(), Uint128::new(5u128)),
+        ],
+    };
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(
+        res.messages[1..],
+        [
+            balance_change_hook(&addr1, BalanceDelta::Decrease(Uint128::new(25u128)), 25),
+            balance_change_hook(&addr2, BalanceDelta::Increase(Uint128::new(20u128)), 60),
+            balance_change_hook(&addr3, BalanceDelta::Increase(Uint128::new(5u128)), 5),
+        ]
+    );
+
+    // the changes of an account are summed up, so new_balance matches the delta
+    let info = mock_info(&addr2, &[]);
+    let msg = ExecuteMsg::BatchTransfer {
+        transfers: vec![
+            (addr3.clone(), Uint128::new(10u128)),
+            (addr3.clone(), Uint128::new(15u128)),
+        ],
+    };
+    let res = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+    assert_eq!(
+        res.messages[1..],
+        [
+            balance_change_hook(&addr2, BalanceDelta::Decrease(Uint128::new(25u128)), 35),
+            balance_change_hook(&addr3, BalanceDelta::Increase(Uint128::new(25u128)), 30),
+        ]
+    );
+
+    // a transfer to itself does not change the balance
+    let msg = ExecuteMsg::Transfer {
+        recipient: addr2.clone(),
+        amount: Uint128::new(5u128),
+    };
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(res.messages.len(), 2);
+}
+
+#[test]
+fn failing_balance_change_hook() {
+    let mut deps = mock_dependencies(&coins(2, "token"));
+    do_init_with_minter(deps.borrow_mut(), MOCK_MINTER_ADDR.to_string(), None);
+
+    // the error of the observer is recorded and the balance change is kept
+    let reply_msg = Reply {
+        id: OBSERVER_HOOK_REPLY_ID,
+        result: ContractResult::Err("observer error".to_string()),
+    };
+    let res = reply(deps.as_mut(), mock_env(), reply_msg).unwrap();
+    assert_eq!(res.messages, vec![]);
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "observer_hook_failed"),
+            attr("error", "observer error"),
+        ]
+    );
+
+    let reply_msg = Reply {
+        id: 0,
+        result: ContractResult::Err("error".to_string()),
+    };
+    let err = reply(deps.as_mut(), mock_env(), reply_msg).unwrap_err();
+    assert_eq!(err, StdError::generic_err("invalid reply id"));
+}
+
+// populate the store the way the token contract did before moving to cw20-base
+fn do_legacy_init<S: Storage, A: Api, Q: Querier>(
+    deps: &mut OwnedDeps<S, A, Q>,
+    accounts: &[(&str, u128)],
+) {
+    let reward_raw = deps
+        .api
+        .addr_canonicalize(MOCK_REWARD_CONTRACT_ADDR)
+        .unwrap();
+    store_reward_contract(&mut deps.storage, &reward_raw).unwrap();
+
+    let initial_balances = accounts
+        .iter()
+        .map(|(address, amount)| Cw20Coin {
+            address: address.to_string(),
+            amount: Uint128::new(*amount),
+        })
+        .collect();
+    cw20_legacy::contract::instantiate(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(MOCK_MINTER_ADDR, &[]),
+        cw20_legacy::msg::InstantiateMsg {
+            name: "bluna".to_string(),
+            symbol: "BLUNA".to_string(),
+            decimals: 6,
+            initial_balances,
+            mint: Some(MinterResponse {
+                minter: MOCK_MINTER_ADDR.to_string(),
+                cap: None,
+            }),
+        },
+    )
+    .unwrap();
+
+    cw20_legacy::contract::execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(accounts[0].0, &[]),
+        cw20_legacy::msg::ExecuteMsg::IncreaseAllowance {
+            spender: accounts[1].0.to_string(),
+            amount: Uint128::new(10u128),
+            expires: None,
+        },
+    )
+    .unwrap();
+}
+
+#[test]
+fn migrate_legacy_state() {
+    let mut deps = mock_dependencies(&[]);
+    let accounts = [
+        ("addr0000", 100u128),
+        ("addr0001", 200u128),
+        ("addr0002", 300u128),
+    ];
+    do_legacy_init(&mut deps, &accounts);
+
+    let msg = MigrateMsg {
+        minter: None,
+        admin: Some(MOCK_ADMIN_ADDR.to_string()),
+        limit: None,
+        marketing: None,
+    };
+    migrate(deps.as_mut(), mock_env(), msg).unwrap();
+    assert_eq!(read_legacy_migration(&deps.storage).unwrap(), None);
+
+    assert_eq!(
+        query_token_info(deps.as_ref()).unwrap(),
+        TokenInfoResponse {
+            name: "bluna".to_string(),
+            symbol: "BLUNA".to_string(),
+            decimals: 6,
+            total_supply: Uint128::new(600u128),
+        }
+    );
+    assert_eq!(
+        query_minter(deps.as_ref()).unwrap(),
+        Some(MinterResponse {
+            minter: MOCK_MINTER_ADDR.to_string(),
+            cap: None,
+        })
+    );
+    for (address, amount) in accounts.iter() {
+        assert_eq!(
+            query_balance(deps.as_ref(), address.to_string())
+                .unwrap()
+                .balance,
+            Uint128::new(*amount)
+        );
+    }
+    assert_eq!(
+        query_allowance(
+            deps.as_ref(),
+            accounts[0].0.to_string(),
+            accounts[1].0.to_string()
+        )
+        .unwrap()
+        .allowance,
+        Uint128::new(10u128)
+    );
+
+    // the migrated state is usable
+    let info = mock_info(accounts[1].0, &[]);
+    let msg = ExecuteMsg::TransferFrom {
+        owner: accounts[0].0.to_string(),
+        recipient: accounts[2].0.to_string(),
+        amount: Uint128::new(10u128),
+    };
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(res.messages.len(), 2);
+    assert_eq!(
+        query_balance(deps.as_ref(), accounts[2].0.to_string())
+            .unwrap()
+            .balance,
+        Uint128::new(310u128)
+    );
+}
+
+#[test]
+fn migrate_legacy_state_in_batches() {
+    let mut deps = mock_dependencies(&[]);
+    let accounts = [
+        ("addr0000", 100u128),
+        ("addr0001", 200u128),
+        ("addr0002", 300u128),
+    ];
+    do_legacy_init(&mut deps, &accounts);
+
+    let msg = MigrateMsg {
+        minter: None,
+        admin: None,
+        limit: Some(2),
+        marketing: None,
+    };
+    let res = migrate(deps.as_mut(), mock_env(), msg).unwrap();
+    assert_eq!(res.attributes[0], attr("staged_balances", "2"));
+    assert_eq!(res.attributes[4], attr("completed", "false"));
+
+    // everything else is blocked until the migration is complete
+    let info = mock_info(accounts[0].0, &[]);
+    let msg = ExecuteMsg::Transfer {
+        recipient: accounts[1].0.to_string(),
+        amount: Uint128::new(1u128),
+    };
+    let err = execute(deps.as_mut(), mock_env(), info.clone(), msg.clone()).unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::Std(StdError::generic_err(
+            "legacy state migration is in progress"
+        ))
+    );
+
+    // balances and allowances can not be queried while they are partially migrated
+    for msg in &[
+        QueryMsg::Balance {
+            address: accounts[0].0.to_string(),
+        },
+        QueryMsg::AllAccounts {
+            start_after: None,
+            limit: None,
+        },
+        QueryMsg::Allowance {
+            owner: accounts[0].0.to_string(),
+            spender: accounts[1].0.to_string(),
+        },
+        QueryMsg::AllAllowances {
+            owner: accounts[0].0.to_string(),
+            start_after: None,
+            limit: None,
+        },
+    ] {
+        let err = query(deps.as_ref(), mock_env(), msg.clone()).unwrap_err();
+        assert_eq!(
+            err,
+            StdError::generic_err("legacy state migration is in progress")
+        );
+    }
+
+    let migrate_msg = ExecuteMsg::MigrateLegacyState { limit: Some(1) };
+    let res = execute(deps.as_mut(), mock_env(), info.clone(), migrate_msg.clone()).unwrap();
+    assert_eq!(res.attributes[1], attr("staged_balances", "1"));
+    assert_eq!(res.attributes[5], attr("completed", "false"));
+
+    let res = execute(deps.as_mut(), mock_env(), info.clone(), migrate_msg).unwrap();
+    assert_eq!(res.attributes[1], attr("staged_balances", "0"));
+    assert_eq!(res.attributes[2], attr("staged_allowances", "1"));
+    assert_eq!(res.attributes[5], attr("completed", "false"));
+
+    // the staged entries are moved to the cw20-base layout within the limit too
+    let migrate_msg = ExecuteMsg::MigrateLegacyState { limit: Some(3) };
+    let res = execute(deps.as_mut(), mock_env(), info.clone(), migrate_msg).unwrap();
+    assert_eq!(res.attributes[2], attr("staged_allowances", "0"));
+    assert_eq!(res.attributes[3], attr("moved_balances", "3"));
+    assert_eq!(res.attributes[4], attr("moved_allowances", "0"));
+    assert_eq!(res.attributes[5], attr("completed", "false"));
+
+    let migrate_msg = ExecuteMsg::MigrateLegacyState { limit: None };
+    let res = execute(deps.as_mut(), mock_env(), info.clone(), migrate_msg).unwrap();
+    assert_eq!(res.attributes[3], attr("moved_balances", "0"));
+    assert_eq!(res.attributes[4], attr("moved_allowances", "1"));
+    assert_eq!(res.attributes[5], attr("completed", "true"));
+    assert!(STAGED_BALANCES
+        .range(&deps.storage, None, None, Order::Ascending)
+        .next()
+        .is_none());
+    assert!(STAGED_ALLOWANCES
+        .range(&deps.storage, None, None, Order::Ascending)
+        .next()
+        .is_none());
+
+    for (address, amount) in accounts.iter() {
+        assert_eq!(
+            query_balance(deps.as_ref(), address.to_string())
+                .unwrap()
+                .balance,
+            Uint128::new(*amount)
+        );
+    }
+    assert_eq!(
+        query_allowance(
+            deps.as_ref(),
+            accounts[0].0.to_string(),
+            accounts[1].0.to_string()
+        )
+        .unwrap()
+        .allowance,
+        Uint128::new(10u128)
+    );
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+}
+
+fn marketing_init_msg(logo: Option<Logo>) -> TokenInstantiateMsg {
+    TokenInstantiateMsg {
+        name: "bonded ETH".to_string(),
+        symbol: "BETH".to_string(),
+        decimals: 6,
+        initial_balances: vec![],
+        mint: None,
+        reward_contract: MOCK_REWARD_CONTRACT_ADDR.to_string(),
+        admin: None,
+        marketing: Some(InstantiateMarketingInfo {
+            project: Some("Anchor Protocol".to_string()),
+            description: Some("bonded ETH".to_string()),
+            marketing: Some(MOCK_MARKETING_ADDR.to_string()),
+            logo,
+        }),
+    }
+}
+
+#[test]
+fn instantiate_with_marketing() {
+    let mut deps = mock_dependencies(&[]);
+    let info = mock_info("creator", &[]);
+
+    // embedded logos are validated
+    let svg = Logo::Embedded(EmbeddedLogo::Svg(b"<svg></svg>".to_vec().into()));
+    let err = instantiate(
+        deps.as_mut(),
+        mock_env(),
+        info.clone(),
+        marketing_init_msg(Some(svg)),
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::InvalidXmlPreamble {});
+
+    let mut png = PNG_HEADER.to_vec();
+    png.resize(6 * 1024, 0);
+    let png = Logo::Embedded(EmbeddedLogo::Png(png.into()));
+    let err = instantiate(
+        deps.as_mut(),
+        mock_env(),
+        info.clone(),
+        marketing_init_msg(Some(png)),
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::LogoTooBig {});
+
+    let png = Logo::Embedded(EmbeddedLogo::Png(PNG_HEADER.to_vec().into()));
+    instantiate(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        marketing_init_msg(Some(png)),
+    )
+    .unwrap();
+
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::MarketingInfo {}).unwrap();
+    let marketing: MarketingInfoResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        marketing,
+        MarketingInfoResponse {
+            project: Some("Anchor Protocol".to_string()),
+            description: Some("bonded ETH".to_string()),
+            marketing: Some(Addr::unchecked(MOCK_MARKETING_ADDR)),
+            logo: Some(LogoInfo::Embedded),
+        }
+    );
+
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::DownloadLogo {}).unwrap();
+    let logo: DownloadLogoResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        logo,
+        DownloadLogoResponse {
+            mime_type: "image/png".to_string(),
+            data: PNG_HEADER.to_vec().into(),
+        }
+    );
+}
+
+#[test]
+fn update_marketing() {
+    let mut deps = mock_dependencies(&[]);
+    let info = mock_info("creator", &[]);
+    instantiate(deps.as_mut(), mock_env(), info, marketing_init_msg(None)).unwrap();
+
+    let msg = ExecuteMsg::UpdateMarketing {
+        project: None,
+        description: Some("bonded ETH on Terra".to_string()),
+        marketing: None,
+    };
+    let info = mock_info("addr0000", &[]);
+    let err = execute(deps.as_mut(), mock_env(), info, msg.clone()).unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+
+    let info = mock_info(MOCK_MARKETING_ADDR, &[]);
+    execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+
+    let svg = b"<?xml version=\"1.0\"?><svg></svg>".to_vec();
+    let msg = ExecuteMsg::UploadLogo(Logo::Embedded(EmbeddedLogo::Svg(svg.clone().into())));
+    execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+
+    let msg = ExecuteMsg::UploadLogo(Logo::Embedded(EmbeddedLogo::Png(b"png".to_vec().into())));
+    let err = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+    assert_eq!(err, ContractError::InvalidPngHeader {});
+
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::MarketingInfo {}).unwrap();
+    let marketing: MarketingInfoResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        marketing,
+        MarketingInfoResponse {
+            project: Some("Anchor Protocol".to_string()),
+            description: Some("bonded ETH on Terra".to_string()),
+            marketing: Some(Addr::unchecked(MOCK_MARKETING_ADDR)),
+            logo: Some(LogoInfo::Embedded),
+        }
+    );
+
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::DownloadLogo {}).unwrap();
+    let logo: DownloadLogoResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        logo,
+        DownloadLogoResponse {
+            mime_type: "image/svg+xml".to_string(),
+            data: svg.into(),
+        }
+    );
+}
+
+#[test]
+fn migrate_marketing() {
+    let mut deps = mock_dependencies(&[]);
+    do_init_with_minter(deps.borrow_mut(), MOCK_MINTER_ADDR.to_string(), None);
+
+    let msg = MigrateMsg {
+        minter: None,
+        admin: None,
+        limit: None,
+        marketing: Some(MOCK_MARKETING_ADDR.to_string()),
+    };
+    migrate(deps.as_mut(), mock_env(), msg).unwrap();
+
+    let info = mock_info(MOCK_MARKETING_ADDR, &[]);
+    let msg = ExecuteMsg::UploadLogo(Logo::Url("https://anchorprotocol.com/beth.svg".to_string()));
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::MarketingInfo {}).unwrap();
+    let marketing: MarketingInfoResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        marketing,
+        MarketingInfoResponse {
+            project: None,
+            description: None,
+            marketing: Some(Addr::unchecked(MOCK_MARKETING_ADDR)),
+            logo: Some(LogoInfo::Url(
+                "https://anchorprotocol.com/beth.svg".to_string()
+            )),
+        }
+    );
+
+    // the migration cannot take over an existing marketing role
+    let msg = MigrateMsg {
+        minter: None,
+        admin: None,
+        limit: None,
+        marketing: Some("addr0000".to_string()),
+    };
+    let err = migrate(deps.as_mut(), mock_env(), msg).unwrap_err();
+    assert_eq!(err, StdError::generic_err("marketing info is already set"));
+}
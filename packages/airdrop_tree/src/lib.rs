@@ -0,0 +1,155 @@
+//! Merkle trees of the `anchor_beth_airdrop` stages.
+//!
+//! The leaves and the proofs use the hashing of `beth::airdrop`, so the roots built here
+//! can be registered in the airdrop contract as is. `Airdrop::from_holders` builds a
+//! stage from a `HoldersResponse` export of the reward contract.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use beth::airdrop::{hash_pair, leaf_hash};
+use beth::reward::HolderResponse;
+use cosmwasm_std::{StdError, StdResult, Uint128};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// Merkle tree with sorted pair hashing, a node without sibling moves up unchanged
+#[derive(Clone, Debug, PartialEq)]
+pub struct MerkleTree {
+    layers: Vec<Vec<[u8; 32]>>,
+}
+
+impl MerkleTree {
+    pub fn new(leaves: Vec<[u8; 32]>) -> StdResult<Self> {
+        if leaves.is_empty() {
+            return Err(StdError::generic_err(
+                "Cannot build a merkle tree without leaves",
+            ));
+        }
+
+        let mut layers = vec![leaves];
+        while layers[layers.len() - 1].len() > 1 {
+            let next = layers[layers.len() - 1]
+                .chunks(2)
+                .map(|pair| match pair {
+                    [a, b] => hash_pair(a, b),
+                    [a] => *a,
+                    _ => unreachable!(),
+                })
+                .collect();
+            layers.push(next);
+        }
+
+        Ok(MerkleTree { layers })
+    }
+
+    pub fn root(&self) -> [u8; 32] {
+        self.layers[self.layers.len() - 1][0]
+    }
+
+    /// Sibling hashes from the leaf at `index` to the root
+    pub fn proof(&self, index: usize) -> Vec<[u8; 32]> {
+        let mut proof = vec![];
+        let mut index = index;
+        for layer in &self.layers[..self.layers.len() - 1] {
+            if let Some(sibling) = layer.get(index ^ 1) {
+                proof.push(*sibling);
+            }
+            index /= 2;
+        }
+        proof
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct AirdropClaim {
+    pub address: String,
+    pub amount: Uint128,
+    /// hex encoded proof to pass to `Claim`
+    pub proof: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Airdrop {
+    /// hex encoded root to pass to `RegisterMerkleRoot`
+    pub merkle_root: String,
+    /// amount to fund the stage with
+    pub total_amount: Uint128,
+    pub claims: Vec<AirdropClaim>,
+}
+
+impl Airdrop {
+    /// Build a stage paying `amount` to each address, an address can only claim once
+    pub fn new(amounts: Vec<(String, Uint128)>) -> StdResult<Self> {
+        let mut addresses = BTreeSet::new();
+        for (address, _) in amounts.iter() {
+            if !addresses.insert(address.as_str()) {
+                return Err(StdError::generic_err(format!(
+                    "Duplicate address {} in the airdrop",
+                    address
+                )));
+            }
+        }
+
+        let leaves = amounts
+            .iter()
+            .map(|(address, amount)| leaf_hash(address, *amount))
+            .collect();
+        let tree = MerkleTree::new(leaves)?;
+
+        let mut total_amount = Uint128::zero();
+        let mut claims = vec![];
+        for (index, (address, amount)) in amounts.into_iter().enumerate() {
+            total_amount = total_amount.checked_add(amount)?;
+            claims.push(AirdropClaim {
+                address,
+                amount,
+                proof: tree.proof(index).iter().map(hex::encode).collect(),
+            });
+        }
+
+        Ok(Airdrop {
+            merkle_root: hex::encode(tree.root()),
+            total_amount,
+            claims,
+        })
+    }
+
+    /// Build a stage from the holders of the reward contract, paying their bETH balance or,
+    /// with `total_amount`, splitting it by balance rounded down. Holders can span several
+    /// pages of the export, duplicates keep the last balance.
+    pub fn from_holders(
+        holders: &[HolderResponse],
+        total_amount: Option<Uint128>,
+    ) -> StdResult<Self> {
+        let balances: BTreeMap<&str, Uint128> = holders
+            .iter()
+            .map(|holder| (holder.address.as_str(), holder.balance))
+            .collect();
+        let total_balance = balances
+            .values()
+            .try_fold(Uint128::zero(), |sum, balance| sum.checked_add(*balance))?;
+        if total_balance.is_zero() {
+            return Err(StdError::generic_err("No holders to airdrop to"));
+        }
+
+        let amounts: Vec<(String, Uint128)> = balances
+            .into_iter()
+            .map(|(address, balance)| {
+                let amount = match total_amount {
+                    Some(total_amount) => total_amount.multiply_ratio(balance, total_balance),
+                    None => balance,
+                };
+                (address.to_string(), amount)
+            })
+            .filter(|(_, amount)| !amount.is_zero())
+            .collect();
+        if amounts.is_empty() {
+            return Err(StdError::generic_err("No holders to airdrop to"));
+        }
+
+        Airdrop::new(amounts)
+    }
+}
+
+#[cfg(test)]
+mod testing;
@@ -0,0 +1,62 @@
+//! Build an airdrop stage from `HoldersResponse` exports of the reward contract.
+//!
+//! ```sh
+//! beth-airdrop-tree [--total <amount>] <holders.json>...
+//! ```
+//!
+//! Prints the merkle root, the total amount to fund the stage with and the claim of
+//! each holder with its proof as JSON.
+
+use std::env;
+use std::fs;
+use std::process;
+
+use beth::reward::HoldersResponse;
+use beth_airdrop_tree::Airdrop;
+use cosmwasm_std::Uint128;
+
+const USAGE: &str = "usage: beth-airdrop-tree [--total <amount>] <holders.json>...";
+
+fn main() {
+    if let Err(err) = run(env::args().skip(1).collect()) {
+        eprintln!("{}", err);
+        process::exit(1);
+    }
+}
+
+fn run(args: Vec<String>) -> Result<(), String> {
+    let mut total_amount = None;
+    let mut files = vec![];
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--total" => {
+                let amount = args.next().ok_or(USAGE)?;
+                let amount = amount
+                    .parse::<u128>()
+                    .map_err(|_| format!("Invalid total amount: {}", amount))?;
+                total_amount = Some(Uint128::new(amount));
+            }
+            "-h" | "--help" => return Err(USAGE.to_string()),
+            _ => files.push(arg),
+        }
+    }
+    if files.is_empty() {
+        return Err(USAGE.to_string());
+    }
+
+    let mut holders = vec![];
+    for file in files {
+        let content = fs::read(&file).map_err(|err| format!("{}: {}", file, err))?;
+        let page: HoldersResponse =
+            serde_json::from_slice(&content).map_err(|err| format!("{}: {}", file, err))?;
+        holders.extend(page.holders);
+    }
+
+    let airdrop = Airdrop::from_holders(&holders, total_amount).map_err(|err| err.to_string())?;
+    println!(
+        "{}",
+        serde_json::to_string_pretty(&airdrop).map_err(|err| err.to_string())?
+    );
+    Ok(())
+}
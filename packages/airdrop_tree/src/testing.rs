@@ -0,0 +1,130 @@
+use crate::{Airdrop, MerkleTree};
+use beth::airdrop::{hash_pair, leaf_hash, verify_proof};
+use beth::reward::HolderResponse;
+use cosmwasm_bignumber::Decimal256;
+use cosmwasm_std::{StdError, Uint128};
+
+fn holder(address: &str, balance: u128) -> HolderResponse {
+    HolderResponse {
+        address: address.to_string(),
+        balance: Uint128::new(balance),
+        index: Decimal256::zero(),
+        pending_rewards: Decimal256::zero(),
+        accrued_rewards: Uint128::zero(),
+        total_claimed: Uint128::zero(),
+        last_claim_time: None,
+        locked_amount: Uint128::zero(),
+        locked_weight: Uint128::zero(),
+        penalty_index: Decimal256::zero(),
+        pending_penalties: Decimal256::zero(),
+        accrued_penalties: Uint128::zero(),
+    }
+}
+
+#[test]
+fn proofs_verify_for_any_tree_size() {
+    for size in 1..=9u128 {
+        let amounts: Vec<(String, Uint128)> = (0..size)
+            .map(|i| (format!("addr{:04}", i), Uint128::new(100 + i)))
+            .collect();
+        let airdrop = Airdrop::new(amounts).unwrap();
+        assert_eq!(airdrop.claims.len() as u128, size);
+
+        for claim in airdrop.claims.iter() {
+            assert!(verify_proof(
+                &airdrop.merkle_root,
+                &claim.address,
+                claim.amount,
+                &claim.proof
+            )
+            .unwrap());
+            // the amount is part of the leaf
+            assert!(!verify_proof(
+                &airdrop.merkle_root,
+                &claim.address,
+                claim.amount + Uint128::new(1),
+                &claim.proof
+            )
+            .unwrap());
+        }
+    }
+}
+
+#[test]
+fn leaves_separate_the_address_from_the_amount() {
+    assert_ne!(
+        leaf_hash("addr1", Uint128::new(23)),
+        leaf_hash("addr12", Uint128::new(3))
+    );
+}
+
+#[test]
+fn duplicate_addresses_are_rejected() {
+    let amounts = vec![
+        ("addr0000".to_string(), Uint128::new(100)),
+        ("addr0001".to_string(), Uint128::new(200)),
+        ("addr0000".to_string(), Uint128::new(300)),
+    ];
+    assert_eq!(
+        Airdrop::new(amounts).unwrap_err(),
+        StdError::generic_err("Duplicate address addr0000 in the airdrop")
+    );
+}
+
+#[test]
+fn odd_nodes_move_up() {
+    let leaves: Vec<[u8; 32]> = (0..3u128)
+        .map(|i| leaf_hash("addr0000", Uint128::new(i)))
+        .collect();
+    let tree = MerkleTree::new(leaves.clone()).unwrap();
+
+    assert_eq!(
+        tree.root(),
+        hash_pair(&hash_pair(&leaves[0], &leaves[1]), &leaves[2])
+    );
+    assert_eq!(tree.proof(2), vec![hash_pair(&leaves[0], &leaves[1])]);
+    assert_eq!(tree.proof(0), vec![leaves[1], leaves[2]]);
+
+    assert_eq!(
+        MerkleTree::new(vec![]).unwrap_err(),
+        StdError::generic_err("Cannot build a merkle tree without leaves")
+    );
+}
+
+#[test]
+fn airdrop_from_holders() {
+    let holders = vec![
+        holder("addr0002", 300),
+        holder("addr0000", 100),
+        holder("addr0003", 0),
+        // a later page with a newer balance
+        holder("addr0000", 200),
+    ];
+
+    let airdrop = Airdrop::from_holders(&holders, None).unwrap();
+    assert_eq!(airdrop.total_amount, Uint128::new(500));
+    let amounts: Vec<(&str, u128)> = airdrop
+        .claims
+        .iter()
+        .map(|claim| (claim.address.as_str(), claim.amount.u128()))
+        .collect();
+    assert_eq!(amounts, vec![("addr0000", 200), ("addr0002", 300)]);
+
+    // the split is rounded down, the total is what the stage needs
+    let airdrop = Airdrop::from_holders(&holders, Some(Uint128::new(1000))).unwrap();
+    let amounts: Vec<(&str, u128)> = airdrop
+        .claims
+        .iter()
+        .map(|claim| (claim.address.as_str(), claim.amount.u128()))
+        .collect();
+    assert_eq!(amounts, vec![("addr0000", 400), ("addr0002", 600)]);
+    assert_eq!(airdrop.total_amount, Uint128::new(1000));
+
+    let airdrop = Airdrop::from_holders(&holders, Some(Uint128::new(7))).unwrap();
+    assert_eq!(airdrop.total_amount, Uint128::new(6));
+
+    assert_eq!(
+        Airdrop::from_holders(&[holder("addr0000", 0)], Some(Uint128::new(1000))).unwrap_err(),
+        StdError::generic_err("No holders to airdrop to")
+    );
+}
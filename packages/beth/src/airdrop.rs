@@ -0,0 +1,142 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use cosmwasm_std::{StdError, StdResult, Uint128};
+use cw20::{Cw20ReceiveMsg, Expiration};
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct InstantiateMsg {
+    pub owner: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ExecuteMsg {
+    /// Receive interface for send token.
+    /// Register a stage paid in the sent CW20 token
+    Receive(Cw20ReceiveMsg),
+
+    ////////////////////
+    /// Owner's operations
+    ///////////////////
+
+    /// Update contract configuration
+    UpdateConfig { owner: Option<String> },
+    /// Register a stage paid in the native coin sent with the message
+    RegisterMerkleRoot {
+        /// hex encoded sha256 merkle root
+        merkle_root: String,
+        expiration: Option<Expiration>,
+    },
+    /// Send the unclaimed funds of an expired stage to `recipient`, the owner by default
+    WithdrawUnclaimed {
+        stage: u8,
+        recipient: Option<String>,
+    },
+
+    ////////////////////
+    /// User's operations
+    ///////////////////
+
+    /// Claim the airdrop of the sender in `stage`
+    Claim {
+        stage: u8,
+        amount: Uint128,
+        /// hex encoded sibling hashes from the leaf to the root
+        proof: Vec<String>,
+    },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum Cw20HookMsg {
+    RegisterMerkleRoot {
+        merkle_root: String,
+        expiration: Option<Expiration>,
+    },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum QueryMsg {
+    Config {},
+    LatestStage {},
+    Stage { stage: u8 },
+    IsClaimed { stage: u8, address: String },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct MigrateMsg {}
+
+/// Asset paid by an airdrop stage
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum AirdropAsset {
+    Native { denom: String },
+    Token { contract_addr: String },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ConfigResponse {
+    pub owner: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct LatestStageResponse {
+    pub latest_stage: u8,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct StageResponse {
+    pub stage: u8,
+    pub merkle_root: String,
+    pub asset: AirdropAsset,
+    pub total_amount: Uint128,
+    pub claimed_amount: Uint128,
+    pub expiration: Expiration,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct IsClaimedResponse {
+    pub is_claimed: bool,
+}
+
+/// Leaf of the airdrop merkle tree, sha256 of the address and the amount in decimal
+/// separated by a colon, so an address ending in digits can not borrow from the amount
+pub fn leaf_hash(address: &str, amount: Uint128) -> [u8; 32] {
+    Sha256::digest(format!("{}:{}", address, amount).as_bytes()).into()
+}
+
+/// Parent of two nodes, the pair is sorted so proofs do not need the side of the siblings
+pub fn hash_pair(a: &[u8; 32], b: &[u8; 32]) -> [u8; 32] {
+    let (first, second) = if a <= b { (a, b) } else { (b, a) };
+    let mut hasher = Sha256::new();
+    hasher.update(first);
+    hasher.update(second);
+    hasher.finalize().into()
+}
+
+/// Decode a hex encoded 32 bytes hash
+pub fn decode_hash(hash: &str) -> StdResult<[u8; 32]> {
+    let mut buf = [0u8; 32];
+    hex::decode_to_slice(hash, &mut buf)
+        .map_err(|_| StdError::generic_err(format!("Invalid hash: {}", hash)))?;
+    Ok(buf)
+}
+
+/// Whether `proof` leads from the leaf of `address` and `amount` to `merkle_root`
+pub fn verify_proof(
+    merkle_root: &str,
+    address: &str,
+    amount: Uint128,
+    proof: &[String],
+) -> StdResult<bool> {
+    let root = decode_hash(merkle_root)?;
+    let computed = proof
+        .iter()
+        .try_fold(leaf_hash(address, amount), |hash, sibling| {
+            Ok::<_, StdError>(hash_pair(&hash, &decode_hash(sibling)?))
+        })?;
+    Ok(computed == root)
+}
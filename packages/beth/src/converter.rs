@@ -1,8 +1,13 @@
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
+use cosmwasm_std::Uint128;
+use cw2::ContractVersion;
 use cw20::Cw20ReceiveMsg;
 
+/// Version of the `ExportState` output, bumped on breaking changes of its schema
+pub const EXPORT_VERSION: u32 = 1;
+
 #[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug, Default)]
 pub struct Asset {
     pub asset_name: String,
@@ -44,6 +49,8 @@ pub enum Cw20HookMsg {
 #[serde(rename_all = "snake_case")]
 pub enum QueryMsg {
     Config {},
+    /// Config, registered assets and their reserves, for moving the contract to another chain
+    ExportState {},
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -53,6 +60,29 @@ pub struct ConfigResponse {
     pub anchor_token_address: Option<String>,
 }
 
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct AssetReserveResponse {
+    /// named after the symbol of the anchor token
+    pub asset: Asset,
+    pub wormhole_decimals: u8,
+    pub anchor_decimals: u8,
+    /// wormhole tokens held by the converter
+    pub wormhole_reserve: Uint128,
+    /// anchor tokens in circulation, redeemable against the reserve
+    pub anchor_supply: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ExportStateResponse {
+    /// `EXPORT_VERSION` of the contract that exported the state
+    pub version: u32,
+    /// cw2 name and version, unset for deployments that predate cw2
+    pub contract: Option<ContractVersion>,
+    pub config: ConfigResponse,
+    /// empty until the tokens are registered
+    pub assets: Vec<AssetReserveResponse>,
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct WhitelistedAssetResponse {
     pub asset: Asset,
@@ -0,0 +1,128 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use cosmwasm_bignumber::Decimal256;
+use cosmwasm_std::Uint128;
+use cw20::Cw20ReceiveMsg;
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct InstantiateMsg {
+    pub owner: String,
+    /// bETH token accepted as collateral
+    pub collateral_token: String,
+    /// money market contract locking and unlocking the collateral
+    pub overseer: String,
+    pub reward_contract: String,
+    pub reward_denom: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ExecuteMsg {
+    /// Receive interface for send token.
+    /// Deposit bETH as collateral of the sender
+    Receive(Cw20ReceiveMsg),
+
+    ////////////////////
+    /// Owner's operations
+    ///////////////////
+
+    /// Update contract configuration
+    UpdateConfig {
+        owner: Option<String>,
+        overseer: Option<String>,
+    },
+
+    ////////////////////
+    /// Overseer's operations
+    ///////////////////
+
+    /// Make `amount` of the spendable collateral of `borrower` non withdrawable
+    LockCollateral { borrower: String, amount: Uint128 },
+    /// Make `amount` of the locked collateral of `borrower` withdrawable again
+    UnlockCollateral { borrower: String, amount: Uint128 },
+
+    ////////////////////
+    /// User's operations
+    ///////////////////
+
+    /// Withdraw spendable collateral, all of it when `amount` is not set
+    WithdrawCollateral { amount: Option<Uint128> },
+    /// Claim the rewards of the custody from the reward contract and
+    /// distribute them to the borrowers by their collateral share
+    DistributeRewards {},
+    /// return the accrued reward in uusd to the borrower.
+    ClaimRewards { recipient: Option<String> },
+    /// Send the early unlock penalties owed to the borrower in bETH
+    ClaimPenalties { recipient: Option<String> },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum Cw20HookMsg {
+    DepositCollateral {},
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum QueryMsg {
+    Config {},
+    State {},
+    /// Rewards of the borrower, including the ones the custody has not claimed yet
+    AccruedRewards {
+        address: String,
+    },
+    /// Early unlock penalties of the borrower in bETH, including the ones the custody
+    /// has not claimed yet
+    AccruedPenalties {
+        address: String,
+    },
+    Borrower {
+        address: String,
+    },
+    Borrowers {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct MigrateMsg {}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ConfigResponse {
+    pub owner: String,
+    pub collateral_token: String,
+    pub overseer: String,
+    pub reward_contract: String,
+    pub reward_denom: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct StateResponse {
+    pub total_collateral: Uint128,
+    pub global_index: Decimal256,
+    pub penalty_index: Decimal256,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct AccruedPenaltiesResponse {
+    pub penalties: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct BorrowerResponse {
+    pub borrower: String,
+    pub balance: Uint128,
+    pub spendable: Uint128,
+    pub index: Decimal256,
+    pub pending_rewards: Decimal256,
+    pub penalty_index: Decimal256,
+    pub pending_penalties: Decimal256,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct BorrowersResponse {
+    pub borrowers: Vec<BorrowerResponse>,
+}
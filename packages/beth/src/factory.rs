@@ -0,0 +1,78 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct InstantiateMsg {
+    pub owner: String,
+    pub token_code_id: u64,
+    pub reward_code_id: u64,
+    pub converter_code_id: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ExecuteMsg {
+    ////////////////////
+    /// Owner's operations
+    ///////////////////
+
+    /// Instantiate the reward, converter and token contracts of a wormhole asset
+    /// and wire them together in one transaction.
+    /// The owner becomes the admin of the three contracts and the owner of the reward
+    /// contract, the factory stays the owner of the converter.
+    Deploy {
+        asset_name: String,
+        wormhole_token: String,
+        token_name: String,
+        token_symbol: String,
+        token_decimals: u8,
+        reward_denom: String,
+    },
+
+    /// Update the owner and the code ids used by the next deployments
+    UpdateConfig {
+        owner: Option<String>,
+        token_code_id: Option<u64>,
+        reward_code_id: Option<u64>,
+        converter_code_id: Option<u64>,
+    },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum QueryMsg {
+    Config {},
+    Deployment {
+        asset_name: String,
+    },
+    Deployments {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct MigrateMsg {}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ConfigResponse {
+    pub owner: String,
+    pub token_code_id: u64,
+    pub reward_code_id: u64,
+    pub converter_code_id: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct DeploymentResponse {
+    pub asset_name: String,
+    pub wormhole_token: String,
+    pub anchor_token: String,
+    pub reward_contract: String,
+    pub converter_contract: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct DeploymentsResponse {
+    pub deployments: Vec<DeploymentResponse>,
+}
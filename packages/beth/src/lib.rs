@@ -1,10 +1,22 @@
 mod tax_querier;
 
-pub use tax_querier::deduct_tax;
+pub use tax_querier::{add_tax, deduct_tax, TaxInfo};
+pub mod airdrop;
 pub mod converter;
+pub mod custody;
+pub mod factory;
+pub mod migration;
+pub mod observer;
 pub mod reward;
+pub mod reward_dispatcher;
 
-#[cfg(test)]
+/// Custom message of the contract responses, Terra messages with the `terra` feature
+#[cfg(feature = "terra")]
+pub type CustomMsg = terra_cosmwasm::TerraMsgWrapper;
+#[cfg(not(feature = "terra"))]
+pub type CustomMsg = cosmwasm_std::Empty;
+
+#[cfg(any(test, feature = "testing"))]
 pub mod mock_querier;
 
 #[cfg(test)]
@@ -0,0 +1,62 @@
+//! Contract versions and versioned state migrations.
+//!
+//! Every contract records its cw2 name and version at instantiate and at migration.
+//! A `migrate` entry point runs each state migration whose version is newer than the
+//! stored one, oldest first. Deployments that predate cw2 have no stored version and
+//! run every state migration, as their state has the layout of the first releases.
+
+use cosmwasm_std::{StdError, StdResult, Storage};
+use cw2::{set_contract_version, CONTRACT};
+
+pub use semver::Version;
+
+/// Records `contract_version` of `contract_name` at migration. The stored contract must
+/// be `contract_name` or one of `previous_names`, and not newer than `contract_version`.
+/// Returns the previous version, `None` for deployments that predate cw2.
+pub fn migrate_contract_version(
+    storage: &mut dyn Storage,
+    contract_name: &str,
+    contract_version: &str,
+    previous_names: &[&str],
+) -> StdResult<Option<Version>> {
+    let new_version = parse_version(contract_version)?;
+    let previous_version = match CONTRACT.may_load(storage)? {
+        Some(stored) => {
+            if stored.contract != contract_name
+                && !previous_names.contains(&stored.contract.as_str())
+            {
+                return Err(StdError::generic_err(format!(
+                    "Cannot migrate from {} to {}",
+                    stored.contract, contract_name
+                )));
+            }
+            let stored_version = parse_version(&stored.version)?;
+            if stored.contract == contract_name && stored_version > new_version {
+                return Err(StdError::generic_err(format!(
+                    "Cannot downgrade from {} to {}",
+                    stored_version, new_version
+                )));
+            }
+            Some(stored_version)
+        }
+        None => None,
+    };
+
+    set_contract_version(storage, contract_name, contract_version)?;
+    Ok(previous_version)
+}
+
+/// Whether a deployment at `previous_version` needs the state migration introduced in
+/// `version`, which is always the case for deployments that predate cw2
+pub fn is_older_than(previous_version: &Option<Version>, version: &str) -> StdResult<bool> {
+    let version = parse_version(version)?;
+    Ok(match previous_version {
+        Some(previous_version) => *previous_version < version,
+        None => true,
+    })
+}
+
+fn parse_version(version: &str) -> StdResult<Version> {
+    Version::parse(version)
+        .map_err(|_| StdError::generic_err(format!("Invalid contract version: {}", version)))
+}
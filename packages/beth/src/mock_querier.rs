@@ -1,34 +1,46 @@
 use cosmwasm_std::testing::{MockApi, MockQuerier, MockStorage};
 use cosmwasm_std::{
-    from_slice, to_binary, AllBalanceResponse, Api, BalanceResponse, BankQuery, CanonicalAddr,
-    Coin, ContractResult, Decimal, OwnedDeps, Querier, QuerierResult, QueryRequest, SystemError,
-    SystemResult, Uint128, WasmQuery,
+    from_binary, from_slice, to_binary, Binary, Coin, ContractResult, Decimal, OwnedDeps, Querier,
+    QuerierResult, QueryRequest, StdResult, SystemError, SystemResult, Uint128, WasmQuery,
 };
 use std::collections::HashMap;
 
-use cw20::TokenInfoResponse;
-use schemars::JsonSchema;
-use serde::{Deserialize, Serialize};
+use cw20::{BalanceResponse as Cw20BalanceResponse, Cw20QueryMsg, TokenInfoResponse};
+#[cfg(feature = "terra")]
 use terra_cosmwasm::{TaxCapResponse, TaxRateResponse, TerraQuery, TerraQueryWrapper, TerraRoute};
 
+#[cfg(feature = "terra")]
+type CustomQuery = TerraQueryWrapper;
+#[cfg(not(feature = "terra"))]
+type CustomQuery = cosmwasm_std::Empty;
+
 pub const MOCK_CONTRACT_ADDR: &str = "cosmos2contract";
 
+/// Answers the smart queries sent to one contract address
+pub type SmartQueryHandler = Box<dyn Fn(&Binary) -> StdResult<Binary>>;
+
 pub fn mock_dependencies(
     contract_balance: &[Coin],
 ) -> OwnedDeps<MockStorage, MockApi, WasmMockQuerier> {
-    let contract_addr = String::from(MOCK_CONTRACT_ADDR);
-    let custom_querier: WasmMockQuerier = WasmMockQuerier::new(
-        MockQuerier::new(&[(&contract_addr, contract_balance)]),
-        MockApi::default(),
-    );
+    mock_dependencies_with_querier(
+        MockQuerierBuilder::default()
+            .with_balance(MOCK_CONTRACT_ADDR, contract_balance)
+            .build(),
+    )
+}
 
+pub fn mock_dependencies_with_querier(
+    querier: WasmMockQuerier,
+) -> OwnedDeps<MockStorage, MockApi, WasmMockQuerier> {
     OwnedDeps {
         storage: MockStorage::default(),
         api: MockApi::default(),
-        querier: custom_querier,
+        querier,
     }
 }
 
+// only answered with the `terra` feature, other chains have no tax queries
+#[cfg_attr(not(feature = "terra"), allow(dead_code))]
 #[derive(Clone, Default)]
 pub struct TaxQuerier {
     rate: Decimal,
@@ -36,33 +48,126 @@ pub struct TaxQuerier {
 }
 
 impl TaxQuerier {
-    pub fn new(rate: Decimal, caps: &[(&String, &Uint128)]) -> Self {
+    pub fn new(rate: Decimal, caps: &[(&str, u128)]) -> Self {
         TaxQuerier {
             rate,
-            caps: caps_to_map(caps),
+            caps: caps
+                .iter()
+                .map(|(denom, cap)| (denom.to_string(), Uint128::new(*cap)))
+                .collect(),
         }
     }
 }
 
-pub(crate) fn caps_to_map(caps: &[(&String, &Uint128)]) -> HashMap<String, Uint128> {
-    let mut owner_map: HashMap<String, Uint128> = HashMap::new();
-    for (denom, cap) in caps.iter() {
-        owner_map.insert(denom.to_string(), **cap);
+#[derive(Clone, Default)]
+pub struct TokenQuerier {
+    infos: HashMap<String, TokenInfoResponse>,
+    // token contract -> holder -> balance
+    balances: HashMap<String, HashMap<String, Uint128>>,
+}
+
+impl TokenQuerier {
+    fn handle(&self, contract_addr: &str, msg: &Binary) -> Option<QuerierResult> {
+        if !self.infos.contains_key(contract_addr) && !self.balances.contains_key(contract_addr) {
+            return None;
+        }
+
+        let res = match from_binary(msg) {
+            Ok(Cw20QueryMsg::TokenInfo {}) => match self.infos.get(contract_addr) {
+                Some(info) => to_binary(info),
+                None => {
+                    return Some(SystemResult::Err(SystemError::InvalidRequest {
+                        error: format!("No token info registered for {}", contract_addr),
+                        request: msg.clone(),
+                    }))
+                }
+            },
+            Ok(Cw20QueryMsg::Balance { address }) => to_binary(&Cw20BalanceResponse {
+                balance: self
+                    .balances
+                    .get(contract_addr)
+                    .and_then(|balances| balances.get(&address))
+                    .copied()
+                    .unwrap_or_default(),
+            }),
+            _ => {
+                return Some(SystemResult::Err(SystemError::UnsupportedRequest {
+                    kind: format!("cw20 query to {}", contract_addr),
+                }))
+            }
+        };
+        Some(SystemResult::Ok(res.into()))
+    }
+}
+
+/// Builds a `WasmMockQuerier`, everything not registered falls through to the
+/// cosmwasm `MockQuerier`
+#[derive(Default)]
+pub struct MockQuerierBuilder {
+    querier: WasmMockQuerier,
+}
+
+impl MockQuerierBuilder {
+    /// Sets the native balance of `address`
+    pub fn with_balance(mut self, address: &str, balance: &[Coin]) -> Self {
+        self.querier.update_balance(address, balance);
+        self
+    }
+
+    /// Sets the treasury tax rate and the per denom caps, other denoms have no cap
+    pub fn with_tax(mut self, rate: Decimal, caps: &[(&str, u128)]) -> Self {
+        self.querier.update_tax(rate, caps);
+        self
+    }
+
+    /// Registers a CW20 token answering `TokenInfo` and `Balance` queries
+    pub fn with_token_info(mut self, token: &str, info: TokenInfoResponse) -> Self {
+        self.querier.update_token_info(token, info);
+        self
+    }
+
+    /// Sets the CW20 balance of `holder` in `token`
+    pub fn with_token_balance(mut self, token: &str, holder: &str, balance: Uint128) -> Self {
+        self.querier.update_token_balance(token, holder, balance);
+        self
+    }
+
+    /// Answers every smart query sent to `contract_addr` with `handler`
+    pub fn with_smart_handler<F>(mut self, contract_addr: &str, handler: F) -> Self
+    where
+        F: Fn(&Binary) -> StdResult<Binary> + 'static,
+    {
+        self.querier.update_smart_handler(contract_addr, handler);
+        self
+    }
+
+    pub fn build(self) -> WasmMockQuerier {
+        self.querier
     }
-    owner_map
 }
 
 pub struct WasmMockQuerier {
-    base: MockQuerier<TerraQueryWrapper>,
+    base: MockQuerier<CustomQuery>,
     tax_querier: TaxQuerier,
-    // first one is anchor token decimals, the second one is wormhole token decimals
-    decimals: (u8, u8),
+    token_querier: TokenQuerier,
+    smart_handlers: HashMap<String, SmartQueryHandler>,
+}
+
+impl Default for WasmMockQuerier {
+    fn default() -> Self {
+        WasmMockQuerier {
+            base: MockQuerier::new(&[]),
+            tax_querier: TaxQuerier::default(),
+            token_querier: TokenQuerier::default(),
+            smart_handlers: HashMap::new(),
+        }
+    }
 }
 
 impl Querier for WasmMockQuerier {
     fn raw_query(&self, bin_request: &[u8]) -> QuerierResult {
         // MockQuerier doesn't support Custom, so we ignore it completely here
-        let request: QueryRequest<TerraQueryWrapper> = match from_slice(bin_request) {
+        let request: QueryRequest<CustomQuery> = match from_slice(bin_request) {
             Ok(v) => v,
             Err(e) => {
                 return SystemResult::Err(SystemError::InvalidRequest {
@@ -76,128 +181,76 @@ impl Querier for WasmMockQuerier {
 }
 
 impl WasmMockQuerier {
-    pub fn handle_query(&self, request: &QueryRequest<TerraQueryWrapper>) -> QuerierResult {
+    pub fn builder() -> MockQuerierBuilder {
+        MockQuerierBuilder::default()
+    }
+
+    pub fn handle_query(&self, request: &QueryRequest<CustomQuery>) -> QuerierResult {
         match &request {
+            #[cfg(feature = "terra")]
             QueryRequest::Custom(TerraQueryWrapper { route, query_data }) => {
-                if &TerraRoute::Treasury == route {
-                    match query_data {
-                        TerraQuery::TaxRate {} => {
-                            let res = TaxRateResponse {
-                                rate: self.tax_querier.rate,
-                            };
-                            SystemResult::Ok(ContractResult::from(to_binary(&res)))
-                        }
-                        TerraQuery::TaxCap { denom } => {
-                            let cap = self
-                                .tax_querier
-                                .caps
-                                .get(denom)
-                                .copied()
-                                .unwrap_or_default();
-                            let res = TaxCapResponse { cap };
-                            SystemResult::Ok(ContractResult::from(to_binary(&res)))
-                        }
-                        _ => panic!("DO NOT ENTER HERE"),
+                match (route, query_data) {
+                    (TerraRoute::Treasury, TerraQuery::TaxRate {}) => {
+                        let res = TaxRateResponse {
+                            rate: self.tax_querier.rate,
+                        };
+                        SystemResult::Ok(ContractResult::from(to_binary(&res)))
                     }
-                } else {
-                    panic!("DO NOT ENTER HERE")
-                }
-            }
-            QueryRequest::Bank(BankQuery::AllBalances { address }) => {
-                if address == &String::from("reward") {
-                    let mut coins: Vec<Coin> = vec![];
-                    let luna = Coin {
-                        denom: "uluna".to_string(),
-                        amount: Uint128::new(1000u128),
-                    };
-                    coins.push(luna);
-                    let krt = Coin {
-                        denom: "ukrt".to_string(),
-                        amount: Uint128::new(1000u128),
-                    };
-                    coins.push(krt);
-                    let all_balances = AllBalanceResponse { amount: coins };
-                    SystemResult::Ok(ContractResult::from(to_binary(&all_balances)))
-                } else {
-                    unimplemented!()
+                    (TerraRoute::Treasury, TerraQuery::TaxCap { denom }) => {
+                        let cap = self
+                            .tax_querier
+                            .caps
+                            .get(denom)
+                            .copied()
+                            .unwrap_or_default();
+                        let res = TaxCapResponse { cap };
+                        SystemResult::Ok(ContractResult::from(to_binary(&res)))
+                    }
+                    _ => SystemResult::Err(SystemError::UnsupportedRequest {
+                        kind: format!("{:?}", query_data),
+                    }),
                 }
             }
-            QueryRequest::Bank(BankQuery::Balance { address, denom }) => {
-                if address == &String::from("reward") && denom == "uusd" {
-                    let bank_res = BalanceResponse {
-                        amount: Coin {
-                            amount: Uint128::new(2000u128),
-                            denom: denom.to_string(),
-                        },
-                    };
-                    SystemResult::Ok(ContractResult::from(to_binary(&bank_res)))
-                } else {
-                    unimplemented!()
+            QueryRequest::Wasm(WasmQuery::Smart { contract_addr, msg }) => {
+                if let Some(handler) = self.smart_handlers.get(contract_addr) {
+                    return SystemResult::Ok(ContractResult::from(handler(msg)));
                 }
-            }
-            QueryRequest::Wasm(WasmQuery::Smart {
-                contract_addr,
-                msg: _,
-            }) => {
-                if contract_addr == "wormhole_token0000" {
-                    SystemResult::Ok(ContractResult::from(to_binary(&TokenInfoResponse {
-                        name: "wormhole_token".to_string(),
-                        symbol: "WORM".to_string(),
-                        decimals: self.decimals.1,
-                        total_supply: Default::default(),
-                    })))
-                } else {
-                    SystemResult::Ok(ContractResult::from(to_binary(&TokenInfoResponse {
-                        name: "anchor_token".to_string(),
-                        symbol: "ANC".to_string(),
-                        decimals: self.decimals.0,
-                        total_supply: Default::default(),
-                    })))
+                match self.token_querier.handle(contract_addr, msg) {
+                    Some(res) => res,
+                    None => SystemResult::Err(SystemError::NoSuchContract {
+                        addr: contract_addr.clone(),
+                    }),
                 }
             }
             _ => self.base.handle_query(request),
         }
     }
-}
 
-#[derive(Clone, Default)]
-pub struct TokenQuerier {
-    balances: HashMap<String, HashMap<String, Uint128>>,
-}
-
-impl WasmMockQuerier {
-    pub fn new<A: Api>(base: MockQuerier<TerraQueryWrapper>, _api: A) -> Self {
-        WasmMockQuerier {
-            base,
-            tax_querier: TaxQuerier::default(),
-            decimals: (6, 8),
-        }
+    pub fn update_balance(&mut self, address: &str, balance: &[Coin]) {
+        self.base.update_balance(address, balance.to_vec());
     }
 
-    // configure the tax mock querier
-    pub fn with_tax(&mut self, rate: Decimal, caps: &[(&String, &Uint128)]) {
+    pub fn update_tax(&mut self, rate: Decimal, caps: &[(&str, u128)]) {
         self.tax_querier = TaxQuerier::new(rate, caps);
     }
 
-    pub fn set_decimals(&mut self, anchor_decimals: u8, wormhole_decimals: u8) {
-        self.decimals = (anchor_decimals, wormhole_decimals)
+    pub fn update_token_info(&mut self, token: &str, info: TokenInfoResponse) {
+        self.token_querier.infos.insert(token.to_string(), info);
     }
-}
 
-#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
-#[serde(rename_all = "snake_case")]
-pub struct TokenInfo {
-    pub name: String,
-    pub symbol: String,
-    pub decimals: u8,
-    pub total_supply: Uint128,
-    pub mint: Option<MinterData>,
-    pub owner: CanonicalAddr,
-}
+    pub fn update_token_balance(&mut self, token: &str, holder: &str, balance: Uint128) {
+        self.token_querier
+            .balances
+            .entry(token.to_string())
+            .or_default()
+            .insert(holder.to_string(), balance);
+    }
 
-#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
-pub struct MinterData {
-    pub minter: CanonicalAddr,
-    /// cap is how many more tokens can be issued by the minter
-    pub cap: Option<Uint128>,
+    pub fn update_smart_handler<F>(&mut self, contract_addr: &str, handler: F)
+    where
+        F: Fn(&Binary) -> StdResult<Binary> + 'static,
+    {
+        self.smart_handlers
+            .insert(contract_addr.to_string(), Box::new(handler));
+    }
 }
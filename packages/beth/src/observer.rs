@@ -0,0 +1,24 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use cosmwasm_std::Uint128;
+
+/// Signed change of a bETH balance
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum BalanceDelta {
+    Increase(Uint128),
+    Decrease(Uint128),
+}
+
+/// Messages the bETH token sends to the registered observer contracts
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ObserverExecuteMsg {
+    /// Sent for every account whose balance changed on a transfer, mint or burn
+    BalanceChangeHook {
+        address: String,
+        delta: BalanceDelta,
+        new_balance: Uint128,
+    },
+}
@@ -1,7 +1,13 @@
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
+use cosmwasm_bignumber::Decimal256;
 use cosmwasm_std::{Decimal, Uint128};
+use cw2::ContractVersion;
+use cw20::Cw20ReceiveMsg;
+
+/// Version of the `ExportState` output, bumped on breaking changes of its schema
+pub const EXPORT_VERSION: u32 = 1;
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct InstantiateMsg {
@@ -20,7 +26,39 @@ pub enum ExecuteMsg {
     PostInitialize { token_contract: String },
 
     /// Update contract configuration, currently only owner is updatable
-    UpdateConfig { owner: String },
+    /// The protocol fee rate only applies to the rewards received after the update
+    UpdateConfig {
+        owner: Option<String>,
+        protocol_fee_rate: Option<Decimal>,
+        treasury: Option<String>,
+    },
+
+    /// Seed a fresh deployment with the holders and the lock positions of another
+    /// deployment's `ExportState`, only before the token contract is set. The contract must
+    /// hold at least `prev_reward_balance`, and the locked and penalty bETH, before the
+    /// holders can claim.
+    ImportHolders {
+        /// state of the export, the totals are rebuilt from the holders
+        state: StateResponse,
+        holders: Vec<HolderResponse>,
+        /// lock positions of `holders`
+        lock_positions: Vec<ExportedLockPosition>,
+    },
+
+    /// Remove a page of the holders with nothing left to claim and count the holders stored
+    /// before `holder_count`. Pages going over all the holders from the first one, each
+    /// starting after the last holder of the previous page, complete the count
+    PruneHolders {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+
+    /// Update the lock parameters, they only apply to the positions locked afterwards
+    UpdateLockConfig {
+        max_lock_duration: Option<u64>,
+        max_boost: Option<Decimal>,
+        early_unlock_penalty: Option<Decimal>,
+    },
 
     ////////////////////
     /// bAsset's operations
@@ -34,6 +72,13 @@ pub enum ExecuteMsg {
     /// Withdraw rewards to pending rewards
     /// Set current reward index to global index
     DecreaseBalance { address: String, amount: Uint128 },
+    /// Decrease the staking balance of `from` by the sum of the transfers
+    /// and increase the staking balance of every recipient
+    /// Withdraw rewards to pending rewards for all of them
+    BatchTransferBalance {
+        from: String,
+        transfers: Vec<(String, Uint128)>,
+    },
 
     ////////////////////
     /// User's operations
@@ -41,6 +86,31 @@ pub enum ExecuteMsg {
 
     /// return the accrued reward in uusd to the user.
     ClaimRewards { recipient: Option<String> },
+
+    /// Deposit `reward_denom` and distribute it to the current holders right away,
+    /// instead of waiting for the next balance change to notice it
+    DepositRewards {},
+
+    /// Send the accumulated protocol fee to the treasury, net of tax
+    WithdrawProtocolFee {},
+
+    /// Send the bETH of the early unlock penalties owed to the holder
+    ClaimPenalties { recipient: Option<String> },
+
+    /// Receive interface for send token.
+    /// Lock the sent bETH for a boosted reward weight
+    Receive(Cw20ReceiveMsg),
+
+    /// Withdraw the bETH of a lock position, before its end the early unlock penalty
+    /// is redistributed to the other holders by reward weight
+    Unlock { lock_id: u64 },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum Cw20HookMsg {
+    /// Lock the sent bETH for `duration` seconds
+    Lock { duration: u64 },
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -48,30 +118,130 @@ pub enum ExecuteMsg {
 pub enum QueryMsg {
     Config {},
     State {},
+    /// Lifetime reward and claim counters
+    Stats {},
+    /// Rewards owed to the holders and the treasury against the reward balance
+    Solvency {},
     AccruedRewards {
         address: String,
     },
     Holder {
         address: String,
     },
+    /// Holders between `start_after` and `start_before` by address, ascending by default.
+    /// A descending page continues with `start_before` set to its last address.
     Holders {
+        start_after: Option<String>,
+        start_before: Option<String>,
+        limit: Option<u32>,
+        order: Option<OrderBy>,
+    },
+    /// Holders of `addresses` in the same order, unknown addresses read as empty holders
+    HoldersByAddresses {
+        addresses: Vec<String>,
+    },
+    /// Holders with the largest balances first. Fails while `holders_indexed` is false,
+    /// the holders stored before the balance index are missing until `PruneHolders` goes
+    /// over all of them.
+    TopHolders {
+        limit: Option<u32>,
+    },
+    /// Config, state and a page of holders, for moving the contract to another chain
+    ExportState {
         start_after: Option<String>,
         limit: Option<u32>,
     },
+    LockConfig {},
+    LockPositions {
+        address: String,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    /// Balance, locked amount and reward weight of a holder
+    Weight {
+        address: String,
+    },
+    /// Updates of the global index, oldest first
+    RewardHistory {
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    /// Global index at the end of the block at `height`
+    GlobalIndexAt {
+        height: u64,
+    },
+    /// Rewards distributed over the last `window_seconds` and their annualized rate
+    RewardRate {
+        window_seconds: u64,
+    },
 }
 
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct MigrateMsg {}
+
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct ConfigResponse {
     pub owner: String,
     pub reward_denom: String,
     pub token_contract: Option<String>,
+    /// share of the incoming rewards kept for the treasury
+    pub protocol_fee_rate: Decimal,
+    pub treasury: Option<String>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct StateResponse {
-    pub global_index: Decimal,
+    pub global_index: Decimal256,
     pub total_balance: Uint128,
     pub prev_reward_balance: Uint128,
+    /// bETH held by the contract in lock positions
+    pub total_locked: Uint128,
+    /// reward weight of the lock positions
+    pub total_locked_weight: Uint128,
+    /// reward weight of all the holders, the global index grows by rewards / weight
+    pub total_effective_balance: Uint128,
+    /// early unlock penalties in bETH per unit of reward weight
+    pub lock_penalty_index: Decimal256,
+    /// protocol fee accumulated for the treasury, part of `prev_reward_balance`
+    pub protocol_fee: Uint128,
+    /// holders with a balance, a lock or rewards to claim
+    pub holder_count: u64,
+    /// false until `PruneHolders` went over the holders stored before the count and the
+    /// balance index, which `holder_count` and `TopHolders` miss until then. Ignored by
+    /// `ImportHolders`
+    #[serde(default)]
+    pub holders_indexed: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct StatsResponse {
+    /// rewards recognized by the global index, protocol fee included
+    pub total_rewards: Uint128,
+    /// rewards claimed by the holders before tax
+    pub total_claimed: Uint128,
+    /// rewards received by the holders after tax
+    pub total_claimed_net: Uint128,
+    /// tax paid on the claims
+    pub total_tax: Uint128,
+    pub claims: u64,
+    /// holders that claimed at least once
+    pub claimants: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct SolvencyResponse {
+    pub reward_balance: Uint128,
+    /// rewards the holders can claim, accrued and pending
+    pub liabilities: Uint128,
+    /// protocol fee not withdrawn by the treasury yet
+    pub protocol_fee: Uint128,
+    /// reward balance above the liabilities and the protocol fee
+    pub surplus: Uint128,
+    /// liabilities and protocol fee above the reward balance
+    pub deficit: Uint128,
+    /// rewards lost to rounding, part of the surplus
+    pub reward_dust: Decimal256,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -83,11 +253,137 @@ pub struct AccruedRewardsResponse {
 pub struct HolderResponse {
     pub address: String,
     pub balance: Uint128,
-    pub index: Decimal,
-    pub pending_rewards: Decimal,
+    pub index: Decimal256,
+    pub pending_rewards: Decimal256,
+    /// rewards claimable now, including the rewards the contract received since the last
+    /// update of the global index, ignored by `ImportHolders`
+    #[serde(default)]
+    pub accrued_rewards: Uint128,
+    /// rewards claimed before tax, kept after the holder is pruned
+    #[serde(default)]
+    pub total_claimed: Uint128,
+    #[serde(default)]
+    pub last_claim_time: Option<u64>,
+    #[serde(default)]
+    pub locked_amount: Uint128,
+    #[serde(default)]
+    pub locked_weight: Uint128,
+    /// `lock_penalty_index` when the penalties of the holder were last settled
+    #[serde(default)]
+    pub penalty_index: Decimal256,
+    /// early unlock penalties owed to the holder in bETH
+    #[serde(default)]
+    pub pending_penalties: Decimal256,
+    /// penalties claimable now, ignored by `ImportHolders`
+    #[serde(default)]
+    pub accrued_penalties: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum OrderBy {
+    Asc,
+    Desc,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct HoldersResponse {
     pub holders: Vec<HolderResponse>,
 }
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct LockConfigResponse {
+    /// locking is disabled while zero
+    pub max_lock_duration: u64,
+    /// extra reward weight of a position locked for `max_lock_duration`, linear in the duration
+    pub max_boost: Decimal,
+    pub early_unlock_penalty: Decimal,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct LockPositionResponse {
+    pub lock_id: u64,
+    pub amount: Uint128,
+    /// reward weight, the amount times the boost
+    pub weight: Uint128,
+    pub start: u64,
+    pub end: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct LockPositionsResponse {
+    pub positions: Vec<LockPositionResponse>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct WeightResponse {
+    pub address: String,
+    pub balance: Uint128,
+    pub locked_amount: Uint128,
+    /// reward weight, the balance plus the weight of the lock positions
+    pub effective_balance: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ExportStateResponse {
+    /// `EXPORT_VERSION` of the contract that exported the state
+    pub version: u32,
+    /// cw2 name and version, unset for deployments that predate cw2
+    pub contract: Option<ContractVersion>,
+    pub config: ConfigResponse,
+    pub state: StateResponse,
+    pub holders: Vec<HolderResponse>,
+    /// lock positions of `holders`
+    pub lock_positions: Vec<ExportedLockPosition>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ExportedLockPosition {
+    pub address: String,
+    pub lock_id: u64,
+    pub amount: Uint128,
+    pub weight: Uint128,
+    pub start: u64,
+    pub end: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct RewardCheckpointResponse {
+    pub id: u64,
+    pub height: u64,
+    pub time: u64,
+    /// rewards recognized by the update, protocol fee included. Zero for the checkpoints
+    /// recorded at instantiation, migration and import.
+    pub amount: Uint128,
+    pub protocol_fee: Uint128,
+    /// reward weight the rewards were split over, the balances and the lock weights
+    pub total_balance: Uint128,
+    /// global index after the update
+    pub global_index: Decimal256,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct RewardHistoryResponse {
+    pub checkpoints: Vec<RewardCheckpointResponse>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct GlobalIndexAtResponse {
+    pub height: u64,
+    pub global_index: Decimal256,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct RewardRateResponse {
+    /// start of the window, or of the checkpoints read when they do not cover it
+    pub start_time: u64,
+    pub end_time: u64,
+    /// rewards distributed to the holders in the window, net of the protocol fee
+    pub rewards: Uint128,
+    /// time weighted average of the reward weight, the balances and the lock weights
+    pub average_total_balance: Uint128,
+    /// `reward_denom` distributed per year to one unit of reward weight at the rate of the window
+    pub annualized_rate: Decimal256,
+    /// false when the window starts before the history or holds too many checkpoints
+    pub complete: bool,
+}
@@ -0,0 +1,102 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use cosmwasm_std::Decimal;
+use cw20::Cw20ReceiveMsg;
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct InstantiateMsg {
+    pub owner: String,
+    pub reward_contract: String,
+    pub treasury: String,
+    pub reward_denom: String,
+    /// share of the swapped rewards sent to the treasury
+    pub protocol_fee: Decimal,
+    /// max spread of the CW20 swaps, the pair default when not set
+    pub max_spread: Option<Decimal>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ExecuteMsg {
+    /// Receive interface for send token.
+    /// Swap CW20 rewards to the reward denom through their registered pair
+    /// and forward them
+    Receive(Cw20ReceiveMsg),
+
+    /// Swap the native rewards held by the contract to the reward denom
+    /// and forward them
+    DispatchRewards {},
+
+    ////////////////////
+    /// Owner's operations
+    ///////////////////
+
+    /// Update contract configuration
+    UpdateConfig {
+        owner: Option<String>,
+        reward_contract: Option<String>,
+        treasury: Option<String>,
+        protocol_fee: Option<Decimal>,
+        max_spread: Option<Decimal>,
+        /// go back to the pair default max spread, can not be set along with `max_spread`
+        #[serde(default)]
+        clear_max_spread: bool,
+    },
+
+    /// Set the pair swapping a CW20 reward token to the reward denom,
+    /// `None` removes it
+    UpdateSwapPair {
+        asset_token: String,
+        pair: Option<String>,
+    },
+
+    ////////////////////
+    /// Contract's operations
+    ///////////////////
+
+    /// Send the protocol fee to the treasury and deposit the rest of the
+    /// reward denom balance to the reward contract, executed after the swaps
+    ForwardRewards {},
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum Cw20HookMsg {
+    DispatchRewards {},
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum QueryMsg {
+    Config {},
+    SwapPairs {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct MigrateMsg {}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ConfigResponse {
+    pub owner: String,
+    pub reward_contract: String,
+    pub treasury: String,
+    pub reward_denom: String,
+    pub protocol_fee: Decimal,
+    pub max_spread: Option<Decimal>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct SwapPairResponse {
+    pub asset_token: String,
+    pub pair: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct SwapPairsResponse {
+    pub swap_pairs: Vec<SwapPairResponse>,
+}
@@ -1,26 +1,102 @@
-use cosmwasm_std::{Coin, Decimal, Deps, StdResult, Uint128};
+use cosmwasm_std::{Coin, Decimal, Deps, StdError, StdResult, Uint128};
 
+#[cfg(feature = "terra")]
 use terra_cosmwasm::TerraQuerier;
 
 static DECIMAL_FRACTION: Uint128 = Uint128::new(1_000_000_000_000_000_000u128);
 
-pub fn compute_tax(deps: Deps, coin: &Coin) -> StdResult<Uint128> {
-    let terra_querier = TerraQuerier::new(&deps.querier);
-    let tax_rate: Decimal = (terra_querier.query_tax_rate()?).rate;
-    let tax_cap: Uint128 = (terra_querier.query_tax_cap(coin.denom.to_string())?).cap;
-    Ok(std::cmp::min(
-        coin.amount.checked_sub(coin.amount.multiply_ratio(
+/// Tax rate and the tax caps of several denoms, queried once and reused for
+/// every coin sent in the same execution
+#[derive(Clone, Debug, PartialEq)]
+pub struct TaxInfo {
+    pub rate: Decimal,
+    pub caps: Vec<(String, Uint128)>,
+}
+
+impl TaxInfo {
+    #[cfg(feature = "terra")]
+    pub fn query(deps: Deps, denoms: &[&str]) -> StdResult<Self> {
+        let terra_querier = TerraQuerier::new(&deps.querier);
+        let rate: Decimal = (terra_querier.query_tax_rate()?).rate;
+        let caps = denoms
+            .iter()
+            .map(|denom| {
+                let cap: Uint128 = (terra_querier.query_tax_cap(denom.to_string())?).cap;
+                Ok((denom.to_string(), cap))
+            })
+            .collect::<StdResult<Vec<_>>>()?;
+        Ok(TaxInfo { rate, caps })
+    }
+
+    /// Chains other than Terra do not tax transfers
+    #[cfg(not(feature = "terra"))]
+    pub fn query(_deps: Deps, denoms: &[&str]) -> StdResult<Self> {
+        Ok(TaxInfo {
+            rate: Decimal::zero(),
+            caps: denoms
+                .iter()
+                .map(|denom| (denom.to_string(), Uint128::zero()))
+                .collect(),
+        })
+    }
+
+    /// Tax cap of `denom`, which must be one of the queried denoms
+    pub fn cap(&self, denom: &str) -> StdResult<Uint128> {
+        self.caps
+            .iter()
+            .find(|(cap_denom, _)| cap_denom == denom)
+            .map(|(_, cap)| *cap)
+            .ok_or_else(|| StdError::generic_err(format!("Tax cap of {} is not queried", denom)))
+    }
+
+    /// Tax included in `coin` when it is sent net of tax
+    pub fn compute_tax(&self, coin: &Coin) -> StdResult<Uint128> {
+        let cap = self.cap(&coin.denom)?;
+        Ok(std::cmp::min(
+            coin.amount.checked_sub(self.untaxed_amount(coin.amount))?,
+            cap,
+        ))
+    }
+
+    /// Net coin to send so that the sent amount plus the tax is `coin`
+    pub fn deduct_tax(&self, coin: Coin) -> StdResult<Coin> {
+        let tax_amount = self.compute_tax(&coin)?;
+        Ok(Coin {
+            denom: coin.denom,
+            amount: coin.amount.checked_sub(tax_amount)?,
+        })
+    }
+
+    /// Smallest gross coin whose net of tax, as computed by `deduct_tax`, is `coin`
+    pub fn add_tax(&self, coin: Coin) -> StdResult<Coin> {
+        let cap = self.cap(&coin.denom)?;
+        // the uncapped gross amount is rounded up, the net of it is rounded down
+        let mut gross = coin.amount.multiply_ratio(
+            DECIMAL_FRACTION * self.rate + DECIMAL_FRACTION,
+            DECIMAL_FRACTION,
+        );
+        if self.untaxed_amount(gross) < coin.amount {
+            gross = gross.checked_add(Uint128::new(1))?;
+        }
+        let tax_amount = std::cmp::min(gross.checked_sub(coin.amount)?, cap);
+        Ok(Coin {
+            denom: coin.denom,
+            amount: coin.amount.checked_add(tax_amount)?,
+        })
+    }
+
+    fn untaxed_amount(&self, amount: Uint128) -> Uint128 {
+        amount.multiply_ratio(
             DECIMAL_FRACTION,
-            DECIMAL_FRACTION * tax_rate + DECIMAL_FRACTION,
-        ))?,
-        tax_cap,
-    ))
+            DECIMAL_FRACTION * self.rate + DECIMAL_FRACTION,
+        )
+    }
 }
 
 pub fn deduct_tax(deps: Deps, coin: Coin) -> StdResult<Coin> {
-    let tax_amount = compute_tax(deps, &coin)?;
-    Ok(Coin {
-        denom: coin.denom,
-        amount: coin.amount.checked_sub(tax_amount)?,
-    })
+    TaxInfo::query(deps, &[&coin.denom])?.deduct_tax(coin)
+}
+
+pub fn add_tax(deps: Deps, coin: Coin) -> StdResult<Coin> {
+    TaxInfo::query(deps, &[&coin.denom])?.add_tax(coin)
 }
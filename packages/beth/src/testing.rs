@@ -1,15 +1,19 @@
-use crate::deduct_tax;
-use crate::mock_querier::mock_dependencies;
-use cosmwasm_std::{Coin, Decimal, Uint128};
+use crate::migration::{is_older_than, migrate_contract_version, Version};
+use crate::mock_querier::{mock_dependencies, mock_dependencies_with_querier, WasmMockQuerier};
+use crate::{add_tax, deduct_tax, TaxInfo};
+use cosmwasm_std::{
+    coins, from_binary, to_binary, BalanceResponse, BankQuery, Coin, Decimal, QueryRequest,
+    StdError, Uint128, WasmQuery,
+};
+use cw20::{BalanceResponse as Cw20BalanceResponse, Cw20QueryMsg, TokenInfoResponse};
 
+#[cfg(feature = "terra")]
 #[test]
 fn test_deduct_tax() {
     let mut deps = mock_dependencies(&[]);
 
-    deps.querier.with_tax(
-        Decimal::percent(1),
-        &[(&"uusd".to_string(), &Uint128::from(1000000u128))],
-    );
+    deps.querier
+        .update_tax(Decimal::percent(1), &[("uusd", 1000000u128)]);
 
     // cap to 1000000
     assert_eq!(
@@ -29,3 +33,270 @@ fn test_deduct_tax() {
         }
     );
 }
+
+#[cfg(feature = "terra")]
+#[test]
+fn test_add_tax() {
+    let mut deps = mock_dependencies(&[]);
+
+    deps.querier
+        .update_tax(Decimal::percent(1), &[("uusd", 1000000u128)]);
+
+    // cap to 1000000
+    assert_eq!(
+        add_tax(deps.as_ref(), Coin::new(9999000000u128, "uusd")).unwrap(),
+        Coin::new(10000000000u128, "uusd")
+    );
+
+    // normal tax, rounded up so that the net of the gross coin is the input
+    assert_eq!(
+        add_tax(deps.as_ref(), Coin::new(49504950u128, "uusd")).unwrap(),
+        Coin::new(50000000u128, "uusd")
+    );
+    assert_eq!(
+        add_tax(deps.as_ref(), Coin::new(150u128, "uusd")).unwrap(),
+        Coin::new(152u128, "uusd")
+    );
+
+    // untaxed denoms have no cap
+    assert_eq!(
+        add_tax(deps.as_ref(), Coin::new(100u128, "uluna")).unwrap(),
+        Coin::new(100u128, "uluna")
+    );
+}
+
+#[cfg(feature = "terra")]
+#[test]
+fn test_tax_info() {
+    let mut deps = mock_dependencies(&[]);
+
+    deps.querier
+        .update_tax(Decimal::percent(1), &[("uusd", 1000000u128)]);
+
+    // one query of the rate for all the denoms
+    let tax_info = TaxInfo::query(deps.as_ref(), &["uusd", "uluna"]).unwrap();
+    assert_eq!(
+        tax_info,
+        TaxInfo {
+            rate: Decimal::percent(1),
+            caps: vec![
+                ("uusd".to_string(), Uint128::new(1000000u128)),
+                ("uluna".to_string(), Uint128::zero()),
+            ],
+        }
+    );
+
+    for amount in [100u128, 150u128, 50000000u128, 10000000000u128] {
+        for denom in ["uusd", "uluna"] {
+            let coin = Coin::new(amount, denom);
+            assert_eq!(
+                tax_info.deduct_tax(coin.clone()).unwrap(),
+                deduct_tax(deps.as_ref(), coin.clone()).unwrap()
+            );
+            assert_eq!(
+                tax_info.add_tax(coin.clone()).unwrap(),
+                add_tax(deps.as_ref(), coin.clone()).unwrap()
+            );
+
+            // the net of the gross coin is the input
+            let gross = tax_info.add_tax(coin.clone()).unwrap();
+            assert_eq!(tax_info.deduct_tax(gross).unwrap(), coin);
+        }
+    }
+
+    assert_eq!(
+        tax_info.add_tax(Coin::new(100u128, "ukrw")).unwrap_err(),
+        StdError::generic_err("Tax cap of ukrw is not queried")
+    );
+}
+
+#[cfg(feature = "terra")]
+mod tax_properties {
+    use crate::TaxInfo;
+    use cosmwasm_std::{Coin, Decimal, Uint128};
+    use proptest::prelude::*;
+
+    proptest! {
+        #[test]
+        fn net_of_gross_coin_is_input(
+            rate_permille in 0u64..=100,
+            cap in 0u128..=10_000_000_000,
+            amount in 0u128..=1_000_000_000_000_000,
+        ) {
+            let tax_info = TaxInfo {
+                rate: Decimal::permille(rate_permille),
+                caps: vec![("uusd".to_string(), Uint128::new(cap))],
+            };
+            let coin = Coin::new(amount, "uusd");
+
+            let gross = tax_info.add_tax(coin.clone()).unwrap();
+            prop_assert!(gross.amount - coin.amount <= Uint128::new(cap));
+            prop_assert_eq!(tax_info.deduct_tax(gross).unwrap(), coin);
+        }
+    }
+}
+
+#[cfg(not(feature = "terra"))]
+#[test]
+fn test_no_tax() {
+    let deps = mock_dependencies(&[]);
+
+    let tax_info = TaxInfo::query(deps.as_ref(), &["uusd"]).unwrap();
+    assert_eq!(tax_info.rate, Decimal::zero());
+    assert_eq!(tax_info.cap("uusd").unwrap(), Uint128::zero());
+
+    let coin = Coin::new(50000000u128, "uusd");
+    assert_eq!(deduct_tax(deps.as_ref(), coin.clone()).unwrap(), coin);
+    assert_eq!(add_tax(deps.as_ref(), coin.clone()).unwrap(), coin);
+}
+
+#[test]
+fn test_mock_querier_builder() {
+    let token_info = TokenInfoResponse {
+        name: "wormhole_token".to_string(),
+        symbol: "WORM".to_string(),
+        decimals: 8,
+        total_supply: Uint128::new(100),
+    };
+    let deps = mock_dependencies_with_querier(
+        WasmMockQuerier::builder()
+            .with_balance("addr0000", &coins(100, "uusd"))
+            .with_token_info("token0000", token_info.clone())
+            .with_token_balance("token0000", "addr0000", Uint128::new(10))
+            .with_smart_handler("contract0000", |msg| match from_binary(msg)? {
+                Cw20QueryMsg::Balance { address } => to_binary(&address),
+                _ => Err(StdError::generic_err("unsupported")),
+            })
+            .build(),
+    );
+
+    // native balances
+    let res: BalanceResponse = deps
+        .as_ref()
+        .querier
+        .query(&QueryRequest::Bank(BankQuery::Balance {
+            address: "addr0000".to_string(),
+            denom: "uusd".to_string(),
+        }))
+        .unwrap();
+    assert_eq!(res.amount, Coin::new(100, "uusd"));
+
+    // cw20 token info and balances, unknown holders have none
+    let res: TokenInfoResponse = deps
+        .as_ref()
+        .querier
+        .query_wasm_smart("token0000", &Cw20QueryMsg::TokenInfo {})
+        .unwrap();
+    assert_eq!(res, token_info);
+    let res: Cw20BalanceResponse = deps
+        .as_ref()
+        .querier
+        .query_wasm_smart(
+            "token0000",
+            &Cw20QueryMsg::Balance {
+                address: "addr0000".to_string(),
+            },
+        )
+        .unwrap();
+    assert_eq!(res.balance, Uint128::new(10));
+    let res: Cw20BalanceResponse = deps
+        .as_ref()
+        .querier
+        .query_wasm_smart(
+            "token0000",
+            &Cw20QueryMsg::Balance {
+                address: "addr0001".to_string(),
+            },
+        )
+        .unwrap();
+    assert_eq!(res.balance, Uint128::zero());
+
+    // smart handlers, errors are returned to the caller
+    let res: String = deps
+        .as_ref()
+        .querier
+        .query(&QueryRequest::Wasm(WasmQuery::Smart {
+            contract_addr: "contract0000".to_string(),
+            msg: to_binary(&Cw20QueryMsg::Balance {
+                address: "addr0000".to_string(),
+            })
+            .unwrap(),
+        }))
+        .unwrap();
+    assert_eq!(res, "addr0000");
+    assert!(deps
+        .as_ref()
+        .querier
+        .query_wasm_smart::<String>("contract0000", &Cw20QueryMsg::TokenInfo {})
+        .is_err());
+
+    // unknown contracts
+    assert!(deps
+        .as_ref()
+        .querier
+        .query_wasm_smart::<TokenInfoResponse>("token0001", &Cw20QueryMsg::TokenInfo {})
+        .is_err());
+}
+
+#[test]
+fn test_migrate_contract_version() {
+    let mut deps = mock_dependencies(&[]);
+
+    // deployments that predate cw2 have no version
+    assert_eq!(
+        migrate_contract_version(&mut deps.storage, "crates.io:beth", "1.0.0", &[]).unwrap(),
+        None
+    );
+
+    // same version and upgrades
+    assert_eq!(
+        migrate_contract_version(&mut deps.storage, "crates.io:beth", "1.0.0", &[]).unwrap(),
+        Some(Version::new(1, 0, 0))
+    );
+    assert_eq!(
+        migrate_contract_version(&mut deps.storage, "crates.io:beth", "1.1.0", &[]).unwrap(),
+        Some(Version::new(1, 0, 0))
+    );
+
+    // downgrades
+    assert_eq!(
+        migrate_contract_version(&mut deps.storage, "crates.io:beth", "1.0.1", &[]).unwrap_err(),
+        StdError::generic_err("Cannot downgrade from 1.1.0 to 1.0.1")
+    );
+
+    // other contracts, unless they are listed as previous names
+    assert_eq!(
+        migrate_contract_version(&mut deps.storage, "crates.io:other", "2.0.0", &[]).unwrap_err(),
+        StdError::generic_err("Cannot migrate from crates.io:beth to crates.io:other")
+    );
+    assert_eq!(
+        migrate_contract_version(
+            &mut deps.storage,
+            "crates.io:other",
+            "0.1.0",
+            &["crates.io:beth"]
+        )
+        .unwrap(),
+        Some(Version::new(1, 1, 0))
+    );
+    let stored = cw2::get_contract_version(&deps.storage).unwrap();
+    assert_eq!(stored.contract, "crates.io:other");
+    assert_eq!(stored.version, "0.1.0");
+
+    assert_eq!(
+        migrate_contract_version(&mut deps.storage, "crates.io:other", "latest", &[]).unwrap_err(),
+        StdError::generic_err("Invalid contract version: latest")
+    );
+}
+
+#[test]
+fn test_is_older_than() {
+    assert!(is_older_than(&None, "0.1.0").unwrap());
+    assert!(is_older_than(&Some(Version::new(1, 2, 0)), "1.3.0").unwrap());
+    assert!(!is_older_than(&Some(Version::new(1, 3, 0)), "1.3.0").unwrap());
+    assert!(!is_older_than(&Some(Version::new(1, 4, 0)), "1.3.0").unwrap());
+    assert_eq!(
+        is_older_than(&None, "latest").unwrap_err(),
+        StdError::generic_err("Invalid contract version: latest")
+    );
+}
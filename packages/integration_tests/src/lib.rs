@@ -0,0 +1,142 @@
+//! Terra chain stubs for running the bETH contracts together on cw-multi-test.
+//!
+//! `TerraModule` answers the treasury tax queries used by `beth::deduct_tax` and
+//! `TerraBank` charges that tax on every native transfer, the same way the chain
+//! does, so the tests catch contracts that would send more than they hold.
+
+use std::collections::HashMap;
+
+use anyhow::{bail, Result as AnyResult};
+use cosmwasm_std::{
+    to_binary, Addr, Api, BankMsg, BankQuery, Binary, BlockInfo, Coin, Decimal, Storage, Uint128,
+};
+use cw_multi_test::{App, AppBuilder, AppResponse, Bank, BankKeeper, CustomHandler};
+use terra_cosmwasm::{
+    TaxCapResponse, TaxRateResponse, TerraMsgWrapper, TerraQuery, TerraQueryWrapper,
+};
+
+pub type TerraApp = App<TerraMsgWrapper, TerraQueryWrapper>;
+
+/// Tax rate and per denom caps, denoms without a cap are not taxed
+#[derive(Clone, Debug, Default)]
+pub struct TaxConfig {
+    pub rate: Decimal,
+    pub caps: HashMap<String, Uint128>,
+}
+
+impl TaxConfig {
+    pub fn new(rate: Decimal, caps: &[(&str, u128)]) -> Self {
+        TaxConfig {
+            rate,
+            caps: caps
+                .iter()
+                .map(|(denom, cap)| (denom.to_string(), Uint128::new(*cap)))
+                .collect(),
+        }
+    }
+
+    pub fn cap(&self, denom: &str) -> Uint128 {
+        self.caps.get(denom).copied().unwrap_or_default()
+    }
+
+    /// Tax the sender pays on top of `coin`
+    pub fn compute_tax(&self, coin: &Coin) -> Uint128 {
+        std::cmp::min(coin.amount * self.rate, self.cap(&coin.denom))
+    }
+}
+
+/// Custom handler for the Terra treasury queries, other custom messages are rejected
+pub struct TerraModule {
+    pub tax: TaxConfig,
+}
+
+impl CustomHandler<TerraMsgWrapper, TerraQueryWrapper> for TerraModule {
+    fn execute(
+        &self,
+        _api: &dyn Api,
+        _storage: &mut dyn Storage,
+        _block: &BlockInfo,
+        _sender: Addr,
+        msg: TerraMsgWrapper,
+    ) -> AnyResult<AppResponse> {
+        bail!("Unsupported terra message: {:?}", msg)
+    }
+
+    fn query(
+        &self,
+        _api: &dyn Api,
+        _storage: &dyn Storage,
+        _block: &BlockInfo,
+        request: TerraQueryWrapper,
+    ) -> AnyResult<Binary> {
+        match request.query_data {
+            TerraQuery::TaxRate {} => Ok(to_binary(&TaxRateResponse {
+                rate: self.tax.rate,
+            })?),
+            TerraQuery::TaxCap { denom } => Ok(to_binary(&TaxCapResponse {
+                cap: self.tax.cap(&denom),
+            })?),
+            query => bail!("Unsupported terra query: {:?}", query),
+        }
+    }
+}
+
+/// Bank that burns the tax from the sender on top of every `BankMsg::Send`
+pub struct TerraBank {
+    pub tax: TaxConfig,
+    inner: BankKeeper,
+}
+
+impl TerraBank {
+    pub fn new(tax: TaxConfig) -> Self {
+        TerraBank {
+            tax,
+            inner: BankKeeper::new(),
+        }
+    }
+}
+
+impl Bank for TerraBank {
+    fn execute(
+        &self,
+        storage: &mut dyn Storage,
+        sender: Addr,
+        msg: BankMsg,
+    ) -> AnyResult<AppResponse> {
+        if let BankMsg::Send { amount, .. } = &msg {
+            let tax = amount
+                .iter()
+                .map(|coin| Coin {
+                    denom: coin.denom.clone(),
+                    amount: self.tax.compute_tax(coin),
+                })
+                .filter(|coin| !coin.amount.is_zero())
+                .collect::<Vec<Coin>>();
+            if !tax.is_empty() {
+                self.inner
+                    .execute(storage, sender.clone(), BankMsg::Burn { amount: tax })?;
+            }
+        }
+        self.inner.execute(storage, sender, msg)
+    }
+
+    fn query(&self, api: &dyn Api, storage: &dyn Storage, request: BankQuery) -> AnyResult<Binary> {
+        self.inner.query(api, storage, request)
+    }
+
+    fn init_balance(
+        &self,
+        storage: &mut dyn Storage,
+        account: &Addr,
+        amount: Vec<Coin>,
+    ) -> AnyResult<()> {
+        self.inner.init_balance(storage, account, amount)
+    }
+}
+
+pub fn mock_terra_app(tax: TaxConfig) -> TerraApp {
+    AppBuilder::new()
+        .with_bank(TerraBank::new(tax.clone()))
+        .with_custom(TerraModule { tax })
+        .build()
+}
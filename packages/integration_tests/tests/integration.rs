@@ -0,0 +1,1007 @@
+//! End to end flows of the wormhole CW20, anchor_beth_token, anchor_beth_reward,
+//! anchor_beth_converter and the contracts built on top of them, deployed directly or by
+//! anchor_beth_factory, running together on a mocked Terra chain.
+
+use cosmwasm_std::{
+    coins, to_binary, Addr, BankMsg, Binary, Decimal, Deps, DepsMut, Empty, Env, MessageInfo,
+    Response, StdError, StdResult, Uint128,
+};
+use cw20::{BalanceResponse, Cw20Coin, Cw20ExecuteMsg, Cw20QueryMsg, MinterResponse};
+use cw_multi_test::{Contract, ContractWrapper, Executor};
+use terra_cosmwasm::TerraMsgWrapper;
+
+use anchor_beth_token::msg::TokenInstantiateMsg;
+use beth::airdrop::{ExecuteMsg as AirdropExecuteMsg, InstantiateMsg as AirdropInstantiateMsg};
+use beth::converter::{
+    ConfigResponse as ConverterConfigResponse, Cw20HookMsg, ExecuteMsg as ConverterExecuteMsg,
+    InstantiateMsg as ConverterInstantiateMsg, QueryMsg as ConverterQueryMsg,
+};
+use beth::custody::{
+    Cw20HookMsg as CustodyCw20HookMsg, ExecuteMsg as CustodyExecuteMsg,
+    InstantiateMsg as CustodyInstantiateMsg, QueryMsg as CustodyQueryMsg,
+};
+use beth::factory::{
+    DeploymentResponse, DeploymentsResponse, ExecuteMsg as FactoryExecuteMsg,
+    InstantiateMsg as FactoryInstantiateMsg, QueryMsg as FactoryQueryMsg,
+};
+use beth::reward::{
+    ConfigResponse as RewardConfigResponse, Cw20HookMsg as RewardCw20HookMsg,
+    ExecuteMsg as RewardExecuteMsg, HolderResponse, HoldersResponse,
+    InstantiateMsg as RewardInstantiateMsg, QueryMsg as RewardQueryMsg, StateResponse,
+};
+use beth::reward_dispatcher::{
+    ExecuteMsg as DispatcherExecuteMsg, InstantiateMsg as DispatcherInstantiateMsg,
+};
+use beth_airdrop_tree::Airdrop;
+use beth_integration_tests::{mock_terra_app, TaxConfig, TerraApp};
+
+const OWNER: &str = "owner0000";
+const HUB: &str = "hub0000";
+const TREASURY: &str = "treasury0000";
+const USER1: &str = "addr0001";
+const USER2: &str = "addr0002";
+const REWARD_DENOM: &str = "uusd";
+
+// wormhole bETH uses 8 decimals, anchor bETH uses 6 decimals
+const WORMHOLE_DECIMALS: u8 = 8;
+const ANCHOR_DECIMALS: u8 = 6;
+
+fn wormhole_token_contract() -> Box<dyn Contract<TerraMsgWrapper>> {
+    Box::new(ContractWrapper::new_with_empty(
+        cw20_base::contract::execute,
+        cw20_base::contract::instantiate,
+        cw20_base::contract::query,
+    ))
+}
+
+fn token_contract() -> Box<dyn Contract<TerraMsgWrapper>> {
+    Box::new(
+        ContractWrapper::new_with_empty(
+            anchor_beth_token::contract::execute,
+            anchor_beth_token::contract::instantiate,
+            anchor_beth_token::contract::query,
+        )
+        .with_reply_empty(anchor_beth_token::contract::reply),
+    )
+}
+
+fn converter_contract() -> Box<dyn Contract<TerraMsgWrapper>> {
+    Box::new(ContractWrapper::new_with_empty(
+        anchor_beth_converter::contract::execute,
+        anchor_beth_converter::contract::instantiate,
+        anchor_beth_converter::contract::query,
+    ))
+}
+
+// an observer that rejects every balance change hook
+fn failing_observer_contract() -> Box<dyn Contract<TerraMsgWrapper>> {
+    fn execute(_: DepsMut, _: Env, _: MessageInfo, _: Empty) -> StdResult<Response> {
+        Err(StdError::generic_err("observer error"))
+    }
+    fn instantiate(_: DepsMut, _: Env, _: MessageInfo, _: Empty) -> StdResult<Response> {
+        Ok(Response::new())
+    }
+    fn query(_: Deps, _: Env, _: Empty) -> StdResult<Binary> {
+        Err(StdError::generic_err("no queries"))
+    }
+    Box::new(ContractWrapper::new_with_empty(execute, instantiate, query))
+}
+
+// the reward contract only returns terra messages from execute
+fn reward_instantiate(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msg: RewardInstantiateMsg,
+) -> StdResult<Response<TerraMsgWrapper>> {
+    let res: Response<Empty> = anchor_beth_reward::contract::instantiate(deps, env, info, msg)?;
+    Ok(Response::new().add_attributes(res.attributes))
+}
+
+fn factory_contract() -> Box<dyn Contract<TerraMsgWrapper>> {
+    Box::new(
+        ContractWrapper::new_with_empty(
+            anchor_beth_factory::contract::execute,
+            anchor_beth_factory::contract::instantiate,
+            anchor_beth_factory::contract::query,
+        )
+        .with_reply_empty(anchor_beth_factory::contract::reply),
+    )
+}
+
+fn reward_contract() -> Box<dyn Contract<TerraMsgWrapper>> {
+    Box::new(ContractWrapper::new(
+        anchor_beth_reward::contract::execute,
+        reward_instantiate,
+        anchor_beth_reward::contract::query,
+    ))
+}
+
+fn instantiate_wormhole_token(app: &mut TerraApp, code_id: u64, symbol: &str) -> Addr {
+    app.instantiate_contract(
+        code_id,
+        Addr::unchecked(OWNER),
+        &cw20_base::msg::InstantiateMsg {
+            name: format!("{} (Wormhole)", symbol),
+            symbol: symbol.to_string(),
+            decimals: WORMHOLE_DECIMALS,
+            initial_balances: vec![
+                Cw20Coin {
+                    address: USER1.to_string(),
+                    amount: Uint128::new(1_000_000_000),
+                },
+                Cw20Coin {
+                    address: USER2.to_string(),
+                    amount: Uint128::new(1_000_000_000),
+                },
+            ],
+            mint: None,
+            marketing: None,
+        },
+        &[],
+        format!("wormhole {}", symbol),
+        None,
+    )
+    .unwrap()
+}
+
+// the dispatcher only returns terra messages from execute
+fn dispatcher_instantiate(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msg: DispatcherInstantiateMsg,
+) -> StdResult<Response<TerraMsgWrapper>> {
+    let res: Response<Empty> =
+        anchor_beth_reward_dispatcher::contract::instantiate(deps, env, info, msg)?;
+    Ok(Response::new().add_attributes(res.attributes))
+}
+
+fn dispatcher_contract() -> Box<dyn Contract<TerraMsgWrapper>> {
+    Box::new(ContractWrapper::new(
+        anchor_beth_reward_dispatcher::contract::execute,
+        dispatcher_instantiate,
+        anchor_beth_reward_dispatcher::contract::query,
+    ))
+}
+
+fn custody_contract() -> Box<dyn Contract<TerraMsgWrapper>> {
+    Box::new(ContractWrapper::new_with_empty(
+        anchor_beth_custody::contract::execute,
+        anchor_beth_custody::contract::instantiate,
+        anchor_beth_custody::contract::query,
+    ))
+}
+
+fn airdrop_contract() -> Box<dyn Contract<TerraMsgWrapper>> {
+    Box::new(ContractWrapper::new_with_empty(
+        anchor_beth_airdrop::contract::execute,
+        anchor_beth_airdrop::contract::instantiate,
+        anchor_beth_airdrop::contract::query,
+    ))
+}
+
+struct Suite {
+    app: TerraApp,
+    wormhole_token: Addr,
+    token: Addr,
+    reward: Addr,
+    converter: Addr,
+}
+
+impl Suite {
+    fn new(tax: TaxConfig) -> Self {
+        let mut app = mock_terra_app(tax);
+        let owner = Addr::unchecked(OWNER);
+
+        let wormhole_token_id = app.store_code(wormhole_token_contract());
+        let token_id = app.store_code(token_contract());
+        let reward_id = app.store_code(reward_contract());
+        let converter_id = app.store_code(converter_contract());
+
+        let wormhole_token = instantiate_wormhole_token(&mut app, wormhole_token_id, "WETH");
+
+        let converter = app
+            .instantiate_contract(
+                converter_id,
+                owner.clone(),
+                &ConverterInstantiateMsg {
+                    owner: OWNER.to_string(),
+                },
+                &[],
+                "converter",
+                None,
+            )
+            .unwrap();
+
+        let reward = app
+            .instantiate_contract(
+                reward_id,
+                owner.clone(),
+                &RewardInstantiateMsg {
+                    owner: OWNER.to_string(),
+                    reward_denom: REWARD_DENOM.to_string(),
+                },
+                &[],
+                "reward",
+                None,
+            )
+            .unwrap();
+
+        let token = app
+            .instantiate_contract(
+                token_id,
+                owner.clone(),
+                &TokenInstantiateMsg {
+                    name: "bETH".to_string(),
+                    symbol: "BETH".to_string(),
+                    decimals: ANCHOR_DECIMALS,
+                    initial_balances: vec![],
+                    mint: Some(MinterResponse {
+                        minter: converter.to_string(),
+                        cap: None,
+                    }),
+                    reward_contract: reward.to_string(),
+                    admin: Some(OWNER.to_string()),
+                    marketing: None,
+                },
+                &[],
+                "beth",
+                None,
+            )
+            .unwrap();
+
+        app.execute_contract(
+            owner.clone(),
+            reward.clone(),
+            &RewardExecuteMsg::PostInitialize {
+                token_contract: token.to_string(),
+            },
+            &[],
+        )
+        .unwrap();
+        app.execute_contract(
+            owner,
+            converter.clone(),
+            &ConverterExecuteMsg::RegisterTokens {
+                wormhole_token_address: wormhole_token.to_string(),
+                anchor_token_address: token.to_string(),
+            },
+            &[],
+        )
+        .unwrap();
+
+        Suite {
+            app,
+            wormhole_token,
+            token,
+            reward,
+            converter,
+        }
+    }
+
+    fn convert_to_anchor(&mut self, sender: &str, amount: u128) {
+        self.app
+            .execute_contract(
+                Addr::unchecked(sender),
+                self.wormhole_token.clone(),
+                &Cw20ExecuteMsg::Send {
+                    contract: self.converter.to_string(),
+                    amount: Uint128::new(amount),
+                    msg: to_binary(&Cw20HookMsg::ConvertWormholeToAnchor {}).unwrap(),
+                },
+                &[],
+            )
+            .unwrap();
+    }
+
+    fn convert_to_wormhole(&mut self, sender: &str, amount: u128) {
+        self.app
+            .execute_contract(
+                Addr::unchecked(sender),
+                self.token.clone(),
+                &Cw20ExecuteMsg::Send {
+                    contract: self.converter.to_string(),
+                    amount: Uint128::new(amount),
+                    msg: to_binary(&Cw20HookMsg::ConvertAnchorToWormhole {}).unwrap(),
+                },
+                &[],
+            )
+            .unwrap();
+    }
+
+    fn transfer(&mut self, sender: &str, recipient: &str, amount: u128) {
+        self.app
+            .execute_contract(
+                Addr::unchecked(sender),
+                self.token.clone(),
+                &Cw20ExecuteMsg::Transfer {
+                    recipient: recipient.to_string(),
+                    amount: Uint128::new(amount),
+                },
+                &[],
+            )
+            .unwrap();
+    }
+
+    fn deposit_rewards(&mut self, amount: u128) {
+        let hub = Addr::unchecked(HUB);
+        self.app
+            .init_bank_balance(&hub, coins(amount * 2, REWARD_DENOM))
+            .unwrap();
+        self.app
+            .execute(
+                hub,
+                BankMsg::Send {
+                    to_address: self.reward.to_string(),
+                    amount: coins(amount, REWARD_DENOM),
+                }
+                .into(),
+            )
+            .unwrap();
+    }
+
+    fn claim_rewards(&mut self, sender: &str) {
+        self.app
+            .execute_contract(
+                Addr::unchecked(sender),
+                self.reward.clone(),
+                &RewardExecuteMsg::ClaimRewards { recipient: None },
+                &[],
+            )
+            .unwrap();
+    }
+
+    fn cw20_balance(&self, token: &Addr, address: &str) -> u128 {
+        let res: BalanceResponse = self
+            .app
+            .wrap()
+            .query_wasm_smart(
+                token,
+                &Cw20QueryMsg::Balance {
+                    address: address.to_string(),
+                },
+            )
+            .unwrap();
+        res.balance.u128()
+    }
+
+    fn beth_balance(&self, address: &str) -> u128 {
+        self.cw20_balance(&self.token, address)
+    }
+
+    fn wormhole_balance(&self, address: &str) -> u128 {
+        self.cw20_balance(&self.wormhole_token, address)
+    }
+
+    fn native_balance(&self, address: &str) -> u128 {
+        self.app
+            .wrap()
+            .query_balance(address, REWARD_DENOM)
+            .unwrap()
+            .amount
+            .u128()
+    }
+
+    fn holder(&self, address: &str) -> HolderResponse {
+        self.app
+            .wrap()
+            .query_wasm_smart(
+                &self.reward,
+                &RewardQueryMsg::Holder {
+                    address: address.to_string(),
+                },
+            )
+            .unwrap()
+    }
+
+    fn reward_state(&self) -> StateResponse {
+        self.app
+            .wrap()
+            .query_wasm_smart(&self.reward, &RewardQueryMsg::State {})
+            .unwrap()
+    }
+
+    /// reward balances must always mirror the token balances
+    fn assert_holder_balance(&self, address: &str, expected: u128) {
+        assert_eq!(self.beth_balance(address), expected);
+        assert_eq!(self.holder(address).balance.u128(), expected);
+    }
+}
+
+fn default_tax() -> TaxConfig {
+    TaxConfig::new(Decimal::percent(1), &[(REWARD_DENOM, 1_000_000)])
+}
+
+#[test]
+fn convert_and_convert_back() {
+    let mut suite = Suite::new(default_tax());
+
+    suite.convert_to_anchor(USER1, 1_000_000_000);
+    assert_eq!(suite.wormhole_balance(USER1), 0);
+    assert_eq!(
+        suite.wormhole_balance(suite.converter.as_str()),
+        1_000_000_000
+    );
+    suite.assert_holder_balance(USER1, 10_000_000);
+    assert_eq!(suite.reward_state().total_balance.u128(), 10_000_000);
+
+    suite.convert_to_wormhole(USER1, 4_000_000);
+    assert_eq!(suite.wormhole_balance(USER1), 400_000_000);
+    suite.assert_holder_balance(USER1, 6_000_000);
+    // the converter burns what it receives
+    suite.assert_holder_balance(suite.converter.as_str(), 0);
+    assert_eq!(suite.reward_state().total_balance.u128(), 6_000_000);
+}
+
+#[test]
+fn transfer_and_send_move_reward_balances() {
+    let mut suite = Suite::new(default_tax());
+
+    suite.convert_to_anchor(USER1, 1_000_000_000);
+    suite.transfer(USER1, USER2, 2_500_000);
+    suite.assert_holder_balance(USER1, 7_500_000);
+    suite.assert_holder_balance(USER2, 2_500_000);
+
+    // send to a contract goes through the converter receive hook
+    suite.convert_to_wormhole(USER2, 500_000);
+    suite.assert_holder_balance(USER2, 2_000_000);
+    assert_eq!(suite.wormhole_balance(USER2), 1_050_000_000);
+
+    suite
+        .app
+        .execute_contract(
+            Addr::unchecked(USER1),
+            suite.token.clone(),
+            &anchor_beth_token::msg::ExecuteMsg::BatchTransfer {
+                transfers: vec![
+                    (USER2.to_string(), Uint128::new(1_000_000)),
+                    ("addr0003".to_string(), Uint128::new(500_000)),
+                ],
+            },
+            &[],
+        )
+        .unwrap();
+    suite.assert_holder_balance(USER1, 6_000_000);
+    suite.assert_holder_balance(USER2, 3_000_000);
+    suite.assert_holder_balance("addr0003", 500_000);
+    assert_eq!(suite.reward_state().total_balance.u128(), 9_500_000);
+}
+
+#[test]
+fn failing_observer_does_not_block_transfers() {
+    let mut suite = Suite::new(default_tax());
+    let observer_id = suite.app.store_code(failing_observer_contract());
+    let observer = suite
+        .app
+        .instantiate_contract(
+            observer_id,
+            Addr::unchecked(OWNER),
+            &Empty {},
+            &[],
+            "observer",
+            None,
+        )
+        .unwrap();
+    suite
+        .app
+        .execute_contract(
+            Addr::unchecked(OWNER),
+            suite.token.clone(),
+            &anchor_beth_token::msg::ExecuteMsg::AddObserver {
+                address: observer.to_string(),
+            },
+            &[],
+        )
+        .unwrap();
+
+    suite.convert_to_anchor(USER1, 1_000_000_000);
+    suite.transfer(USER1, USER2, 2_500_000);
+    assert_eq!(suite.beth_balance(USER1), 7_500_000);
+    assert_eq!(suite.beth_balance(USER2), 2_500_000);
+    suite.assert_holder_balance(USER1, 7_500_000);
+    suite.assert_holder_balance(USER2, 2_500_000);
+}
+
+#[test]
+fn claim_rewards_net_of_tax() {
+    let mut suite = Suite::new(default_tax());
+
+    suite.convert_to_anchor(USER1, 1_000_000_000);
+    suite.transfer(USER1, USER2, 2_500_000);
+    suite.deposit_rewards(1_000_000);
+    assert_eq!(suite.native_balance(suite.reward.as_str()), 1_000_000);
+
+    // 750000 uusd accrued, the contract sends 742574 and pays 7425 of tax
+    suite.claim_rewards(USER1);
+    assert_eq!(suite.native_balance(USER1), 742_574);
+    assert_eq!(suite.native_balance(suite.reward.as_str()), 250_001);
+
+    // 250000 uusd accrued, the contract sends 247524 and pays 2475 of tax
+    suite.claim_rewards(USER2);
+    assert_eq!(suite.native_balance(USER2), 247_524);
+    // the tax is rounded down, the dust stays in the reward contract
+    assert_eq!(suite.native_balance(suite.reward.as_str()), 2);
+}
+
+#[test]
+fn rewards_follow_converted_balances() {
+    let mut suite = Suite::new(default_tax());
+
+    suite.convert_to_anchor(USER1, 1_000_000_000);
+    suite.deposit_rewards(100_000);
+
+    // converting back does not forfeit the rewards accrued before
+    suite.convert_to_anchor(USER2, 1_000_000_000);
+    suite.convert_to_wormhole(USER1, 10_000_000);
+    suite.assert_holder_balance(USER1, 0);
+
+    suite.deposit_rewards(100_000);
+
+    // 100000 * 100 / 101 = 99009
+    suite.claim_rewards(USER1);
+    assert_eq!(suite.native_balance(USER1), 99_009);
+
+    // the uusd left by the rounding of the tax of the first claim is distributed as rewards
+    suite.claim_rewards(USER2);
+    assert_eq!(suite.native_balance(USER2), 99_010);
+    assert_eq!(suite.wormhole_balance(USER1), 1_000_000_000);
+}
+
+#[test]
+fn dispatcher_forwards_rewards_net_of_fee() {
+    let mut suite = Suite::new(default_tax());
+    suite.convert_to_anchor(USER1, 1_000_000_000);
+
+    let dispatcher_id = suite.app.store_code(dispatcher_contract());
+    let dispatcher = suite
+        .app
+        .instantiate_contract(
+            dispatcher_id,
+            Addr::unchecked(OWNER),
+            &DispatcherInstantiateMsg {
+                owner: OWNER.to_string(),
+                reward_contract: suite.reward.to_string(),
+                treasury: TREASURY.to_string(),
+                reward_denom: REWARD_DENOM.to_string(),
+                protocol_fee: Decimal::percent(10),
+                max_spread: None,
+            },
+            &[],
+            "dispatcher",
+            None,
+        )
+        .unwrap();
+
+    let hub = Addr::unchecked(HUB);
+    suite
+        .app
+        .init_bank_balance(&hub, coins(2_000_000, REWARD_DENOM))
+        .unwrap();
+    suite
+        .app
+        .execute(
+            hub.clone(),
+            BankMsg::Send {
+                to_address: dispatcher.to_string(),
+                amount: coins(1_000_000, REWARD_DENOM),
+            }
+            .into(),
+        )
+        .unwrap();
+    suite
+        .app
+        .execute_contract(
+            hub,
+            dispatcher.clone(),
+            &DispatcherExecuteMsg::DispatchRewards {},
+            &[],
+        )
+        .unwrap();
+
+    // 100000 uusd of fee, the treasury gets 99009 and 990 is paid as tax
+    assert_eq!(suite.native_balance(TREASURY), 99_009);
+    // 900000 uusd of rewards, the reward contract gets 891089 and 8910 is paid as tax
+    assert_eq!(suite.native_balance(suite.reward.as_str()), 891_089);
+    assert_eq!(suite.native_balance(dispatcher.as_str()), 2);
+
+    // the deposit moves the index without waiting for a balance change
+    let state = suite.reward_state();
+    assert_eq!(state.prev_reward_balance.u128(), 891_089);
+    assert_eq!(state.global_index.to_string(), "0.0891089");
+}
+
+#[test]
+fn custody_passes_rewards_to_borrowers() {
+    let mut suite = Suite::new(default_tax());
+    suite.convert_to_anchor(USER1, 1_000_000_000);
+    suite.transfer(USER1, USER2, 2_500_000);
+
+    let custody_id = suite.app.store_code(custody_contract());
+    let custody = suite
+        .app
+        .instantiate_contract(
+            custody_id,
+            Addr::unchecked(OWNER),
+            &CustodyInstantiateMsg {
+                owner: OWNER.to_string(),
+                collateral_token: suite.token.to_string(),
+                overseer: OWNER.to_string(),
+                reward_contract: suite.reward.to_string(),
+                reward_denom: REWARD_DENOM.to_string(),
+            },
+            &[],
+            "custody",
+            None,
+        )
+        .unwrap();
+
+    let deposit_collateral = |suite: &mut Suite, sender: &str, amount: u128| {
+        suite
+            .app
+            .execute_contract(
+                Addr::unchecked(sender),
+                suite.token.clone(),
+                &Cw20ExecuteMsg::Send {
+                    contract: custody.to_string(),
+                    amount: Uint128::new(amount),
+                    msg: to_binary(&CustodyCw20HookMsg::DepositCollateral {}).unwrap(),
+                },
+                &[],
+            )
+            .unwrap();
+    };
+    deposit_collateral(&mut suite, USER1, 6_000_000);
+    deposit_collateral(&mut suite, USER2, 2_000_000);
+    suite.assert_holder_balance(custody.as_str(), 8_000_000);
+
+    // the custody holds 80% of the supply and accrues 800000 uusd
+    suite.deposit_rewards(1_000_000);
+    suite
+        .app
+        .execute_contract(
+            Addr::unchecked(USER1),
+            custody.clone(),
+            &CustodyExecuteMsg::DistributeRewards {},
+            &[],
+        )
+        .unwrap();
+    // the reward contract sends 792079 and pays 7920 of tax
+    assert_eq!(suite.native_balance(custody.as_str()), 792_079);
+    assert_eq!(suite.native_balance(suite.reward.as_str()), 200_001);
+
+    // collateral deposited after the distribution does not share the rewards
+    deposit_collateral(&mut suite, USER2, 500_000);
+    suite.assert_holder_balance(custody.as_str(), 8_500_000);
+
+    let claim_rewards = |suite: &mut Suite, sender: &str| {
+        suite
+            .app
+            .execute_contract(
+                Addr::unchecked(sender),
+                custody.clone(),
+                &CustodyExecuteMsg::ClaimRewards { recipient: None },
+                &[],
+            )
+            .unwrap();
+    };
+
+    // 594059 uusd accrued, the custody sends 588177 and pays 5881 of tax
+    claim_rewards(&mut suite, USER1);
+    assert_eq!(suite.native_balance(USER1), 588_177);
+
+    // 198019 uusd accrued, the custody sends 196058 and pays 1960 of tax
+    claim_rewards(&mut suite, USER2);
+    assert_eq!(suite.native_balance(USER2), 196_058);
+    // the tax is rounded down, the dust stays in the custody
+    assert_eq!(suite.native_balance(custody.as_str()), 3);
+
+    let res: beth::custody::StateResponse = suite
+        .app
+        .wrap()
+        .query_wasm_smart(&custody, &CustodyQueryMsg::State {})
+        .unwrap();
+    assert_eq!(res.total_collateral.u128(), 8_500_000);
+
+    // withdrawn collateral leaves the custody balance in the reward contract
+    suite
+        .app
+        .execute_contract(
+            Addr::unchecked(USER1),
+            custody.clone(),
+            &CustodyExecuteMsg::WithdrawCollateral { amount: None },
+            &[],
+        )
+        .unwrap();
+    suite.assert_holder_balance(USER1, 7_500_000);
+    suite.assert_holder_balance(custody.as_str(), 2_500_000);
+}
+
+#[test]
+fn airdrop_to_reward_holders() {
+    let mut suite = Suite::new(default_tax());
+    suite.convert_to_anchor(USER1, 1_000_000_000);
+    suite.transfer(USER1, USER2, 2_500_000);
+
+    let holders: HoldersResponse = suite
+        .app
+        .wrap()
+        .query_wasm_smart(
+            &suite.reward,
+            &RewardQueryMsg::Holders {
+                start_after: None,
+                start_before: None,
+                limit: None,
+                order: None,
+            },
+        )
+        .unwrap();
+    let airdrop = Airdrop::from_holders(&holders.holders, Some(Uint128::new(1_000_000))).unwrap();
+    assert_eq!(airdrop.total_amount.u128(), 1_000_000);
+
+    let owner = Addr::unchecked(OWNER);
+    let airdrop_id = suite.app.store_code(airdrop_contract());
+    let distributor = suite
+        .app
+        .instantiate_contract(
+            airdrop_id,
+            owner.clone(),
+            &AirdropInstantiateMsg {
+                owner: OWNER.to_string(),
+            },
+            &[],
+            "airdrop",
+            None,
+        )
+        .unwrap();
+    suite
+        .app
+        .init_bank_balance(&owner, coins(2_000_000, REWARD_DENOM))
+        .unwrap();
+    suite
+        .app
+        .execute_contract(
+            owner,
+            distributor.clone(),
+            &AirdropExecuteMsg::RegisterMerkleRoot {
+                merkle_root: airdrop.merkle_root.clone(),
+                expiration: None,
+            },
+            &coins(airdrop.total_amount.u128(), REWARD_DENOM),
+        )
+        .unwrap();
+
+    for claim in airdrop.claims.iter() {
+        suite
+            .app
+            .execute_contract(
+                Addr::unchecked(&claim.address),
+                distributor.clone(),
+                &AirdropExecuteMsg::Claim {
+                    stage: 1,
+                    amount: claim.amount,
+                    proof: claim.proof.clone(),
+                },
+                &[],
+            )
+            .unwrap();
+    }
+
+    // 750000 and 250000 uusd are airdropped net of tax
+    assert_eq!(suite.native_balance(USER1), 742_574);
+    assert_eq!(suite.native_balance(USER2), 247_524);
+    assert_eq!(suite.native_balance(distributor.as_str()), 2);
+}
+
+#[test]
+fn factory_deploys_wired_asset_sets() {
+    let mut app = mock_terra_app(default_tax());
+    let owner = Addr::unchecked(OWNER);
+
+    let wormhole_token_id = app.store_code(wormhole_token_contract());
+    let token_id = app.store_code(token_contract());
+    let reward_id = app.store_code(reward_contract());
+    let converter_id = app.store_code(converter_contract());
+    let factory_id = app.store_code(factory_contract());
+    let factory = app
+        .instantiate_contract(
+            factory_id,
+            owner.clone(),
+            &FactoryInstantiateMsg {
+                owner: OWNER.to_string(),
+                token_code_id: token_id,
+                reward_code_id: reward_id,
+                converter_code_id: converter_id,
+            },
+            &[],
+            "factory",
+            None,
+        )
+        .unwrap();
+
+    for (asset_name, symbol) in [("beth", "WETH"), ("bsol", "WSOL")] {
+        let wormhole_token = instantiate_wormhole_token(&mut app, wormhole_token_id, symbol);
+        app.execute_contract(
+            owner.clone(),
+            factory.clone(),
+            &FactoryExecuteMsg::Deploy {
+                asset_name: asset_name.to_string(),
+                wormhole_token: wormhole_token.to_string(),
+                token_name: asset_name.to_string(),
+                token_symbol: asset_name.to_uppercase(),
+                token_decimals: ANCHOR_DECIMALS,
+                reward_denom: REWARD_DENOM.to_string(),
+            },
+            &[],
+        )
+        .unwrap();
+
+        let deployment: DeploymentResponse = app
+            .wrap()
+            .query_wasm_smart(
+                &factory,
+                &FactoryQueryMsg::Deployment {
+                    asset_name: asset_name.to_string(),
+                },
+            )
+            .unwrap();
+        assert_eq!(deployment.wormhole_token, wormhole_token.to_string());
+
+        let reward_config: RewardConfigResponse = app
+            .wrap()
+            .query_wasm_smart(&deployment.reward_contract, &RewardQueryMsg::Config {})
+            .unwrap();
+        assert_eq!(reward_config.owner, OWNER);
+
+        let converter_config: ConverterConfigResponse = app
+            .wrap()
+            .query_wasm_smart(
+                &deployment.converter_contract,
+                &ConverterQueryMsg::Config {},
+            )
+            .unwrap();
+        assert_eq!(
+            converter_config.anchor_token_address,
+            Some(deployment.anchor_token.clone())
+        );
+
+        let minter: MinterResponse = app
+            .wrap()
+            .query_wasm_smart(&deployment.anchor_token, &Cw20QueryMsg::Minter {})
+            .unwrap();
+        assert_eq!(minter.minter, deployment.converter_contract);
+
+        // the deployed set converts and tracks reward balances like a manual deployment
+        let mut suite = Suite {
+            app,
+            wormhole_token,
+            token: Addr::unchecked(deployment.anchor_token),
+            reward: Addr::unchecked(deployment.reward_contract),
+            converter: Addr::unchecked(deployment.converter_contract),
+        };
+        suite.convert_to_anchor(USER1, 1_000_000_000);
+        suite.assert_holder_balance(USER1, 10_000_000);
+        assert_eq!(suite.reward_state().total_balance.u128(), 10_000_000);
+        app = suite.app;
+    }
+
+    let res: DeploymentsResponse = app
+        .wrap()
+        .query_wasm_smart(
+            &factory,
+            &FactoryQueryMsg::Deployments {
+                start_after: None,
+                limit: None,
+            },
+        )
+        .unwrap();
+    let asset_names: Vec<String> = res.deployments.into_iter().map(|d| d.asset_name).collect();
+    assert_eq!(asset_names, vec!["beth".to_string(), "bsol".to_string()]);
+}
+
+#[test]
+fn locked_balances_earn_boosted_rewards() {
+    let mut suite = Suite::new(default_tax());
+
+    suite.convert_to_anchor(USER1, 1_000_000_000);
+    suite.transfer(USER1, USER2, 5_000_000);
+    suite
+        .app
+        .execute_contract(
+            Addr::unchecked(OWNER),
+            suite.reward.clone(),
+            &RewardExecuteMsg::UpdateLockConfig {
+                max_lock_duration: Some(1000),
+                max_boost: Some(Decimal::one()),
+                early_unlock_penalty: Some(Decimal::percent(50)),
+            },
+            &[],
+        )
+        .unwrap();
+
+    // a lock for the max duration doubles the reward weight
+    suite
+        .app
+        .execute_contract(
+            Addr::unchecked(USER1),
+            suite.token.clone(),
+            &Cw20ExecuteMsg::Send {
+                contract: suite.reward.to_string(),
+                amount: Uint128::new(5_000_000),
+                msg: to_binary(&RewardCw20HookMsg::Lock { duration: 1000 }).unwrap(),
+            },
+            &[],
+        )
+        .unwrap();
+    suite.assert_holder_balance(USER1, 0);
+    assert_eq!(suite.beth_balance(suite.reward.as_str()), 5_000_000);
+    assert_eq!(suite.holder(suite.reward.as_str()).balance.u128(), 0);
+    let state = suite.reward_state();
+    assert_eq!(state.total_balance.u128(), 5_000_000);
+    assert_eq!(state.total_locked.u128(), 5_000_000);
+    assert_eq!(state.total_effective_balance.u128(), 15_000_000);
+
+    // 1000000 and 500000 uusd accrued, sent net of tax
+    suite.deposit_rewards(1_500_000);
+    suite.claim_rewards(USER1);
+    assert_eq!(suite.native_balance(USER1), 990_099);
+    suite.claim_rewards(USER2);
+    assert_eq!(suite.native_balance(USER2), 495_049);
+
+    suite
+        .app
+        .update_block(|block| block.time = block.time.plus_seconds(1000));
+    suite
+        .app
+        .execute_contract(
+            Addr::unchecked(USER1),
+            suite.reward.clone(),
+            &RewardExecuteMsg::Unlock { lock_id: 1 },
+            &[],
+        )
+        .unwrap();
+    suite.assert_holder_balance(USER1, 5_000_000);
+    assert_eq!(suite.beth_balance(suite.reward.as_str()), 0);
+    let state = suite.reward_state();
+    assert_eq!(state.total_balance.u128(), 10_000_000);
+    assert_eq!(state.total_effective_balance.u128(), 10_000_000);
+}
+
+#[test]
+fn protocol_fee_goes_to_treasury() {
+    let mut suite = Suite::new(default_tax());
+
+    suite.convert_to_anchor(USER1, 1_000_000_000);
+    suite
+        .app
+        .execute_contract(
+            Addr::unchecked(OWNER),
+            suite.reward.clone(),
+            &RewardExecuteMsg::UpdateConfig {
+                owner: None,
+                protocol_fee_rate: Some(Decimal::percent(10)),
+                treasury: Some(TREASURY.to_string()),
+            },
+            &[],
+        )
+        .unwrap();
+
+    // 900000 uusd accrued, the contract sends 891089 and pays 8910 of tax
+    suite.deposit_rewards(1_000_000);
+    suite.claim_rewards(USER1);
+    assert_eq!(suite.native_balance(USER1), 891_089);
+
+    // 100000 uusd of fee, the contract sends 99009 and pays 990 of tax
+    suite
+        .app
+        .execute_contract(
+            Addr::unchecked(TREASURY),
+            suite.reward.clone(),
+            &RewardExecuteMsg::WithdrawProtocolFee {},
+            &[],
+        )
+        .unwrap();
+    assert_eq!(suite.native_balance(TREASURY), 99_009);
+    assert_eq!(suite.native_balance(suite.reward.as_str()), 2);
+    assert_eq!(suite.reward_state().protocol_fee.u128(), 0);
+}